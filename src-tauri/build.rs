@@ -1,3 +1,92 @@
+/// Every command in `generate_handler!`. Declaring them makes each one need an `allow-*`
+/// permission: `capabilities/default.json` grants the panel the ones it uses, and
+/// `capabilities/content.json` lets web pages in tabs report only their own state.
+const COMMANDS: &[&str] = &[
+    "create_content_window",
+    "create_private_window",
+    "navigate_to_url",
+    "go_back",
+    "go_forward",
+    "reload_page",
+    "show_browser_window",
+    "hide_browser_window",
+    "close_browser_window",
+    "minimize_browser_window",
+    "toggle_maximize_browser_window",
+    "get_page_state",
+    "report_page_info",
+    "report_scroll_position",
+    "get_tabs",
+    "create_tab",
+    "close_tab",
+    "activate_tab",
+    "move_tab",
+    "enter_split_view",
+    "leave_split_view",
+    "swap_split_panes",
+    "resize_split_divider",
+    "titlebar_minimize",
+    "titlebar_maximize",
+    "titlebar_close",
+    "titlebar_drag",
+    "get_search_suggestions",
+    "get_search_engines",
+    "add_search_engine",
+    "update_search_engine",
+    "remove_search_engine",
+    "get_search_engine_offers",
+    "accept_search_engine_offer",
+    "ignore_search_engine_offer",
+    "resolve_omnibox_input",
+    "query_history",
+    "delete_history_entry",
+    "delete_history_range",
+    "clear_history",
+    "get_bookmarks",
+    "add_bookmark",
+    "update_bookmark",
+    "delete_bookmark",
+    "reorder_bookmarks",
+    "set_bookmark_starred",
+    "set_bookmark_tags",
+    "list_bookmark_tags",
+    "create_bookmark_folder",
+    "rename_bookmark_folder",
+    "delete_bookmark_folder",
+    "move_bookmark_item",
+    "get_bookmark_folder",
+    "query_bookmarks",
+    "clear_bookmarks",
+    "import_bookmarks_file",
+    "export_bookmarks_html",
+    "get_settings",
+    "set_setting",
+    "reset_settings",
+    "open_blocked_popup",
+    "allow_popups_for_site",
+    "get_session_restore_offer",
+    "restore_last_session",
+    "dismiss_session_restore",
+    "save_session",
+    "list_sessions",
+    "restore_session",
+    "delete_session",
+    "list_closed_windows",
+    "reopen_closed_window",
+    "list_profiles",
+    "create_profile",
+    "rename_profile",
+    "delete_profile",
+    "switch_profile",
+    "get_containers",
+    "create_container",
+    "update_container",
+    "delete_container",
+];
+
 fn main() {
-    tauri_build::build()
+    tauri_build::try_build(
+        tauri_build::Attributes::new().app_manifest(tauri_build::AppManifest::new().commands(COMMANDS)),
+    )
+    .expect("failed to run tauri-build");
 }
@@ -0,0 +1,537 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::{netscape, normalize_tags, validate_url, Bookmark, BookmarkCollection, BookmarkFolder};
+use crate::clock::now_millis;
+use crate::storage;
+
+/// A folder or bookmark read from another browser, before validation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportNode {
+    Folder {
+        name: String,
+        created_at: Option<i64>,
+        children: Vec<ImportNode>,
+    },
+    Bookmark {
+        name: String,
+        url: String,
+        favicon: Option<String>,
+        tags: Vec<String>,
+        /// Only Meikai's own exports carry this
+        starred: bool,
+        created_at: Option<i64>,
+        last_visited_at: Option<i64>,
+    },
+}
+
+/// Supported bookmark sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    /// `bookmarks.html`, exported by every browser
+    NetscapeHtml,
+    /// Chromium's `Bookmarks` file (Chrome, Edge, Brave, ...)
+    ChromiumJson,
+    /// Firefox's `places.sqlite`
+    FirefoxPlaces,
+}
+
+/// An entry that was skipped during import
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedBookmark {
+    pub name: String,
+    pub url: String,
+    /// Folder names from the import root down to the entry's folder
+    pub path: Vec<String>,
+    /// Why it was skipped (only set for invalid URLs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Outcome of an import. Problem entries are reported here instead of failing the import.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub imported: usize,
+    pub folders_created: usize,
+    /// URLs that were already bookmarked (or appeared twice in the file)
+    pub duplicates: Vec<SkippedBookmark>,
+    pub invalid: Vec<SkippedBookmark>,
+}
+
+/// Guess the format from the file name and contents
+pub fn detect_format(path: &Path, head: &[u8]) -> Option<ImportFormat> {
+    if head.starts_with(b"SQLite format 3\0") {
+        return Some(ImportFormat::FirefoxPlaces);
+    }
+    let text = String::from_utf8_lossy(head);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with('{') {
+        return Some(ImportFormat::ChromiumJson);
+    }
+    if text.starts_with('<') || text.to_ascii_lowercase().contains("netscape-bookmark-file") {
+        return Some(ImportFormat::NetscapeHtml);
+    }
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "html" | "htm" => Some(ImportFormat::NetscapeHtml),
+        "json" => Some(ImportFormat::ChromiumJson),
+        "sqlite" => Some(ImportFormat::FirefoxPlaces),
+        _ => None,
+    }
+}
+
+/// Read a bookmark file into a folder tree
+pub fn read_file(path: &Path, format: Option<ImportFormat>) -> Result<Vec<ImportNode>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let format = format
+        .or_else(|| detect_format(path, &bytes[..bytes.len().min(512)]))
+        .ok_or_else(|| "Unrecognized bookmark file format".to_string())?;
+
+    match format {
+        ImportFormat::NetscapeHtml => Ok(netscape::parse(&String::from_utf8_lossy(&bytes))),
+        ImportFormat::ChromiumJson => parse_chromium(&String::from_utf8_lossy(&bytes)),
+        ImportFormat::FirefoxPlaces => read_firefox_places(path),
+    }
+}
+
+/// Chromium stores times as microseconds since 1601-01-01
+fn chromium_time(value: Option<&serde_json::Value>) -> Option<i64> {
+    const EPOCH_OFFSET_MILLIS: i64 = 11_644_473_600_000;
+    let micros: i64 = value?.as_str()?.parse().ok()?;
+    (micros > 0).then(|| micros / 1000 - EPOCH_OFFSET_MILLIS)
+}
+
+fn chromium_node(value: &serde_json::Value) -> Option<ImportNode> {
+    let name = value.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string();
+    let created_at = chromium_time(value.get("date_added"));
+
+    match value.get("type").and_then(|t| t.as_str())? {
+        "folder" => Some(ImportNode::Folder {
+            name,
+            created_at,
+            children: value
+                .get("children")
+                .and_then(|c| c.as_array())
+                .map(|children| children.iter().filter_map(chromium_node).collect())
+                .unwrap_or_default(),
+        }),
+        "url" => Some(ImportNode::Bookmark {
+            name,
+            url: value.get("url").and_then(|u| u.as_str()).unwrap_or_default().to_string(),
+            favicon: None,
+            tags: Vec::new(),
+            starred: false,
+            created_at,
+            last_visited_at: chromium_time(value.get("date_last_used")),
+        }),
+        _ => None,
+    }
+}
+
+/// Parse Chromium's `Bookmarks` JSON. Each root (bookmarks bar, other, mobile) becomes a folder.
+pub fn parse_chromium(json: &str) -> Result<Vec<ImportNode>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid Chromium bookmarks file: {}", e))?;
+    let roots = value
+        .get("roots")
+        .and_then(|r| r.as_object())
+        .ok_or_else(|| "Invalid Chromium bookmarks file: missing roots".to_string())?;
+
+    // Keep the browser's own order; anything else in `roots` (e.g. sync metadata) is skipped
+    Ok(["bookmark_bar", "other", "synced"]
+        .iter()
+        .filter_map(|key| roots.get(*key))
+        .filter_map(chromium_node)
+        .filter(|node| !matches!(node, ImportNode::Folder { children, .. } if children.is_empty()))
+        .collect())
+}
+
+/// One row of `moz_bookmarks`
+struct PlacesRow {
+    id: i64,
+    kind: i64,
+    parent: i64,
+    title: String,
+    created_at: Option<i64>,
+    guid: String,
+    url: Option<String>,
+}
+
+/// Display names for Firefox's built-in roots, whose stored titles are internal
+fn firefox_root_name(guid: &str) -> Option<&'static str> {
+    match guid {
+        "menu________" => Some("Bookmarks Menu"),
+        "toolbar_____" => Some("Bookmarks Toolbar"),
+        "unfiled_____" => Some("Other Bookmarks"),
+        "mobile______" => Some("Mobile Bookmarks"),
+        _ => None,
+    }
+}
+
+/// Read Firefox's `places.sqlite`. Tags are attached to their bookmarks.
+pub fn read_firefox_places(path: &Path) -> Result<Vec<ImportNode>, String> {
+    // Firefox keeps the database locked while it runs, so read a copy (with its write-ahead log)
+    let copy = std::env::temp_dir().join(format!("meikai-places-{}.sqlite", uuid::Uuid::new_v4()));
+    fs::copy(path, &copy).map_err(|e| format!("Failed to copy places.sqlite: {}", e))?;
+    let wal = storage::with_suffix(path, "-wal");
+    if wal.exists() {
+        let _ = fs::copy(&wal, storage::with_suffix(&copy, "-wal"));
+    }
+
+    let rows = load_places_rows(&copy);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(storage::with_suffix(&copy, suffix));
+    }
+    let rows = rows.map_err(|e| format!("Invalid places.sqlite: {}", e))?;
+
+    Ok(places_tree(rows))
+}
+
+fn load_places_rows(path: &Path) -> rusqlite::Result<Vec<PlacesRow>> {
+    let conn = Connection::open(path)?;
+    let mut statement = conn.prepare(
+        "SELECT b.id, b.type, b.parent, COALESCE(b.title, ''), b.dateAdded, b.guid, p.url
+         FROM moz_bookmarks b LEFT JOIN moz_places p ON p.id = b.fk
+         ORDER BY b.parent, b.position",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok(PlacesRow {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            parent: row.get(2)?,
+            title: row.get(3)?,
+            // Firefox stores microseconds since the Unix epoch
+            created_at: row.get::<_, Option<i64>>(4)?.filter(|&t| t > 0).map(|t| t / 1000),
+            guid: row.get(5)?,
+            url: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Build the folder tree from `moz_bookmarks` rows (type 1 = bookmark, 2 = folder, 3 = separator)
+fn places_tree(rows: Vec<PlacesRow>) -> Vec<ImportNode> {
+    let root_id = rows.iter().find(|row| row.guid == "root________").map(|row| row.id).unwrap_or(1);
+    let tags_root = rows.iter().find(|row| row.guid == "tags________").map(|row| row.id);
+
+    // Tags are folders under the tags root holding bookmarks that point at the tagged URL
+    let tag_names: HashMap<i64, &str> = rows
+        .iter()
+        .filter(|row| Some(row.parent) == tags_root && row.kind == 2)
+        .map(|row| (row.id, row.title.as_str()))
+        .collect();
+    let mut tags: HashMap<&str, Vec<String>> = HashMap::new();
+    for row in &rows {
+        if let (Some(tag), Some(url)) = (tag_names.get(&row.parent), row.url.as_deref()) {
+            tags.entry(url).or_default().push(tag.to_string());
+        }
+    }
+
+    let mut children: HashMap<i64, Vec<&PlacesRow>> = HashMap::new();
+    for row in &rows {
+        children.entry(row.parent).or_default().push(row);
+    }
+
+    fn build(
+        parent: i64,
+        children: &HashMap<i64, Vec<&PlacesRow>>,
+        tags: &HashMap<&str, Vec<String>>,
+        skip: Option<i64>,
+    ) -> Vec<ImportNode> {
+        let Some(rows) = children.get(&parent) else {
+            return Vec::new();
+        };
+        rows.iter()
+            .filter(|row| Some(row.id) != skip)
+            .filter_map(|row| match (row.kind, row.url.as_deref()) {
+                (2, _) => Some(ImportNode::Folder {
+                    name: firefox_root_name(&row.guid).map(String::from).unwrap_or_else(|| row.title.clone()),
+                    created_at: row.created_at,
+                    children: build(row.id, children, tags, skip),
+                }),
+                // `place:` URLs are Firefox's saved searches, not pages
+                (1, Some(url)) if !url.starts_with("place:") => Some(ImportNode::Bookmark {
+                    name: row.title.clone(),
+                    url: url.to_string(),
+                    favicon: None,
+                    tags: tags.get(url).cloned().unwrap_or_default(),
+                    starred: false,
+                    created_at: row.created_at,
+                    last_visited_at: None,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    build(root_id, &children, &tags, tags_root)
+        .into_iter()
+        .filter(|node| !matches!(node, ImportNode::Folder { children, .. } if children.is_empty()))
+        .collect()
+}
+
+/// Add an import tree under `parent_id`, skipping duplicate and invalid URLs.
+/// Folders that already exist with the same name under the same parent are reused,
+/// so importing the same file twice adds nothing.
+pub fn merge(
+    collection: &mut BookmarkCollection,
+    nodes: Vec<ImportNode>,
+    parent_id: Option<String>,
+) -> ImportReport {
+    let mut report = ImportReport::default();
+    // Stored URLs may predate validation (defaults, migrated files); compare them the way imports are
+    let mut known_urls: HashSet<String> = collection
+        .bookmarks
+        .iter()
+        .map(|bookmark| validate_url(&bookmark.url).unwrap_or_else(|_| bookmark.url.clone()))
+        .collect();
+    merge_level(collection, nodes, parent_id, &mut Vec::new(), &mut known_urls, &mut report);
+    report
+}
+
+fn merge_level(
+    collection: &mut BookmarkCollection,
+    nodes: Vec<ImportNode>,
+    parent_id: Option<String>,
+    path: &mut Vec<String>,
+    known_urls: &mut HashSet<String>,
+    report: &mut ImportReport,
+) {
+    for node in nodes {
+        match node {
+            ImportNode::Folder { name, created_at, children } => {
+                let name = if name.trim().is_empty() { "Untitled folder".to_string() } else { name };
+                let existing = collection
+                    .folders
+                    .iter()
+                    .find(|folder| folder.parent_id == parent_id && folder.name == name)
+                    .map(|folder| folder.id.clone());
+                let folder_id = match existing {
+                    Some(id) => id,
+                    None => {
+                        let mut folder = BookmarkFolder::new(&name);
+                        folder.parent_id = parent_id.clone();
+                        folder.created_at = created_at.unwrap_or_else(now_millis);
+                        report.folders_created += 1;
+                        collection.push_folder(folder).id
+                    }
+                };
+                path.push(name);
+                merge_level(collection, children, Some(folder_id), path, known_urls, report);
+                path.pop();
+            }
+            ImportNode::Bookmark { name, url, favicon, tags, starred, created_at, last_visited_at } => {
+                let skipped = |reason: Option<String>| SkippedBookmark {
+                    name: name.clone(),
+                    url: url.clone(),
+                    path: path.clone(),
+                    reason,
+                };
+                let url = match validate_url(&url) {
+                    Ok(url) => url,
+                    Err(error) => {
+                        report.invalid.push(skipped(Some(error)));
+                        continue;
+                    }
+                };
+                if !known_urls.insert(url.clone()) {
+                    report.duplicates.push(skipped(None));
+                    continue;
+                }
+
+                let display_name = if name.trim().is_empty() { url.clone() } else { name };
+                let mut bookmark = Bookmark::new(&display_name, &url, starred);
+                bookmark.parent_id = parent_id.clone();
+                bookmark.favicon = favicon.filter(|f| !f.is_empty());
+                bookmark.tags = normalize_tags(tags);
+                bookmark.created_at = created_at.unwrap_or(bookmark.created_at);
+                bookmark.last_visited_at = last_visited_at;
+                collection.push_bookmark(bookmark);
+                report.imported += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROMIUM: &str = include_str!("../../tests/fixtures/bookmarks/chromium.json");
+    const PLACES_SQL: &str = include_str!("../../tests/fixtures/bookmarks/places.sql");
+
+    fn bookmark(name: &str, url: &str) -> ImportNode {
+        link(name, url, &[], None, None)
+    }
+
+    fn link(name: &str, url: &str, tags: &[&str], created_at: Option<i64>, last_visited_at: Option<i64>) -> ImportNode {
+        ImportNode::Bookmark {
+            name: name.to_string(),
+            url: url.to_string(),
+            favicon: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            starred: false,
+            created_at,
+            last_visited_at,
+        }
+    }
+
+    fn folder(name: &str, children: Vec<ImportNode>) -> ImportNode {
+        ImportNode::Folder { name: name.to_string(), created_at: None, children }
+    }
+
+    /// Build a places database from the SQL fixture and read it like an import would
+    fn read_places_fixture() -> Vec<ImportNode> {
+        let path = std::env::temp_dir().join(format!("meikai-places-fixture-{}.sqlite", uuid::Uuid::new_v4()));
+        Connection::open(&path).unwrap().execute_batch(PLACES_SQL).unwrap();
+        let nodes = read_file(&path, None);
+        fs::remove_file(&path).unwrap();
+        nodes.unwrap()
+    }
+
+    #[test]
+    fn chromium_roots_become_folders() {
+        let nodes = parse_chromium(CHROMIUM).unwrap();
+        assert_eq!(
+            nodes,
+            [
+                ImportNode::Folder {
+                    name: "Bookmarks bar".to_string(),
+                    created_at: Some(1_695_526_400_000),
+                    children: vec![
+                        link("Rust", "https://www.rust-lang.org/", &[], Some(1_705_526_400_000), Some(1_706_526_400_000)),
+                        ImportNode::Folder {
+                            name: "Work".to_string(),
+                            created_at: Some(1_704_526_400_000),
+                            children: vec![link("Tauri docs", "https://tauri.app/start/", &[], Some(1_705_526_400_000), None)],
+                        },
+                    ],
+                },
+                ImportNode::Folder {
+                    name: "Other bookmarks".to_string(),
+                    created_at: Some(1_695_526_400_000),
+                    children: vec![link("Settings", "chrome://settings/", &[], Some(1_705_526_400_000), None)],
+                },
+            ]
+        );
+        assert!(parse_chromium(r#"{"version": 1}"#).unwrap_err().contains("missing roots"));
+    }
+
+    #[test]
+    fn firefox_places_keep_order_and_tags() {
+        let nodes = read_places_fixture();
+        assert_eq!(
+            nodes,
+            [
+                ImportNode::Folder {
+                    name: "Bookmarks Toolbar".to_string(),
+                    created_at: Some(1_700_000_000_000),
+                    children: vec![
+                        link("MDN", "https://developer.mozilla.org/en-US/", &["docs"], Some(1_700_000_300_000), None),
+                        ImportNode::Folder {
+                            name: "Reading".to_string(),
+                            created_at: Some(1_700_000_100_000),
+                            children: vec![link("Mozilla", "https://www.mozilla.org/", &[], Some(1_700_000_200_000), None)],
+                        },
+                    ],
+                },
+                ImportNode::Folder {
+                    name: "Other Bookmarks".to_string(),
+                    created_at: Some(1_700_000_000_000),
+                    children: vec![link("Unfiled", "https://example.com/unfiled", &[], Some(1_700_000_500_000), None)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_are_detected_from_contents_first() {
+        assert_eq!(detect_format(Path::new("x.html"), b"SQLite format 3\0..."), Some(ImportFormat::FirefoxPlaces));
+        assert_eq!(detect_format(Path::new("Bookmarks"), b"\xef\xbb\xbf {\"roots\""), Some(ImportFormat::ChromiumJson));
+        assert_eq!(
+            detect_format(Path::new("export"), b"<!DOCTYPE NETSCAPE-Bookmark-file-1>"),
+            Some(ImportFormat::NetscapeHtml)
+        );
+        assert_eq!(detect_format(Path::new("bookmarks.htm"), b"plain"), Some(ImportFormat::NetscapeHtml));
+        assert_eq!(detect_format(Path::new("notes.txt"), b"plain"), None);
+    }
+
+    #[test]
+    fn existing_urls_are_compared_normalized() {
+        let mut collection = BookmarkCollection::default();
+        // Stored as written by the defaults, without the trailing slash
+        collection.push_bookmark(Bookmark::new("Google", "https://google.com", true));
+
+        let report = merge(
+            &mut collection,
+            vec![
+                bookmark("Google", "https://google.com/"),
+                bookmark("Google again", "google.com"),
+                bookmark("Rust", "https://www.rust-lang.org/"),
+            ],
+            None,
+        );
+        assert_eq!(report.imported, 1);
+        let duplicates: Vec<_> = report.duplicates.iter().map(|skipped| skipped.name.as_str()).collect();
+        assert_eq!(duplicates, ["Google", "Google again"]);
+        assert_eq!(collection.bookmarks.len(), 2);
+    }
+
+    #[test]
+    fn invalid_urls_are_reported_with_their_folder() {
+        let mut collection = BookmarkCollection::default();
+        let report = merge(&mut collection, parse_chromium(CHROMIUM).unwrap(), None);
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.folders_created, 3);
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.invalid[0].url, "chrome://settings/");
+        assert_eq!(report.invalid[0].path, ["Other bookmarks"]);
+        assert!(report.invalid[0].reason.is_some());
+    }
+
+    #[test]
+    fn importing_twice_adds_nothing() {
+        let mut collection = BookmarkCollection::default();
+        merge(&mut collection, read_places_fixture(), None);
+        let (folders, bookmarks) = (collection.folders.len(), collection.bookmarks.len());
+
+        let report = merge(&mut collection, read_places_fixture(), None);
+        assert_eq!((report.imported, report.folders_created, report.duplicates.len()), (0, 0, 3));
+        assert_eq!((collection.folders.len(), collection.bookmarks.len()), (folders, bookmarks));
+    }
+
+    #[test]
+    fn imported_entries_keep_their_details() {
+        let mut collection = BookmarkCollection::default();
+        merge(
+            &mut collection,
+            vec![folder(
+                " ",
+                vec![ImportNode::Bookmark {
+                    name: String::new(),
+                    url: "https://example.com".to_string(),
+                    favicon: Some(String::new()),
+                    tags: vec!["a".to_string(), " A ".to_string(), "b".to_string()],
+                    starred: true,
+                    created_at: Some(5),
+                    last_visited_at: Some(6),
+                }],
+            )],
+            None,
+        );
+        assert_eq!(collection.folders[0].name, "Untitled folder");
+        let imported = &collection.bookmarks[0];
+        assert_eq!(imported.name, "https://example.com/");
+        assert_eq!(imported.parent_id.as_deref(), Some(collection.folders[0].id.as_str()));
+        assert_eq!(imported.tags, ["a", "b"]);
+        assert!(imported.starred);
+        assert_eq!(imported.favicon, None);
+        assert_eq!((imported.created_at, imported.last_visited_at), (5, Some(6)));
+    }
+}
@@ -0,0 +1,1061 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::clock::now_millis;
+use crate::navigation::normalize_url;
+use crate::storage::{self, FileState};
+
+mod import;
+mod netscape;
+
+pub use import::{ImportFormat, ImportReport};
+
+/// File name of the bookmarks file inside the app data directory
+pub const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// Current on-disk schema version
+const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(default)]
+    pub starred: bool,
+    /// Containing folder, `None` for the root
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Order among the folder's children (folders and bookmarks share one sequence)
+    #[serde(default)]
+    pub position: u32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Milliseconds since the Unix epoch
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub last_visited_at: Option<i64>,
+}
+
+impl Bookmark {
+    pub fn new(name: &str, url: &str, starred: bool) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            url: url.to_string(),
+            favicon: None,
+            starred,
+            parent_id: None,
+            position: 0,
+            tags: Vec::new(),
+            created_at: now_millis(),
+            last_visited_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkFolder {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub position: u32,
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+impl BookmarkFolder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            parent_id: None,
+            position: 0,
+            created_at: now_millis(),
+        }
+    }
+}
+
+/// All folders and bookmarks. Also the payload of `bookmarks-changed`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkCollection {
+    pub folders: Vec<BookmarkFolder>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkCollection {
+    fn folder(&self, id: &str) -> Option<&BookmarkFolder> {
+        self.folders.iter().find(|folder| folder.id == id)
+    }
+
+    fn ensure_folder(&self, id: Option<&str>) -> Result<(), String> {
+        match id {
+            Some(id) if self.folder(id).is_none() => Err(format!("Folder not found: {}", id)),
+            _ => Ok(()),
+        }
+    }
+
+    fn bookmark_mut(&mut self, id: &str) -> Result<&mut Bookmark, String> {
+        self.bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.id == id)
+            .ok_or_else(|| format!("Bookmark not found: {}", id))
+    }
+
+    fn folder_mut(&mut self, id: &str) -> Result<&mut BookmarkFolder, String> {
+        self.folders
+            .iter_mut()
+            .find(|folder| folder.id == id)
+            .ok_or_else(|| format!("Folder not found: {}", id))
+    }
+
+    /// Parent and position of any item (folder or bookmark)
+    fn slot_mut(&mut self, id: &str) -> Option<(&mut Option<String>, &mut u32)> {
+        if let Some(folder) = self.folders.iter_mut().find(|folder| folder.id == id) {
+            return Some((&mut folder.parent_id, &mut folder.position));
+        }
+        self.bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.id == id)
+            .map(|bookmark| (&mut bookmark.parent_id, &mut bookmark.position))
+    }
+
+    /// IDs of a folder's direct children in display order
+    fn ordered_children(&self, parent_id: Option<&str>) -> Vec<String> {
+        let mut children: Vec<(u32, &str)> = self
+            .folders
+            .iter()
+            .filter(|folder| folder.parent_id.as_deref() == parent_id)
+            .map(|folder| (folder.position, folder.id.as_str()))
+            .chain(
+                self.bookmarks
+                    .iter()
+                    .filter(|bookmark| bookmark.parent_id.as_deref() == parent_id)
+                    .map(|bookmark| (bookmark.position, bookmark.id.as_str())),
+            )
+            .collect();
+        children.sort_by_key(|(position, _)| *position);
+        children.into_iter().map(|(_, id)| id.to_string()).collect()
+    }
+
+    /// Assign positions 0..n following `ids`
+    fn set_order(&mut self, ids: &[String]) {
+        for (index, id) in ids.iter().enumerate() {
+            if let Some((_, position)) = self.slot_mut(id) {
+                *position = index as u32;
+            }
+        }
+    }
+
+    /// Close gaps left by removed or moved items
+    fn renumber(&mut self, parent_id: Option<&str>) {
+        let ids = self.ordered_children(parent_id);
+        self.set_order(&ids);
+    }
+
+    /// Position for an item appended to a folder
+    fn next_position(&self, parent_id: Option<&str>) -> u32 {
+        self.ordered_children(parent_id).len() as u32
+    }
+
+    /// A folder and every folder nested inside it
+    fn folder_subtree(&self, folder_id: &str) -> HashSet<String> {
+        let mut subtree = HashSet::from([folder_id.to_string()]);
+        loop {
+            let before = subtree.len();
+            for folder in &self.folders {
+                if folder.parent_id.as_ref().is_some_and(|parent| subtree.contains(parent)) {
+                    subtree.insert(folder.id.clone());
+                }
+            }
+            if subtree.len() == before {
+                return subtree;
+            }
+        }
+    }
+
+    /// Move a folder or bookmark into `parent_id` at `index` (clamped to the end)
+    fn move_item(&mut self, id: &str, parent_id: Option<&str>, index: usize) -> Result<(), String> {
+        self.ensure_folder(parent_id)?;
+        if let Some(target) = parent_id {
+            if self.folder(id).is_some() && self.folder_subtree(id).contains(target) {
+                return Err("Can't move a folder into itself".to_string());
+            }
+        }
+
+        let (parent_slot, _) = self
+            .slot_mut(id)
+            .ok_or_else(|| format!("Bookmark or folder not found: {}", id))?;
+        let old_parent = std::mem::replace(parent_slot, parent_id.map(String::from));
+
+        let mut siblings: Vec<String> = self
+            .ordered_children(parent_id)
+            .into_iter()
+            .filter(|child| child != id)
+            .collect();
+        siblings.insert(index.min(siblings.len()), id.to_string());
+        self.set_order(&siblings);
+
+        if old_parent.as_deref() != parent_id {
+            self.renumber(old_parent.as_deref());
+        }
+        Ok(())
+    }
+
+    /// Reorder a folder's children to match `ids`; the rest keep their order at the end
+    fn reorder(&mut self, parent_id: Option<&str>, ids: &[String]) -> Result<(), String> {
+        self.ensure_folder(parent_id)?;
+        let mut children = self.ordered_children(parent_id);
+        children.sort_by_key(|child| ids.iter().position(|id| id == child).unwrap_or(usize::MAX));
+        self.set_order(&children);
+        Ok(())
+    }
+
+    /// Delete a folder with everything inside it
+    fn delete_folder(&mut self, id: &str) -> Result<(), String> {
+        let parent_id = self
+            .folder(id)
+            .ok_or_else(|| format!("Folder not found: {}", id))?
+            .parent_id
+            .clone();
+        let subtree = self.folder_subtree(id);
+        self.folders.retain(|folder| !subtree.contains(&folder.id));
+        self.bookmarks
+            .retain(|bookmark| !bookmark.parent_id.as_ref().is_some_and(|p| subtree.contains(p)));
+        self.renumber(parent_id.as_deref());
+        Ok(())
+    }
+
+    /// Bookmarks matching a query
+    fn query(&self, query: BookmarkQuery) -> Result<Vec<Bookmark>, String> {
+        self.ensure_folder(query.folder_id.as_deref())?;
+
+        let scope: Option<HashSet<String>> = match (&query.folder_id, query.recursive) {
+            (Some(folder_id), true) => Some(self.folder_subtree(folder_id)),
+            _ => None,
+        };
+        let tag = query.tag.map(|tag| tag.trim().to_lowercase()).filter(|t| !t.is_empty());
+        let text = query.text.map(|text| text.trim().to_lowercase()).filter(|t| !t.is_empty());
+
+        Ok(self
+            .bookmarks
+            .iter()
+            .filter(|bookmark| match (&scope, query.recursive) {
+                (Some(scope), _) => bookmark.parent_id.as_ref().is_some_and(|p| scope.contains(p)),
+                (None, true) => true,
+                (None, false) => bookmark.parent_id == query.folder_id,
+            })
+            .filter(|bookmark| {
+                tag.as_ref().is_none_or(|tag| bookmark.tags.iter().any(|t| t.to_lowercase() == *tag))
+            })
+            .filter(|bookmark| {
+                text.as_ref().is_none_or(|text| {
+                    bookmark.name.to_lowercase().contains(text)
+                        || bookmark.url.to_lowercase().contains(text)
+                })
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Folders and bookmarks in depth-first display order
+    fn ordered(&self) -> BookmarkCollection {
+        let mut ordered = BookmarkCollection::default();
+        let mut stack = vec![self.ordered_children(None)];
+        while let Some(mut level) = stack.pop() {
+            if level.is_empty() {
+                continue;
+            }
+            let id = level.remove(0);
+            stack.push(level);
+            if let Some(folder) = self.folder(&id) {
+                ordered.folders.push(folder.clone());
+                stack.push(self.ordered_children(Some(&id)));
+            } else if let Some(bookmark) = self.bookmarks.iter().find(|b| b.id == id) {
+                ordered.bookmarks.push(bookmark.clone());
+            }
+        }
+        ordered
+    }
+
+    /// Append a bookmark at the end of its folder
+    pub fn push_bookmark(&mut self, mut bookmark: Bookmark) -> Bookmark {
+        bookmark.position = self.next_position(bookmark.parent_id.as_deref());
+        self.bookmarks.push(bookmark.clone());
+        bookmark
+    }
+
+    /// Append a folder at the end of its parent
+    pub fn push_folder(&mut self, mut folder: BookmarkFolder) -> BookmarkFolder {
+        folder.position = self.next_position(folder.parent_id.as_deref());
+        self.folders.push(folder.clone());
+        folder
+    }
+}
+
+/// Trim, drop empties and de-duplicate tags case-insensitively (first spelling wins)
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .collect()
+}
+
+/// Versioned on-disk format
+#[derive(Debug, Serialize, Deserialize)]
+struct BookmarksFile {
+    version: u32,
+    #[serde(flatten)]
+    collection: BookmarkCollection,
+}
+
+/// Default bookmarks for first-time users (starred ones show up as quick links)
+fn default_bookmarks() -> BookmarkCollection {
+    let mut collection = BookmarkCollection::default();
+    for (name, url, starred) in [
+        ("Google", "https://google.com", true),
+        ("YouTube", "https://youtube.com", true),
+        ("Netflix", "https://netflix.com", true),
+        ("Twitter", "https://twitter.com", false),
+        ("Reddit", "https://reddit.com", false),
+        ("Pinterest", "https://pinterest.com", true),
+        ("Meikai", "https://meikai.rhishav.com", true),
+        ("Rhishav", "https://rhishav.com", true),
+    ] {
+        collection.push_bookmark(Bookmark::new(name, url, starred));
+    }
+    collection
+}
+
+/// Version 1 -> 2: flat list becomes the root folder, in its existing order
+fn migrate_v1(bookmarks: Vec<Bookmark>) -> BookmarkCollection {
+    let created_at = now_millis();
+    let mut collection = BookmarkCollection::default();
+    for mut bookmark in bookmarks {
+        bookmark.parent_id = None;
+        if bookmark.created_at == 0 {
+            bookmark.created_at = created_at;
+        }
+        collection.push_bookmark(bookmark);
+    }
+    collection
+}
+
+/// Parse any known version of the bookmarks file and upgrade it to the current schema.
+/// The flag is true when a migration ran and the file should be rewritten.
+fn parse_and_migrate(content: &str) -> Result<(BookmarkCollection, FileState), String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+
+    // Version 0: the bare array written by the old React hook, same shape as version 1 entries
+    if value.is_array() {
+        let bookmarks = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        return Ok((migrate_v1(bookmarks), FileState::Outdated));
+    }
+
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    match version {
+        1 => {
+            let bookmarks = value
+                .get("bookmarks")
+                .cloned()
+                .ok_or_else(|| "Missing bookmarks".to_string())?;
+            let bookmarks = serde_json::from_value(bookmarks).map_err(|e| e.to_string())?;
+            Ok((migrate_v1(bookmarks), FileState::Outdated))
+        }
+        2 => {
+            let file: BookmarksFile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+            Ok((file.collection, FileState::Current))
+        }
+        // Whatever a newer layout keeps in the current shape is shown; the file itself is left alone
+        newer if newer > u64::from(SCHEMA_VERSION) => {
+            let collection = serde_json::from_value::<BookmarksFile>(value)
+                .map(|file| file.collection)
+                .unwrap_or_default();
+            Ok((collection, FileState::Newer))
+        }
+        other => Err(format!("Unsupported bookmarks schema version {}", other)),
+    }
+}
+
+/// Owns `bookmarks.json`; every mutation is persisted before it's reported back
+pub struct BookmarkStore {
+    path: Mutex<PathBuf>,
+    collection: Mutex<BookmarkCollection>,
+    /// The file is from a newer build: changes only last until the app quits
+    read_only: Mutex<bool>,
+}
+
+impl BookmarkStore {
+    /// Load bookmarks from `dir`, recovering from the backup if the file is corrupt
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(BOOKMARKS_FILE);
+        let existed = path.exists() || storage::backup_path(&path).exists();
+
+        let (collection, state) = match storage::load_with_backup(&path, parse_and_migrate) {
+            Some(loaded) => loaded,
+            // Only seed defaults for a fresh install; an unreadable file has been set aside
+            None if existed => (BookmarkCollection::default(), FileState::Outdated),
+            None => (default_bookmarks(), FileState::Outdated),
+        };
+
+        let store = Self {
+            path: Mutex::new(path),
+            collection: Mutex::new(collection),
+            read_only: Mutex::new(state == FileState::Newer),
+        };
+
+        // Persist defaults and migrated data in the current schema
+        if state == FileState::Outdated {
+            let _ = store.save(&store.snapshot());
+        }
+        store
+    }
+
+    fn save(&self, collection: &BookmarkCollection) -> Result<(), String> {
+        if *self.read_only.lock().unwrap() {
+            return Ok(());
+        }
+        let file = BookmarksFile {
+            version: SCHEMA_VERSION,
+            collection: collection.clone(),
+        };
+        let content = serde_json::to_vec_pretty(&file).map_err(|e| e.to_string())?;
+        storage::write_atomic(&self.path.lock().unwrap(), &content).map_err(|e| e.to_string())
+    }
+
+    /// Switch to the bookmarks in `dir`, e.g. another profile's, and tell every window
+    pub fn reload(&self, app: &tauri::AppHandle, dir: &Path) {
+        let loaded = Self::load(dir);
+        let mut collection = self.collection.lock().unwrap();
+        *self.path.lock().unwrap() = loaded.path.into_inner().unwrap();
+        *collection = loaded.collection.into_inner().unwrap();
+        *self.read_only.lock().unwrap() = loaded.read_only.into_inner().unwrap();
+        emit_changed(app, &collection.ordered());
+    }
+
+    /// Everything, in display order
+    pub fn snapshot(&self) -> BookmarkCollection {
+        self.collection.lock().unwrap().ordered()
+    }
+
+    /// Apply a change and persist it. Nothing is kept in memory if the write fails.
+    fn mutate<T>(
+        &self,
+        change: impl FnOnce(&mut BookmarkCollection) -> Result<T, String>,
+    ) -> Result<(T, BookmarkCollection), String> {
+        let mut collection = self.collection.lock().unwrap();
+        let mut updated = collection.clone();
+        let result = change(&mut updated)?;
+        self.save(&updated)?;
+        *collection = updated;
+        Ok((result, collection.ordered()))
+    }
+}
+
+/// Validate a bookmark URL the same way navigation does
+fn validate_url(url: &str) -> Result<String, String> {
+    normalize_url(url).map(|u| u.to_string()).map_err(|e| e.to_string())
+}
+
+/// Tell every window about the new bookmark tree
+fn emit_changed(app: &tauri::AppHandle, collection: &BookmarkCollection) {
+    let _ = app.emit("bookmarks-changed", collection);
+}
+
+/// Stamp `last_visited_at` on bookmarks for a URL (called by the URL monitor)
+pub fn record_visit(app: &tauri::AppHandle, url: &str) {
+    let Some(store) = app.try_state::<BookmarkStore>() else {
+        return;
+    };
+    if !store.collection.lock().unwrap().bookmarks.iter().any(|b| b.url == url) {
+        return;
+    }
+
+    let visited_at = now_millis();
+    let result = store.mutate(|collection| {
+        for bookmark in collection.bookmarks.iter_mut().filter(|b| b.url == url) {
+            bookmark.last_visited_at = Some(visited_at);
+        }
+        Ok(())
+    });
+    if let Ok((_, collection)) = result {
+        emit_changed(app, &collection);
+    }
+}
+
+#[tauri::command]
+pub async fn get_bookmarks(
+    store: tauri::State<'_, BookmarkStore>,
+) -> Result<BookmarkCollection, String> {
+    Ok(store.snapshot())
+}
+
+#[tauri::command]
+pub async fn add_bookmark(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    name: String,
+    url: String,
+    starred: Option<bool>,
+    parent_id: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Bookmark, String> {
+    let url = validate_url(&url)?;
+    let (bookmark, collection) = store.mutate(|collection| {
+        collection.ensure_folder(parent_id.as_deref())?;
+        let mut bookmark = Bookmark::new(&name, &url, starred.unwrap_or(false));
+        bookmark.parent_id = parent_id;
+        bookmark.tags = normalize_tags(tags.unwrap_or_default());
+        Ok(collection.push_bookmark(bookmark))
+    })?;
+    emit_changed(&app, &collection);
+    Ok(bookmark)
+}
+
+#[tauri::command]
+pub async fn update_bookmark(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+    name: String,
+    url: String,
+) -> Result<(), String> {
+    let url = validate_url(&url)?;
+    let (_, collection) = store.mutate(|collection| {
+        let bookmark = collection.bookmark_mut(&id)?;
+        bookmark.name = name;
+        bookmark.url = url;
+        Ok(())
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_bookmark(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| {
+        let parent_id = collection.bookmark_mut(&id)?.parent_id.clone();
+        collection.bookmarks.retain(|bookmark| bookmark.id != id);
+        collection.renumber(parent_id.as_deref());
+        Ok(())
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+/// Reorder a folder's children (root by default) to match `ids`.
+/// Children missing from `ids` keep their relative order at the end.
+#[tauri::command]
+pub async fn reorder_bookmarks(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    ids: Vec<String>,
+    parent_id: Option<String>,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| collection.reorder(parent_id.as_deref(), &ids))?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_bookmark_starred(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+    starred: bool,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| {
+        collection.bookmark_mut(&id)?.starred = starred;
+        Ok(())
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_bookmark_tags(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| {
+        collection.bookmark_mut(&id)?.tags = normalize_tags(tags);
+        Ok(())
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+/// Every tag in use, sorted case-insensitively
+#[tauri::command]
+pub async fn list_bookmark_tags(
+    store: tauri::State<'_, BookmarkStore>,
+) -> Result<Vec<String>, String> {
+    let collection = store.snapshot();
+    let mut seen = HashSet::new();
+    let mut tags: Vec<String> = collection
+        .bookmarks
+        .into_iter()
+        .flat_map(|bookmark| bookmark.tags)
+        .filter(|tag| seen.insert(tag.to_lowercase()))
+        .collect();
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn create_bookmark_folder(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    name: String,
+    parent_id: Option<String>,
+) -> Result<BookmarkFolder, String> {
+    let (folder, collection) = store.mutate(|collection| {
+        collection.ensure_folder(parent_id.as_deref())?;
+        let mut folder = BookmarkFolder::new(&name);
+        folder.parent_id = parent_id;
+        Ok(collection.push_folder(folder))
+    })?;
+    emit_changed(&app, &collection);
+    Ok(folder)
+}
+
+#[tauri::command]
+pub async fn rename_bookmark_folder(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+    name: String,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| {
+        collection.folder_mut(&id)?.name = name;
+        Ok(())
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+/// Delete a folder together with its subfolders and bookmarks
+#[tauri::command]
+pub async fn delete_bookmark_folder(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| collection.delete_folder(&id))?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+/// Move a bookmark or folder into another folder (root when `parent_id` is absent)
+#[tauri::command]
+pub async fn move_bookmark_item(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    id: String,
+    parent_id: Option<String>,
+    index: Option<usize>,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| {
+        collection.move_item(&id, parent_id.as_deref(), index.unwrap_or(usize::MAX))
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+/// Direct contents of a folder, with the breadcrumb path from the root
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderContents {
+    /// Ancestors from the root down to (and including) the folder itself
+    pub path: Vec<BookmarkFolder>,
+    pub folders: Vec<BookmarkFolder>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[tauri::command]
+pub async fn get_bookmark_folder(
+    store: tauri::State<'_, BookmarkStore>,
+    folder_id: Option<String>,
+) -> Result<FolderContents, String> {
+    let collection = store.snapshot();
+    collection.ensure_folder(folder_id.as_deref())?;
+
+    let mut path = Vec::new();
+    let mut current = folder_id.clone();
+    while let Some(folder) = current.as_deref().and_then(|id| collection.folder(id)) {
+        path.insert(0, folder.clone());
+        current = folder.parent_id.clone();
+    }
+
+    let parent_id = folder_id.as_deref();
+    Ok(FolderContents {
+        path,
+        folders: collection
+            .folders
+            .iter()
+            .filter(|folder| folder.parent_id.as_deref() == parent_id)
+            .cloned()
+            .collect(),
+        bookmarks: collection
+            .bookmarks
+            .iter()
+            .filter(|bookmark| bookmark.parent_id.as_deref() == parent_id)
+            .cloned()
+            .collect(),
+    })
+}
+
+/// Filters for `query_bookmarks`. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkQuery {
+    /// Limit results to this folder (root when absent)
+    pub folder_id: Option<String>,
+    /// Include bookmarks in nested folders; without a folder this searches everything
+    #[serde(default)]
+    pub recursive: bool,
+    /// Case-insensitive tag match
+    pub tag: Option<String>,
+    /// Substring matched against name and URL
+    pub text: Option<String>,
+}
+
+#[tauri::command]
+pub async fn query_bookmarks(
+    store: tauri::State<'_, BookmarkStore>,
+    query: BookmarkQuery,
+) -> Result<Vec<Bookmark>, String> {
+    store.snapshot().query(query)
+}
+
+#[tauri::command]
+pub async fn clear_bookmarks(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+) -> Result<(), String> {
+    let (_, collection) = store.mutate(|collection| {
+        *collection = BookmarkCollection::default();
+        Ok(())
+    })?;
+    emit_changed(&app, &collection);
+    Ok(())
+}
+
+/// Import a Netscape HTML, Chromium `Bookmarks` or Firefox `places.sqlite` file the user
+/// picks, keeping its folder structure. The format is detected when not given.
+/// Duplicate and invalid URLs are skipped and listed in the report; `None` if cancelled.
+#[tauri::command]
+pub async fn import_bookmarks_file(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+    format: Option<ImportFormat>,
+    parent_id: Option<String>,
+) -> Result<Option<ImportReport>, String> {
+    // Chromium's file is just called `Bookmarks`, so allow anything
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("Bookmarks", &["html", "htm", "json", "sqlite"])
+        .add_filter("All files", &["*"])
+        .blocking_pick_file();
+    let Some(path) = picked else {
+        return Ok(None);
+    };
+    let path = path.into_path().map_err(|e| e.to_string())?;
+    let nodes = import::read_file(&path, format)?;
+    let (report, collection) = store.mutate(|collection| {
+        collection.ensure_folder(parent_id.as_deref())?;
+        Ok(import::merge(collection, nodes, parent_id))
+    })?;
+    emit_changed(&app, &collection);
+    Ok(Some(report))
+}
+
+/// Export everything as a Netscape bookmark file that other browsers can import, to an
+/// `.html` file the user picks. False if cancelled.
+#[tauri::command]
+pub async fn export_bookmarks_html(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, BookmarkStore>,
+) -> Result<bool, String> {
+    let picked = app
+        .dialog()
+        .file()
+        .set_file_name("meikai-bookmarks.html")
+        .add_filter("Bookmarks HTML", &["html"])
+        .blocking_save_file();
+    let Some(path) = picked else {
+        return Ok(false);
+    };
+    let path = path.into_path().map_err(|e| e.to_string())?;
+    if !is_html_file(&path) {
+        return Err("Bookmarks can only be exported to an .html file".to_string());
+    }
+    let html = netscape::export(&store.snapshot());
+    std::fs::write(&path, html).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Whether a path names an `.html` or `.htm` file
+fn is_html_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| ["html", "htm"].iter().any(|html| extension.eq_ignore_ascii_case(html)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(collection: &BookmarkCollection) -> Vec<&str> {
+        collection.bookmarks.iter().map(|bookmark| bookmark.name.as_str()).collect()
+    }
+
+    #[test]
+    fn version_0_array_is_migrated_in_order() {
+        let content = r#"[
+            {"id": "b", "name": "Second", "url": "https://b.com", "starred": true},
+            {"id": "a", "name": "First", "url": "https://a.com", "position": 7, "parentId": "gone"}
+        ]"#;
+        let (collection, state) = parse_and_migrate(content).unwrap();
+        assert_eq!(state, FileState::Outdated);
+        assert_eq!(names(&collection), ["Second", "First"]);
+        assert!(collection.folders.is_empty());
+        assert!(collection.bookmarks.iter().all(|b| b.parent_id.is_none() && b.created_at > 0));
+        assert_eq!(collection.bookmarks[1].position, 1);
+        assert!(collection.bookmarks[0].starred);
+    }
+
+    #[test]
+    fn version_1_keeps_existing_timestamps() {
+        let content = r#"{"version": 1, "bookmarks": [
+            {"id": "a", "name": "Old", "url": "https://a.com", "createdAt": 42, "tags": ["x"]},
+            {"id": "b", "name": "New", "url": "https://b.com"}
+        ]}"#;
+        let (collection, state) = parse_and_migrate(content).unwrap();
+        assert_eq!(state, FileState::Outdated);
+        assert_eq!(names(&collection), ["Old", "New"]);
+        assert_eq!(collection.bookmarks[0].created_at, 42);
+        assert_eq!(collection.bookmarks[0].tags, ["x"]);
+        assert_eq!(collection.bookmarks[1].position, 1);
+        assert!(parse_and_migrate(r#"{"version": 1}"#).is_err());
+    }
+
+    #[test]
+    fn version_2_round_trips_unchanged() {
+        let mut collection = BookmarkCollection::default();
+        let folder = collection.push_folder(BookmarkFolder::new("Work"));
+        collection.push_bookmark(Bookmark {
+            parent_id: Some(folder.id.clone()),
+            ..Bookmark::new("Docs", "https://docs.rs/", false)
+        });
+        let content = serde_json::to_string(&BookmarksFile { version: SCHEMA_VERSION, collection }).unwrap();
+
+        let (loaded, state) = parse_and_migrate(&content).unwrap();
+        assert_eq!(state, FileState::Current);
+        assert_eq!(loaded.folders[0].name, "Work");
+        assert_eq!(loaded.bookmarks[0].parent_id.as_deref(), Some(folder.id.as_str()));
+    }
+
+    /// Root: Work (Docs, Archive (Old)), Home; Work also holds a tagged bookmark
+    fn sample() -> (BookmarkCollection, [String; 2]) {
+        let mut collection = BookmarkCollection::default();
+        let work = collection.push_folder(BookmarkFolder::new("Work")).id;
+        let archive = collection
+            .push_folder(BookmarkFolder { parent_id: Some(work.clone()), ..BookmarkFolder::new("Archive") })
+            .id;
+        collection.push_bookmark(in_folder(&work, Bookmark::new("Docs", "https://docs.rs/", false)));
+        collection.push_bookmark(in_folder(&work, Bookmark {
+            tags: vec!["Rust".to_string()],
+            ..Bookmark::new("Crates", "https://crates.io/", false)
+        }));
+        collection.push_bookmark(in_folder(&archive, Bookmark::new("Old", "https://old.example/", false)));
+        collection.push_bookmark(Bookmark::new("Home", "https://home.example/", false));
+        (collection, [work, archive])
+    }
+
+    fn in_folder(folder_id: &str, bookmark: Bookmark) -> Bookmark {
+        Bookmark { parent_id: Some(folder_id.to_string()), ..bookmark }
+    }
+
+    fn id_of(collection: &BookmarkCollection, name: &str) -> String {
+        let bookmark = collection.bookmarks.iter().find(|bookmark| bookmark.name == name);
+        let folder = collection.folders.iter().find(|folder| folder.name == name);
+        bookmark.map(|b| b.id.clone()).or_else(|| folder.map(|f| f.id.clone())).unwrap()
+    }
+
+    /// Names of a folder's children with their positions, in display order
+    fn children<'a>(collection: &'a BookmarkCollection, parent_id: Option<&str>) -> Vec<(&'a str, u32)> {
+        collection
+            .ordered_children(parent_id)
+            .into_iter()
+            .map(|id| {
+                let folder = collection.folder(&id).map(|f| (f.name.as_str(), f.position));
+                folder.unwrap_or_else(|| {
+                    let bookmark = collection.bookmarks.iter().find(|b| b.id == id).unwrap();
+                    (bookmark.name.as_str(), bookmark.position)
+                })
+            })
+            .collect()
+    }
+
+    fn query_names(collection: &BookmarkCollection, query: BookmarkQuery) -> Vec<String> {
+        collection.query(query).unwrap().into_iter().map(|bookmark| bookmark.name).collect()
+    }
+
+    #[test]
+    fn folders_cant_move_into_themselves_or_their_descendants() {
+        let (mut collection, [work, archive]) = sample();
+        assert!(collection.move_item(&work, Some(&work), 0).is_err());
+        assert!(collection.move_item(&work, Some(&archive), 0).is_err());
+        assert_eq!(collection.folder(&work).unwrap().parent_id, None);
+
+        // Out of a folder is fine, and so is a bookmark into any folder
+        collection.move_item(&archive, None, 0).unwrap();
+        assert_eq!(collection.folder(&archive).unwrap().parent_id, None);
+        assert!(collection.move_item(&work, Some("missing"), 0).is_err());
+    }
+
+    #[test]
+    fn moving_renumbers_both_folders() {
+        let (mut collection, [work, _]) = sample();
+        let docs = id_of(&collection, "Docs");
+        collection.move_item(&docs, None, 1).unwrap();
+
+        assert_eq!(children(&collection, None), [("Work", 0), ("Docs", 1), ("Home", 2)]);
+        assert_eq!(children(&collection, Some(&work)), [("Archive", 0), ("Crates", 1)]);
+
+        // Indexes past the end append
+        collection.move_item(&docs, Some(&work), usize::MAX).unwrap();
+        assert_eq!(children(&collection, Some(&work)).last(), Some(&("Docs", 2)));
+        assert_eq!(children(&collection, None)[1], ("Home", 1));
+    }
+
+    #[test]
+    fn reordering_puts_unlisted_children_last() {
+        let (mut collection, [work, _]) = sample();
+        let crates = id_of(&collection, "Crates");
+        collection.reorder(Some(&work), &[crates]).unwrap();
+        assert_eq!(children(&collection, Some(&work)), [("Crates", 0), ("Archive", 1), ("Docs", 2)]);
+        assert!(collection.reorder(Some("missing"), &[]).is_err());
+    }
+
+    #[test]
+    fn deleting_a_folder_removes_its_subtree() {
+        let (mut collection, [work, _]) = sample();
+        collection.delete_folder(&work).unwrap();
+        assert!(collection.folders.is_empty());
+        assert_eq!(names(&collection), ["Home"]);
+        assert_eq!(collection.bookmarks[0].position, 0);
+        assert!(collection.delete_folder(&work).is_err());
+    }
+
+    #[test]
+    fn ordered_walks_folders_depth_first() {
+        let (collection, _) = sample();
+        let ordered = collection.ordered();
+        assert_eq!(names(&ordered), ["Old", "Docs", "Crates", "Home"]);
+        let folders: Vec<&str> = ordered.folders.iter().map(|folder| folder.name.as_str()).collect();
+        assert_eq!(folders, ["Work", "Archive"]);
+    }
+
+    #[test]
+    fn queries_are_scoped_to_a_folder() {
+        let (collection, [work, _]) = sample();
+        assert_eq!(query_names(&collection, BookmarkQuery::default()), ["Home"]);
+        let query = BookmarkQuery { folder_id: Some(work.clone()), ..Default::default() };
+        assert_eq!(query_names(&collection, query), ["Docs", "Crates"]);
+        let query = BookmarkQuery { folder_id: Some(work.clone()), recursive: true, ..Default::default() };
+        assert_eq!(query_names(&collection, query), ["Docs", "Crates", "Old"]);
+        let query = BookmarkQuery { recursive: true, ..Default::default() };
+        assert_eq!(query_names(&collection, query).len(), 4);
+        let query = BookmarkQuery { folder_id: Some("missing".to_string()), ..Default::default() };
+        assert!(collection.query(query).is_err());
+    }
+
+    #[test]
+    fn queries_filter_by_tag_and_text() {
+        let (collection, _) = sample();
+        let everywhere = |tag: Option<&str>, text: Option<&str>| BookmarkQuery {
+            recursive: true,
+            tag: tag.map(str::to_string),
+            text: text.map(str::to_string),
+            ..Default::default()
+        };
+        assert_eq!(query_names(&collection, everywhere(Some(" rust "), None)), ["Crates"]);
+        assert_eq!(query_names(&collection, everywhere(None, Some("EXAMPLE"))), ["Old", "Home"]);
+        assert!(query_names(&collection, everywhere(Some("rust"), Some("docs"))).is_empty());
+    }
+
+    #[test]
+    fn exports_only_go_to_html_files() {
+        assert!(is_html_file(Path::new("/home/user/bookmarks.html")));
+        assert!(is_html_file(Path::new("C:\\Users\\me\\Bookmarks.HTM")));
+        assert!(!is_html_file(Path::new("/home/user/.bashrc")));
+        assert!(!is_html_file(Path::new("/home/user/bookmarks.html.exe")));
+        assert!(!is_html_file(Path::new("/home/user/html")));
+    }
+
+    #[test]
+    fn unknown_versions_and_garbage_are_errors() {
+        assert!(parse_and_migrate(r#"{"version": 0, "folders": [], "bookmarks": []}"#)
+            .unwrap_err()
+            .contains("version 0"));
+        assert!(parse_and_migrate("not json").is_err());
+        assert!(parse_and_migrate(r#"[{"name": "no id or url"}]"#).is_err());
+    }
+
+    #[test]
+    fn future_files_load_what_this_build_understands() {
+        let future = r#"{"version": 3, "folders": [], "bookmarks": [
+            {"id": "a", "name": "Docs", "url": "https://docs.rs/", "position": 0, "createdAt": 1}
+        ], "smartFolders": []}"#;
+        let (collection, state) = parse_and_migrate(future).unwrap();
+        assert_eq!(state, FileState::Newer);
+        assert_eq!(names(&collection), ["Docs"]);
+
+        let (collection, state) = parse_and_migrate(r#"{"version": 3, "items": {}}"#).unwrap();
+        assert_eq!(state, FileState::Newer);
+        assert!(collection.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn future_files_are_never_overwritten() {
+        let dir = std::env::temp_dir().join(format!("meikai-bookmarks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(BOOKMARKS_FILE);
+        let future = r#"{"version": 3, "items": {}}"#;
+        std::fs::write(&path, future).unwrap();
+
+        let store = BookmarkStore::load(&dir);
+        store
+            .mutate(|collection| {
+                collection.push_folder(BookmarkFolder::new("Work"));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(store.snapshot().folders[0].name, "Work");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), future);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
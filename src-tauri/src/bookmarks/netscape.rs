@@ -0,0 +1,463 @@
+use super::import::ImportNode;
+use super::BookmarkCollection;
+
+/// Opening tag or closing tag with its attributes, or a run of text
+enum Token<'a> {
+    Open { name: String, attrs: Vec<(String, String)> },
+    Close(String),
+    Text(&'a str),
+}
+
+/// Minimal tag-soup tokenizer. Bookmark files are rarely well-formed
+/// (unclosed `<DT>` and `<p>` everywhere), so it never fails.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        rest = &rest[start..];
+
+        // Comments can contain '>' - skip to the terminator
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+
+        let Some(end) = tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim().to_ascii_lowercase()));
+        } else if !tag.starts_with('!') {
+            let name_end = tag.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(tag.len());
+            tokens.push(Token::Open {
+                name: tag[..name_end].to_ascii_lowercase(),
+                attrs: parse_attributes(&tag[name_end..]),
+            });
+        }
+    }
+    tokens
+}
+
+/// Index of the `>` closing the tag at the start of `input`, ignoring any inside quoted values
+fn tag_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in input.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `NAME="value" NAME='value' NAME=value NAME`, names lowercased
+fn parse_attributes(input: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = input.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (raw, remaining) = match after_eq.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let body = &after_eq[1..];
+                    let close = body.find(q).unwrap_or(body.len());
+                    (&body[..close], body.get(close + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    (&after_eq[..end], &after_eq[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+
+        if !name.is_empty() {
+            attrs.push((name, value));
+        }
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    }
+    attrs
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// `ADD_DATE` and friends are seconds since the Unix epoch
+fn attr_time(attrs: &[(String, String)], name: &str) -> Option<i64> {
+    attr(attrs, name)?.trim().parse::<i64>().ok().filter(|&secs| secs > 0).map(|secs| secs * 1000)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                numeric => numeric
+                    .strip_prefix("#x")
+                    .or_else(|| numeric.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| numeric.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            // Not an entity - keep the ampersand as written
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// What the text collected so far belongs to
+enum Pending {
+    None,
+    /// Inside `<H3>`
+    Heading { name: String, created_at: Option<i64> },
+    /// A finished heading waiting for its `<DL>`
+    Folder { name: String, created_at: Option<i64> },
+    Link(ImportNode),
+}
+
+/// Parse a Netscape bookmark file into a folder tree.
+/// Anything outside the top-level `<DL>` (title, `<H1>`) is ignored.
+pub fn parse(html: &str) -> Vec<ImportNode> {
+    // Children of each open <DL>; the bottom entry is the root
+    let mut stack: Vec<Vec<ImportNode>> = vec![Vec::new()];
+    // Folder headers waiting for their <DL>, one per open level
+    let mut headers: Vec<Option<(String, Option<i64>)>> = Vec::new();
+    let mut pending = Pending::None;
+    // Headings only become folders when they appear inside the bookmark list
+    let mut depth = 0usize;
+
+    for token in tokenize(html) {
+        match token {
+            Token::Open { name, attrs } => match name.as_str() {
+                "h3" if depth > 0 => {
+                    pending = Pending::Heading {
+                        name: String::new(),
+                        created_at: attr_time(&attrs, "add_date"),
+                    };
+                }
+                "a" if depth > 0 => {
+                    pending = Pending::Link(ImportNode::Bookmark {
+                        name: String::new(),
+                        url: attr(&attrs, "href").unwrap_or_default().trim().to_string(),
+                        favicon: attr(&attrs, "icon_uri").map(String::from),
+                        tags: attr(&attrs, "tags")
+                            .map(|tags| tags.split(',').map(String::from).collect())
+                            .unwrap_or_default(),
+                        starred: attr(&attrs, "starred").is_some_and(|value| value != "0"),
+                        created_at: attr_time(&attrs, "add_date"),
+                        last_visited_at: attr_time(&attrs, "last_visit"),
+                    });
+                }
+                // An unclosed <A> ends at the next entry
+                "dt" => finish_link(&mut pending, &mut stack),
+                "dl" => {
+                    finish_link(&mut pending, &mut stack);
+                    // The first <DL> is the root list; later ones belong to the last <H3>
+                    let header = match std::mem::replace(&mut pending, Pending::None) {
+                        Pending::Heading { name, created_at } | Pending::Folder { name, created_at }
+                            if depth > 0 =>
+                        {
+                            Some((decode_entities(name.trim()), created_at))
+                        }
+                        _ => None,
+                    };
+                    if depth > 0 {
+                        stack.push(Vec::new());
+                        headers.push(header);
+                    }
+                    depth += 1;
+                }
+                _ => {}
+            },
+            Token::Text(text) => match &mut pending {
+                Pending::Heading { name, .. } => name.push_str(text),
+                Pending::Link(ImportNode::Bookmark { name, .. }) => name.push_str(text),
+                _ => {}
+            },
+            Token::Close(name) => match name.as_str() {
+                "a" => finish_link(&mut pending, &mut stack),
+                "h3" => {
+                    if let Pending::Heading { name, created_at } = std::mem::replace(&mut pending, Pending::None) {
+                        pending = Pending::Folder { name, created_at };
+                    }
+                }
+                "dl" if depth > 0 => {
+                    finish_link(&mut pending, &mut stack);
+                    depth -= 1;
+                    if depth > 0 {
+                        close_folder(&mut stack, &mut headers);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    // Unterminated lists still count
+    while stack.len() > 1 {
+        close_folder(&mut stack, &mut headers);
+    }
+    stack.pop().unwrap_or_default()
+}
+
+/// Add the link being read (if any) to the innermost list
+fn finish_link(pending: &mut Pending, stack: &mut [Vec<ImportNode>]) {
+    if !matches!(pending, Pending::Link(_)) {
+        return;
+    }
+    if let Pending::Link(mut node) = std::mem::replace(pending, Pending::None) {
+        if let ImportNode::Bookmark { name, .. } = &mut node {
+            *name = decode_entities(name.trim());
+        }
+        if let Some(children) = stack.last_mut() {
+            children.push(node);
+        }
+    }
+}
+
+/// Pop the innermost list and attach it to its parent as a folder.
+/// A nested `<DL>` without a heading is flattened into its parent.
+fn close_folder(stack: &mut Vec<Vec<ImportNode>>, headers: &mut Vec<Option<(String, Option<i64>)>>) {
+    let Some(children) = stack.pop() else {
+        return;
+    };
+    let Some(parent) = stack.last_mut() else {
+        return;
+    };
+    match headers.pop().flatten() {
+        Some((name, created_at)) => parent.push(ImportNode::Folder { name, created_at, children }),
+        None => parent.extend(children),
+    }
+}
+
+/// Write the whole collection as a Netscape bookmark file, keeping folders, order, tags and stars
+pub fn export(collection: &BookmarkCollection) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <!-- This is an automatically generated file.\n     \
+         It will be read and overwritten.\n     \
+         DO NOT EDIT! -->\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n",
+    );
+    write_folder(collection, None, 0, &mut html);
+    html
+}
+
+fn write_folder(collection: &BookmarkCollection, folder_id: Option<&str>, depth: usize, html: &mut String) {
+    let indent = "    ".repeat(depth);
+    html.push_str(&format!("{}<DL><p>\n", indent));
+
+    for id in collection.ordered_children(folder_id) {
+        if let Some(folder) = collection.folder(&id) {
+            html.push_str(&format!(
+                "{}    <DT><H3{}>{}</H3>\n",
+                indent,
+                time_attr("ADD_DATE", Some(folder.created_at)),
+                escape(&folder.name)
+            ));
+            write_folder(collection, Some(&id), depth + 1, html);
+        } else if let Some(bookmark) = collection.bookmarks.iter().find(|b| b.id == id) {
+            let mut attrs = format!(" HREF=\"{}\"", escape(&bookmark.url));
+            attrs.push_str(&time_attr("ADD_DATE", Some(bookmark.created_at)));
+            attrs.push_str(&time_attr("LAST_VISIT", bookmark.last_visited_at));
+            if let Some(favicon) = &bookmark.favicon {
+                attrs.push_str(&format!(" ICON_URI=\"{}\"", escape(favicon)));
+            }
+            if !bookmark.tags.is_empty() {
+                attrs.push_str(&format!(" TAGS=\"{}\"", escape(&bookmark.tags.join(","))));
+            }
+            // Not part of the format; other browsers ignore it
+            if bookmark.starred {
+                attrs.push_str(" STARRED=\"1\"");
+            }
+            html.push_str(&format!(
+                "{}    <DT><A{}>{}</A>\n",
+                indent,
+                attrs,
+                escape(&bookmark.name)
+            ));
+        }
+    }
+
+    html.push_str(&format!("{}</DL><p>\n", indent));
+}
+
+/// ` NAME="<seconds>"`, or nothing for a missing or zero timestamp
+fn time_attr(name: &str, millis: Option<i64>) -> String {
+    match millis {
+        Some(millis) if millis > 0 => format!(" {}=\"{}\"", name, millis / 1000),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::import::merge;
+    use super::super::{Bookmark, BookmarkFolder};
+    use super::*;
+
+    fn sample_collection() -> BookmarkCollection {
+        let mut collection = BookmarkCollection::default();
+        let work = collection.push_folder(BookmarkFolder {
+            created_at: 1_700_000_000_000,
+            ..BookmarkFolder::new("Work & Play")
+        });
+        let nested = collection.push_folder(BookmarkFolder {
+            parent_id: Some(work.id.clone()),
+            ..BookmarkFolder::new("<Nested>")
+        });
+        collection.push_bookmark(Bookmark {
+            parent_id: Some(work.id.clone()),
+            tags: vec!["rust".to_string(), "docs".to_string()],
+            favicon: Some("https://docs.rs/favicon.ico".to_string()),
+            created_at: 1_700_000_001_000,
+            last_visited_at: Some(1_700_000_002_000),
+            ..Bookmark::new("Docs \"quoted\"", "https://docs.rs/?a=1&b=2", true)
+        });
+        collection.push_bookmark(Bookmark {
+            parent_id: Some(nested.id.clone()),
+            ..Bookmark::new("Deep", "https://example.com/deep", false)
+        });
+        collection.push_bookmark(Bookmark::new("Top", "https://example.com/", true));
+        collection
+    }
+
+    /// Folder path, name, URL and details of every bookmark, in display order
+    fn flatten(collection: &BookmarkCollection) -> Vec<String> {
+        let ordered = collection.ordered();
+        ordered
+            .bookmarks
+            .iter()
+            .map(|bookmark| {
+                let mut path = Vec::new();
+                let mut parent = bookmark.parent_id.clone();
+                while let Some(id) = parent {
+                    let folder = ordered.folder(&id).unwrap();
+                    path.insert(0, folder.name.clone());
+                    parent = folder.parent_id.clone();
+                }
+                format!(
+                    "{:?} {} <{}> starred={} tags={:?} icon={:?}",
+                    path, bookmark.name, bookmark.url, bookmark.starred, bookmark.tags, bookmark.favicon
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let original = sample_collection();
+        let html = export(&original);
+
+        let mut imported = BookmarkCollection::default();
+        let report = merge(&mut imported, parse(&html), None);
+        assert_eq!(report.imported, 3);
+        assert!(report.invalid.is_empty() && report.duplicates.is_empty());
+        assert_eq!(flatten(&imported), flatten(&original));
+
+        let docs = imported.bookmarks.iter().find(|b| b.name.starts_with("Docs")).unwrap();
+        assert_eq!((docs.created_at, docs.last_visited_at), (1_700_000_001_000, Some(1_700_000_002_000)));
+        let work = imported.folders.iter().find(|f| f.name == "Work & Play").unwrap();
+        assert_eq!(work.created_at, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn starred_is_written_as_an_attribute() {
+        let html = export(&sample_collection());
+        assert!(html.contains(r#"<A HREF="https://example.com/" ADD_DATE="#));
+        assert_eq!(html.matches(r#" STARRED="1""#).count(), 2);
+    }
+
+    #[test]
+    fn tag_soup_from_other_browsers_parses() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<!-- comment with <DL> and > inside -->
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000" PERSONAL_TOOLBAR_FOLDER="true">Toolbar</H3>
+    <DL><p>
+        <DT><A HREF="https://a.com/" ADD_DATE="1700000001" TAGS="x,y">A &amp; B</A>
+        <DT><A HREF='https://b.com/' STARRED="0">B
+        <DT><A HREF=https://c.com/>C</A>
+    </DL><p>
+    <DT><A HREF="https://d.com/">D</A>
+"#;
+        let nodes = parse(html);
+        assert_eq!(nodes.len(), 2);
+        let ImportNode::Folder { name, created_at, children } = &nodes[0] else {
+            panic!("expected a folder, got {:?}", nodes[0]);
+        };
+        assert_eq!((name.as_str(), *created_at), ("Toolbar", Some(1_700_000_000_000)));
+        let links: Vec<_> = children
+            .iter()
+            .map(|child| match child {
+                ImportNode::Bookmark { name, url, tags, starred, .. } => {
+                    (name.as_str(), url.as_str(), tags.len(), *starred)
+                }
+                ImportNode::Folder { .. } => panic!("unexpected folder"),
+            })
+            .collect();
+        assert_eq!(
+            links,
+            [("A & B", "https://a.com/", 2, false), ("B", "https://b.com/", 0, false), ("C", "https://c.com/", 0, false)]
+        );
+        assert!(matches!(&nodes[1], ImportNode::Bookmark { name, .. } if name == "D"));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::clock::now_millis;
+use crate::session::{self, RestoredWindow, SessionWindow, WindowState};
+
+/// Most recently closed windows kept for reopening
+const MAX_CLOSED_WINDOWS: usize = 10;
+
+struct ClosedWindow {
+    id: String,
+    window: SessionWindow,
+    closed_at: i64,
+}
+
+/// Entry of `list_closed_windows` and the `closed-windows-changed` payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosedWindowSummary {
+    pub id: String,
+    /// Title and URL of the tab that was showing
+    pub title: String,
+    pub url: String,
+    pub tab_count: usize,
+    pub closed_at: i64,
+}
+
+impl ClosedWindow {
+    fn summary(&self) -> ClosedWindowSummary {
+        let active = self.window.tabs.get(self.window.active_tab).or(self.window.tabs.first());
+        ClosedWindowSummary {
+            id: self.id.clone(),
+            title: active.map(|tab| tab.title.clone()).unwrap_or_default(),
+            url: active.map(|tab| tab.url.clone()).unwrap_or_default(),
+            tab_count: self.window.tabs.len(),
+            closed_at: self.closed_at,
+        }
+    }
+}
+
+/// Browser windows the user closed, newest first, with their tabs, history and geometry
+#[derive(Default)]
+pub struct ClosedWindows {
+    /// Taken when a close is requested, while the window and its tabs still exist
+    closing: Mutex<HashMap<String, SessionWindow>>,
+    stack: Mutex<VecDeque<ClosedWindow>>,
+}
+
+impl ClosedWindows {
+    fn summaries(&self) -> Vec<ClosedWindowSummary> {
+        self.stack.lock().unwrap().iter().map(ClosedWindow::summary).collect()
+    }
+
+    /// Put a window on top, dropping the oldest beyond `MAX_CLOSED_WINDOWS`
+    fn push(&self, closed: ClosedWindow) {
+        let mut stack = self.stack.lock().unwrap();
+        stack.push_front(closed);
+        stack.truncate(MAX_CLOSED_WINDOWS);
+    }
+
+    /// Take a window off the stack by id, or the newest without one
+    fn take(&self, id: Option<&str>) -> Option<ClosedWindow> {
+        let mut stack = self.stack.lock().unwrap();
+        let index = match id {
+            Some(id) => stack.iter().position(|closed| closed.id == id)?,
+            None => 0,
+        };
+        stack.remove(index)
+    }
+}
+
+fn emit_changed(app: &tauri::AppHandle) {
+    let _ = app.emit("closed-windows-changed", app.state::<ClosedWindows>().summaries());
+}
+
+/// Remember what a window looks like as its close is requested
+pub fn closing(app: &tauri::AppHandle, parent_label: &str) {
+    if let Some(window) = session::snapshot_window(app, parent_label) {
+        app.state::<ClosedWindows>()
+            .closing
+            .lock()
+            .unwrap()
+            .insert(parent_label.to_string(), window);
+    }
+}
+
+/// Put a destroyed window on the stack. Windows torn down without a close request
+/// (the app quitting) aren't recorded.
+pub fn closed(app: &tauri::AppHandle, parent_label: &str) {
+    let closed_windows = app.state::<ClosedWindows>();
+    let Some(window) = closed_windows.closing.lock().unwrap().remove(parent_label) else {
+        return;
+    };
+    closed_windows.push(ClosedWindow {
+        id: uuid::Uuid::new_v4().to_string(),
+        window,
+        closed_at: now_millis(),
+    });
+    emit_changed(app);
+}
+
+/// Recently closed windows, newest first
+#[tauri::command]
+pub async fn list_closed_windows(
+    closed_windows: tauri::State<'_, ClosedWindows>,
+) -> Result<Vec<ClosedWindowSummary>, String> {
+    Ok(closed_windows.summaries())
+}
+
+/// Open a closed window again where it was, with its tabs and their history.
+/// Without an id, the most recently closed one.
+#[tauri::command]
+pub async fn reopen_closed_window(
+    app: tauri::AppHandle,
+    closed_windows: tauri::State<'_, ClosedWindows>,
+    id: Option<String>,
+) -> Result<RestoredWindow, String> {
+    let closed = closed_windows
+        .take(id.as_deref())
+        .ok_or_else(|| "There is no closed window to reopen".to_string())?;
+    emit_changed(&app);
+
+    let mut window = closed.window.clone();
+    // The frontend shows the reopened window itself
+    if window.state == WindowState::Hidden {
+        window.state = WindowState::Normal;
+    }
+    session::restore_window(&app, &window).inspect_err(|_| {
+        // Keep it so the user can try again
+        closed_windows.push(closed);
+        emit_changed(&app);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionTab;
+
+    fn closed_window(id: &str, urls: &[&str], active_tab: usize) -> ClosedWindow {
+        let tabs = urls
+            .iter()
+            .map(|url| SessionTab {
+                url: url.to_string(),
+                title: format!("Title of {}", url),
+                back: Vec::new(),
+                forward: Vec::new(),
+            })
+            .collect();
+        ClosedWindow {
+            id: id.to_string(),
+            window: SessionWindow {
+                tabs,
+                active_tab,
+                x: 0,
+                y: 0,
+                width: 800,
+                height: 600,
+                state: WindowState::Normal,
+                profile_id: None,
+                container_id: None,
+            },
+            closed_at: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn summary_shows_the_active_tab() {
+        let summary = closed_window("a", &["https://a.example/", "https://b.example/"], 1).summary();
+        assert_eq!(summary.url, "https://b.example/");
+        assert_eq!(summary.title, "Title of https://b.example/");
+        assert_eq!(summary.tab_count, 2);
+
+        // An out of range index falls back to the first tab
+        assert_eq!(closed_window("a", &["https://a.example/"], 5).summary().url, "https://a.example/");
+        assert_eq!(closed_window("a", &[], 0).summary().url, "");
+    }
+
+    #[test]
+    fn newest_windows_come_first_and_the_oldest_are_dropped() {
+        let closed_windows = ClosedWindows::default();
+        for index in 0..MAX_CLOSED_WINDOWS + 2 {
+            closed_windows.push(closed_window(&index.to_string(), &["https://a.example/"], 0));
+        }
+        let ids: Vec<String> = closed_windows.summaries().into_iter().map(|summary| summary.id).collect();
+        assert_eq!(ids.len(), MAX_CLOSED_WINDOWS);
+        assert_eq!(ids.first().map(String::as_str), Some("11"));
+        assert_eq!(ids.last().map(String::as_str), Some("2"));
+    }
+
+    #[test]
+    fn windows_are_taken_by_id_or_newest_first() {
+        let closed_windows = ClosedWindows::default();
+        for id in ["a", "b", "c"] {
+            closed_windows.push(closed_window(id, &["https://a.example/"], 0));
+        }
+        assert_eq!(closed_windows.take(Some("b")).unwrap().id, "b");
+        assert!(closed_windows.take(Some("b")).is_none());
+        assert_eq!(closed_windows.take(None).unwrap().id, "c");
+        assert_eq!(closed_windows.take(None).unwrap().id, "a");
+        assert!(closed_windows.take(None).is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Url};
+
+use crate::popup_blocker;
+use crate::profiles::{self, ProfileManager};
+use crate::settings::{Settings, SettingsError, SettingsStore};
+use crate::tabs;
+use crate::url_monitor::UrlMonitorRegistry;
+
+/// Directory inside a profile's directory holding one directory per container
+const CONTAINERS_DIR: &str = "containers";
+
+/// Webview cookies, cache and storage inside a container's directory
+const WEBVIEW_DATA_DIR: &str = "webview-data";
+
+/// Longest allowed container name
+const MAX_NAME_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerColor {
+    Blue,
+    Turquoise,
+    Green,
+    Yellow,
+    Orange,
+    Red,
+    Pink,
+    Purple,
+}
+
+/// A cookie and storage partition within a profile, e.g. "Work" or "Client A".
+/// Bookmarks, settings and history stay shared with the rest of the profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub color: ContainerColor,
+}
+
+/// Opens a site, subdomains included, in a container
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRule {
+    /// e.g. `example.com`
+    pub host: String,
+    pub container_id: String,
+}
+
+/// Check containers and their rules, for settings
+pub fn validate(containers: &[Container], rules: &[ContainerRule]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for container in containers {
+        if uuid::Uuid::parse_str(&container.id).is_err() || !ids.insert(container.id.as_str()) {
+            return Err(format!("Container \"{}\" needs a unique UUID as its id", container.name));
+        }
+        let length = container.name.trim().chars().count();
+        if length == 0 || length > MAX_NAME_LENGTH {
+            return Err(format!("Container names must be 1 to {} characters", MAX_NAME_LENGTH));
+        }
+    }
+    let mut hosts = HashSet::new();
+    for rule in rules {
+        let host = &rule.host;
+        if host.is_empty() || host.contains(['/', ':', '*']) || host.chars().any(char::is_whitespace) {
+            return Err(format!("Container rules need a host name like example.com (got \"{}\")", host));
+        }
+        if !ids.contains(rule.container_id.as_str()) {
+            return Err(format!("Container rule for {} names an unknown container", host));
+        }
+        if !hosts.insert(host.to_lowercase()) {
+            return Err(format!("There is more than one container rule for {}", host));
+        }
+    }
+    Ok(())
+}
+
+/// Container a URL always opens in. The most specific host wins, so a rule for
+/// `mail.example.com` beats one for `example.com`.
+pub fn rule_for(settings: &Settings, url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    settings
+        .container_rules
+        .iter()
+        .filter(|rule| popup_blocker::is_site_allowed(host, std::slice::from_ref(&rule.host)))
+        .max_by_key(|rule| rule.host.len())
+        .map(|rule| rule.container_id.clone())
+}
+
+/// Which container each browser window's tabs keep their cookies and storage in
+#[derive(Default)]
+pub struct ContainerRegistry {
+    windows: Mutex<HashMap<String, Container>>,
+}
+
+impl ContainerRegistry {
+    pub fn assign_window(&self, parent_label: &str, container: Container) {
+        self.windows.lock().unwrap().insert(parent_label.to_string(), container);
+    }
+
+    pub fn forget_window(&self, parent_label: &str) {
+        self.windows.lock().unwrap().remove(parent_label);
+    }
+
+    /// A window's container, as it was when the window opened; `None` for the profile's own storage
+    pub fn window_container(&self, parent_label: &str) -> Option<Container> {
+        self.windows.lock().unwrap().get(parent_label).cloned()
+    }
+
+    fn has_windows(&self, container_id: &str) -> bool {
+        self.windows.lock().unwrap().values().any(|container| container.id == container_id)
+    }
+}
+
+/// Container a tab belongs to, through its window
+pub fn tab_container(app: &tauri::AppHandle, tab_label: &str) -> Option<Container> {
+    app.state::<ContainerRegistry>()
+        .window_container(&tabs::parent_window_label(app, tab_label))
+}
+
+/// Container a link opened from `opener_label` belongs in: the one a rule names for it, or the opener's.
+/// Private windows have none.
+pub fn link_container(app: &tauri::AppHandle, opener_label: &str, url: &str) -> Option<String> {
+    if app.state::<UrlMonitorRegistry>().is_private(opener_label) {
+        return None;
+    }
+    let settings = profiles::settings_of(app, &profiles::tab_profile(app, opener_label));
+    rule_for(&settings, url).or_else(|| tab_container(app, opener_label).map(|container| container.id))
+}
+
+/// Find a container among a profile's settings
+pub fn find(app: &tauri::AppHandle, profile_id: &str, container_id: &str) -> Result<Container, String> {
+    profiles::settings_of(app, profile_id)
+        .containers
+        .into_iter()
+        .find(|container| container.id == container_id)
+        .ok_or_else(|| format!("Container not found: {}", container_id))
+}
+
+/// Webview data directory of a container in a profile
+pub fn data_dir(app: &tauri::AppHandle, profile_id: &str, container_id: &str) -> PathBuf {
+    app.state::<ProfileManager>()
+        .dir(profile_id)
+        .join(CONTAINERS_DIR)
+        .join(container_id)
+        .join(WEBVIEW_DATA_DIR)
+}
+
+/// Containers of the active profile, for the settings UI
+#[tauri::command]
+pub async fn get_containers(store: tauri::State<'_, SettingsStore>) -> Result<Vec<Container>, SettingsError> {
+    Ok(store.snapshot().containers)
+}
+
+#[tauri::command]
+pub async fn create_container(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    name: String,
+    color: ContainerColor,
+) -> Result<Container, SettingsError> {
+    let container = Container {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        color,
+    };
+    store.update(&app, |settings| {
+        let mut updated = settings.clone();
+        updated.containers.push(container.clone());
+        Ok(updated)
+    })?;
+    Ok(container)
+}
+
+/// Rename or recolor a container. Open windows keep the look they opened with.
+#[tauri::command]
+pub async fn update_container(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    container: Container,
+) -> Result<Container, SettingsError> {
+    let container = Container {
+        name: container.name.trim().to_string(),
+        ..container
+    };
+    store.update(&app, |settings| {
+        let mut updated = settings.clone();
+        let existing = updated
+            .containers
+            .iter_mut()
+            .find(|existing| existing.id == container.id)
+            .ok_or_else(|| SettingsError::UnknownContainer(container.id.clone()))?;
+        *existing = container.clone();
+        Ok(updated)
+    })?;
+    Ok(container)
+}
+
+/// Remove a container with its rules, cookies and storage
+#[tauri::command]
+pub async fn delete_container(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    id: String,
+) -> Result<(), SettingsError> {
+    if app.state::<ContainerRegistry>().has_windows(&id) {
+        return Err(SettingsError::InvalidValue(
+            "Close the container's windows before deleting it".to_string(),
+        ));
+    }
+    store.update(&app, |settings| {
+        let mut updated = settings.clone();
+        let before = updated.containers.len();
+        updated.containers.retain(|container| container.id != id);
+        if updated.containers.len() == before {
+            return Err(SettingsError::UnknownContainer(id.clone()));
+        }
+        updated.container_rules.retain(|rule| rule.container_id != id);
+        Ok(updated)
+    })?;
+    let profile_id = app.state::<ProfileManager>().active_id();
+    if let Some(dir) = data_dir(&app, &profile_id, &id).parent() {
+        let _ = fs::remove_dir_all(dir);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORK: &str = "6f1c2f6e-1b7a-4a8e-9a53-2f0c1d3e4b5a";
+    const MAIL: &str = "0b9d8c7e-6f5a-4b3c-8d2e-1f0a9b8c7d6e";
+
+    fn container(id: &str, name: &str) -> Container {
+        Container { id: id.to_string(), name: name.to_string(), color: ContainerColor::Blue }
+    }
+
+    fn rule(host: &str, container_id: &str) -> ContainerRule {
+        ContainerRule { host: host.to_string(), container_id: container_id.to_string() }
+    }
+
+    #[test]
+    fn valid_containers_and_rules_pass() {
+        let containers = [container(WORK, "Work"), container(MAIL, "Mail")];
+        let rules = [rule("example.com", WORK), rule("mail.example.com", MAIL)];
+        assert_eq!(validate(&containers, &rules), Ok(()));
+        assert_eq!(validate(&[], &[]), Ok(()));
+    }
+
+    #[test]
+    fn containers_need_unique_uuids_and_a_name() {
+        assert!(validate(&[container("work", "Work")], &[]).is_err());
+        assert!(validate(&[container(WORK, "Work"), container(WORK, "Also work")], &[]).is_err());
+        assert!(validate(&[container(WORK, "  ")], &[]).is_err());
+        assert!(validate(&[container(WORK, &"x".repeat(MAX_NAME_LENGTH + 1))], &[]).is_err());
+        assert_eq!(validate(&[container(WORK, &"x".repeat(MAX_NAME_LENGTH))], &[]), Ok(()));
+    }
+
+    #[test]
+    fn rules_need_a_bare_host_and_a_known_container() {
+        let containers = [container(WORK, "Work")];
+        let bad_hosts = [
+            "",
+            "https://example.com",
+            "example.com/path",
+            "example.com:8080",
+            "*.example.com",
+            "a b",
+        ];
+        for host in bad_hosts {
+            let error = validate(&containers, &[rule(host, WORK)]).unwrap_err();
+            assert!(error.contains("host name"), "{}: {}", host, error);
+        }
+        let error = validate(&containers, &[rule("example.com", MAIL)]).unwrap_err();
+        assert!(error.contains("unknown container"), "{}", error);
+    }
+
+    #[test]
+    fn one_rule_per_host_ignoring_case() {
+        let containers = [container(WORK, "Work"), container(MAIL, "Mail")];
+        let rules = [rule("example.com", WORK), rule("Example.COM", MAIL)];
+        let error = validate(&containers, &rules).unwrap_err();
+        assert!(error.contains("more than one"), "{}", error);
+    }
+
+    #[test]
+    fn the_most_specific_host_wins() {
+        let settings = Settings {
+            containers: vec![container(WORK, "Work"), container(MAIL, "Mail")],
+            container_rules: vec![rule("example.com", WORK), rule("mail.example.com", MAIL)],
+            ..Settings::default()
+        };
+        assert_eq!(rule_for(&settings, "https://example.com/").as_deref(), Some(WORK));
+        assert_eq!(rule_for(&settings, "https://docs.example.com/a").as_deref(), Some(WORK));
+        assert_eq!(rule_for(&settings, "https://mail.example.com/inbox").as_deref(), Some(MAIL));
+        assert_eq!(rule_for(&settings, "https://eu.MAIL.example.com/").as_deref(), Some(MAIL));
+    }
+
+    #[test]
+    fn rules_only_match_whole_host_labels() {
+        let settings = Settings {
+            containers: vec![container(WORK, "Work")],
+            container_rules: vec![rule("example.com", WORK)],
+            ..Settings::default()
+        };
+        assert_eq!(rule_for(&settings, "https://notexample.com/"), None);
+        assert_eq!(rule_for(&settings, "https://example.com.evil.test/"), None);
+        assert_eq!(rule_for(&settings, "about:blank"), None);
+        assert_eq!(rule_for(&settings, "not a url"), None);
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{LogicalPosition, LogicalSize, Manager, Url};
+
+use crate::popup_blocker;
+use crate::settings::{self, Settings};
+use crate::tabs::{self, TabRegistry};
+use crate::url_monitor::{LoadingState, UrlMonitorRegistry};
+use crate::window;
+
+/// How often tabs are checked for hibernation
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Pages that never answer with their scroll position are hibernated without it after this long
+const SCROLL_REPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What's left of a tab once its webview is gone
+#[derive(Debug, Clone)]
+struct HibernatedTab {
+    url: String,
+    scroll_x: f64,
+    scroll_y: f64,
+}
+
+/// A scroll position the page was asked for and hasn't reported yet
+#[derive(Debug, Clone)]
+struct ScrollRequest {
+    since: Instant,
+    /// Sent back with the report, so a page can't hibernate itself
+    token: String,
+}
+
+#[derive(Debug, Clone)]
+struct TabActivity {
+    /// Last time the tab was on screen in a shown window
+    last_seen: Instant,
+    scroll_request: Option<ScrollRequest>,
+}
+
+/// Destroys the webviews of tabs nobody has looked at for a while. Each costs 80-100 MB;
+/// the tab keeps its place, URL, title and history and is rebuilt when it's shown again.
+#[derive(Default)]
+pub struct HibernationManager {
+    activity: Mutex<HashMap<String, TabActivity>>,
+    hibernated: Mutex<HashMap<String, HibernatedTab>>,
+}
+
+impl HibernationManager {
+    pub fn is_hibernated(&self, tab_label: &str) -> bool {
+        self.hibernated.lock().unwrap().contains_key(tab_label)
+    }
+
+    /// Hibernated tabs among `tab_labels`
+    pub fn hibernated_of(&self, tab_labels: &[String]) -> Vec<String> {
+        let hibernated = self.hibernated.lock().unwrap();
+        tab_labels.iter().filter(|tab| hibernated.contains_key(*tab)).cloned().collect()
+    }
+
+    /// Drop everything known about a closed tab
+    pub fn forget(&self, tab_label: &str) {
+        self.activity.lock().unwrap().remove(tab_label);
+        self.hibernated.lock().unwrap().remove(tab_label);
+    }
+
+    /// Note that a tab is on screen
+    fn seen(&self, tab_label: &str, now: Instant) {
+        self.activity.lock().unwrap().insert(tab_label.to_string(), TabActivity {
+            last_seen: now,
+            scroll_request: None,
+        });
+    }
+
+    /// Time since a tab was last on screen; a tab seen for the first time starts counting now
+    fn activity(&self, tab_label: &str, now: Instant) -> TabActivity {
+        self.activity
+            .lock()
+            .unwrap()
+            .entry(tab_label.to_string())
+            .or_insert(TabActivity {
+                last_seen: now,
+                scroll_request: None,
+            })
+            .clone()
+    }
+
+    /// Start waiting for a tab's scroll position. Returns the token its report has to carry.
+    fn request_scroll(&self, tab_label: &str, now: Instant) -> Option<String> {
+        let mut activity = self.activity.lock().unwrap();
+        let activity = activity.get_mut(tab_label)?;
+        let token = uuid::Uuid::new_v4().to_string();
+        activity.scroll_request = Some(ScrollRequest { since: now, token: token.clone() });
+        Some(token)
+    }
+
+    /// Stop waiting for a tab's scroll position. With a token, only if it's the one that was sent.
+    fn end_scroll_request(&self, tab_label: &str, token: Option<&str>) -> bool {
+        let mut activity = self.activity.lock().unwrap();
+        let Some(activity) = activity.get_mut(tab_label) else {
+            return false;
+        };
+        let matches = activity
+            .scroll_request
+            .as_ref()
+            .is_some_and(|request| token.is_none_or(|token| request.token == token));
+        if matches {
+            activity.scroll_request = None;
+        }
+        matches
+    }
+}
+
+/// Threshold for a tab that's off screen: `window_shown` is false for hidden and minimized windows
+fn threshold(settings: &Settings, window_shown: bool) -> Option<Duration> {
+    let minutes = if window_shown {
+        settings.hibernate_background_tabs_after_minutes
+    } else {
+        settings.hibernate_hidden_windows_after_minutes
+    };
+    (minutes > 0).then(|| Duration::from_secs(u64::from(minutes) * 60))
+}
+
+/// Check the never-hibernate list, for settings
+pub fn validate_never_hibernate(sites: &[String]) -> Result<(), String> {
+    for site in sites {
+        if site.is_empty() || site.contains(['/', ':', '*']) || site.chars().any(char::is_whitespace) {
+            return Err(format!("Never-hibernate entries must be host names like example.com (got \"{}\")", site));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a page may be hibernated at all: not mid-load and not on the never-hibernate list
+fn can_hibernate(app: &tauri::AppHandle, tab_label: &str, settings: &Settings) -> bool {
+    let Some(state) = app.state::<UrlMonitorRegistry>().page_state(tab_label) else {
+        return false;
+    };
+    // A private tab's cookies and storage would go with its webview
+    if state.loading == LoadingState::Started || state.url.is_empty() || state.private {
+        return false;
+    }
+    let host = Url::parse(&state.url).ok().and_then(|url| url.host_str().map(str::to_string));
+    !host.is_some_and(|host| popup_blocker::is_site_allowed(&host, &settings.never_hibernate))
+}
+
+/// Check every tab once, starting hibernation for the ones idle past their threshold
+fn sweep(app: &tauri::AppHandle) {
+    let settings = settings::current(app);
+    let manager = app.state::<HibernationManager>();
+    let registry = app.state::<TabRegistry>();
+    let now = Instant::now();
+
+    for parent_label in registry.windows() {
+        let Some(window) = app.get_window(&parent_label) else {
+            continue;
+        };
+        let shown = window.is_visible().unwrap_or(true) && !window.is_minimized().unwrap_or(false);
+        let on_screen = tabs::visible_tabs(app, &parent_label);
+
+        for tab in registry.tabs(&parent_label) {
+            if shown && on_screen.contains(&tab) {
+                manager.seen(&tab, now);
+                continue;
+            }
+            if manager.is_hibernated(&tab) {
+                continue;
+            }
+            let activity = manager.activity(&tab, now);
+            let idle = now.duration_since(activity.last_seen);
+            let due = || {
+                threshold(&settings, shown).is_some_and(|limit| idle >= limit)
+                    && can_hibernate(app, &tab, &settings)
+            };
+            match activity.scroll_request {
+                Some(request) if now.duration_since(request.since) < SCROLL_REPORT_TIMEOUT => {}
+                // The page never reported where it was scrolled to. It may have started
+                // loading or moved to a never-hibernate site since it was asked.
+                Some(_) => {
+                    manager.end_scroll_request(&tab, None);
+                    if due() {
+                        hibernate(app, &tab, 0.0, 0.0);
+                    }
+                }
+                None => {
+                    if due() {
+                        request_scroll_report(app, &tab, now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run the sweep for as long as the app lives
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SWEEP_INTERVAL).await;
+            sweep(&app);
+        }
+    });
+}
+
+/// Ask the page where it's scrolled to. It answers with `report_scroll_position`, which
+/// hibernates the tab.
+fn request_scroll_report(app: &tauri::AppHandle, tab_label: &str, now: Instant) {
+    let Some(webview) = app.get_webview(tab_label) else {
+        return;
+    };
+    let Some(token) = app.state::<HibernationManager>().request_scroll(tab_label, now) else {
+        return;
+    };
+    let script = format!(
+        r#"(function () {{
+  var internals = window.__TAURI_INTERNALS__;
+  if (!internals) return;
+  internals.invoke("report_scroll_position", {{
+    token: {token},
+    scrollX: Math.round(window.scrollX),
+    scrollY: Math.round(window.scrollY)
+  }}).catch(function () {{}});
+}})();"#,
+        token = serde_json::Value::from(token),
+    );
+    if webview.eval(script).is_err() {
+        hibernate(app, tab_label, 0.0, 0.0);
+    }
+}
+
+/// Called by the script `request_scroll_report` runs in a tab. Only finishes what the sweep
+/// started: the token has to match, and a page can only report on itself.
+#[tauri::command]
+pub async fn report_scroll_position(
+    webview: tauri::Webview,
+    token: String,
+    scroll_x: f64,
+    scroll_y: f64,
+) -> Result<(), String> {
+    let app = webview.app_handle();
+    let tab_label = webview.label();
+    if !app.state::<HibernationManager>().end_scroll_request(tab_label, Some(&token)) {
+        return Err("No scroll position was asked for".to_string());
+    }
+    // The page may have started loading while it was asked
+    if can_hibernate(app, tab_label, &settings::current(app)) {
+        let position = |value: f64| if value.is_finite() { value.max(0.0) } else { 0.0 };
+        hibernate(app, tab_label, position(scroll_x), position(scroll_y));
+    }
+    Ok(())
+}
+
+/// Destroy a tab's webview, keeping what's needed to bring it back
+fn hibernate(app: &tauri::AppHandle, tab_label: &str, scroll_x: f64, scroll_y: f64) {
+    let monitor = app.state::<UrlMonitorRegistry>();
+    let (Some(webview), Some(state)) = (app.get_webview(tab_label), monitor.page_state(tab_label)) else {
+        return;
+    };
+    if webview.close().is_err() {
+        return;
+    }
+    // Keep the page state and back/forward history for the tab strip and the rebuilt webview
+    monitor.suspend(tab_label);
+    app.state::<HibernationManager>().hibernated.lock().unwrap().insert(
+        tab_label.to_string(),
+        HibernatedTab {
+            url: state.url,
+            scroll_x,
+            scroll_y,
+        },
+    );
+    if let Some(parent_label) = app.state::<TabRegistry>().parent_of(tab_label) {
+        tabs::tabs_changed(app, &parent_label);
+    }
+}
+
+/// Scrolls the rebuilt page back to where it was once it has loaded
+fn restore_scroll_script(tab: &HibernatedTab) -> String {
+    format!(
+        r#"if (location.href === {url}) {{
+  window.addEventListener("load", function () {{ window.scrollTo({x}, {y}); }}, {{ once: true }});
+}}"#,
+        url = serde_json::Value::from(tab.url.as_str()),
+        x = tab.scroll_x,
+        y = tab.scroll_y,
+    )
+}
+
+/// Rebuild a hibernated tab's webview in its window. Returns false if it wasn't hibernated.
+pub fn wake(app: &tauri::AppHandle, parent_label: &str, tab_label: &str) -> bool {
+    let manager = app.state::<HibernationManager>();
+    let Some(tab) = manager.hibernated.lock().unwrap().remove(tab_label) else {
+        return false;
+    };
+    manager.seen(tab_label, Instant::now());
+
+    let rebuilt = app.get_window(parent_label).is_some_and(|window| {
+        let size = match (window.inner_size(), window.scale_factor()) {
+            (Ok(size), Ok(scale)) => size.to_logical::<f64>(scale),
+            _ => LogicalSize::new(0.0, 0.0),
+        };
+        window::content_webview_builder(app, tab_label, &tab.url, false)
+            .map(|builder| builder.initialization_script(restore_scroll_script(&tab)))
+            .and_then(|builder| {
+                window
+                    .add_child(builder, LogicalPosition::new(0.0, 0.0), size)
+                    .map_err(|e| e.to_string())
+            })
+            .is_ok()
+    });
+    if !rebuilt {
+        // Try again next time the tab is shown
+        manager.hibernated.lock().unwrap().insert(tab_label.to_string(), tab);
+    }
+    rebuilt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_reports_need_the_token_that_was_sent() {
+        let manager = HibernationManager::default();
+        let now = Instant::now();
+        // Tabs the sweep never looked at can't be asked
+        assert_eq!(manager.request_scroll("content-1", now), None);
+
+        manager.activity("content-1", now);
+        let token = manager.request_scroll("content-1", now).unwrap();
+        assert!(!manager.end_scroll_request("content-1", Some("forged")));
+        assert!(!manager.end_scroll_request("content-2", Some(&token)));
+        assert!(manager.end_scroll_request("content-1", Some(&token)));
+        // A report only counts once
+        assert!(!manager.end_scroll_request("content-1", Some(&token)));
+    }
+
+    #[test]
+    fn showing_a_tab_cancels_its_scroll_request() {
+        let manager = HibernationManager::default();
+        let now = Instant::now();
+        manager.activity("content-1", now);
+        let token = manager.request_scroll("content-1", now).unwrap();
+        manager.seen("content-1", now);
+        assert!(manager.activity("content-1", now).scroll_request.is_none());
+        assert!(!manager.end_scroll_request("content-1", Some(&token)));
+    }
+
+    #[test]
+    fn thresholds_depend_on_the_window() {
+        let settings = Settings {
+            hibernate_hidden_windows_after_minutes: 5,
+            hibernate_background_tabs_after_minutes: 0,
+            ..Settings::default()
+        };
+        assert_eq!(threshold(&settings, false), Some(Duration::from_secs(300)));
+        assert_eq!(threshold(&settings, true), None);
+    }
+}
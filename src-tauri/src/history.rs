@@ -0,0 +1,490 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::Url;
+
+use crate::clock::now_millis;
+use crate::profiles;
+
+/// File name of the history database inside the app data directory
+pub const HISTORY_DB_FILE: &str = "history.db";
+
+/// A single visit joined with its page's aggregate data
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// Visit ID - used to delete single entries
+    pub id: i64,
+    pub url: String,
+    pub title: String,
+    pub domain: String,
+    /// Visit time in milliseconds since the Unix epoch
+    pub visit_time: i64,
+    /// Total number of visits to this URL
+    pub visit_count: i64,
+    /// Label of the content webview the visit came from
+    pub source_label: String,
+}
+
+/// A page with the aggregates used to rank it as a suggestion
+#[derive(Debug, Clone)]
+pub struct PageMatch {
+    pub url: String,
+    pub title: String,
+    pub visit_count: i64,
+    pub last_visit_time: i64,
+}
+
+/// Filters for `query_history`. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQuery {
+    /// Substring matched against URL and title
+    pub text: Option<String>,
+    /// Inclusive lower bound, milliseconds since the Unix epoch
+    pub start_time: Option<i64>,
+    /// Exclusive upper bound, milliseconds since the Unix epoch
+    pub end_time: Option<i64>,
+    /// Host to match, including its subdomains
+    pub domain: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// Default number of rows returned by a history query
+const DEFAULT_QUERY_LIMIT: u32 = 200;
+
+/// SQLite-backed browsing history
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// Open (or create) the history database at `path`
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Non-persistent store, used if the database file can't be opened
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        Ok(Self { conn: Mutex::new(Self::prepare(conn)?) })
+    }
+
+    /// Switch to the database at `path`, e.g. another profile's
+    pub fn reopen(&self, path: &Path) -> rusqlite::Result<()> {
+        let conn = Self::prepare(Connection::open(path)?)?;
+        *self.conn.lock().unwrap() = conn;
+        Ok(())
+    }
+
+    fn prepare(conn: Connection) -> rusqlite::Result<Connection> {
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+             CREATE TABLE IF NOT EXISTS pages (
+                 id INTEGER PRIMARY KEY,
+                 url TEXT NOT NULL UNIQUE,
+                 title TEXT NOT NULL DEFAULT '',
+                 domain TEXT NOT NULL DEFAULT '',
+                 visit_count INTEGER NOT NULL DEFAULT 0,
+                 last_visit_time INTEGER NOT NULL DEFAULT 0
+             );
+             CREATE TABLE IF NOT EXISTS visits (
+                 id INTEGER PRIMARY KEY,
+                 page_id INTEGER NOT NULL REFERENCES pages(id) ON DELETE CASCADE,
+                 visit_time INTEGER NOT NULL,
+                 source_label TEXT NOT NULL DEFAULT ''
+             );
+             CREATE INDEX IF NOT EXISTS idx_visits_time ON visits(visit_time);
+             CREATE INDEX IF NOT EXISTS idx_visits_page ON visits(page_id);
+             CREATE INDEX IF NOT EXISTS idx_pages_domain ON pages(domain);",
+        )?;
+        Ok(conn)
+    }
+
+    /// Record a committed navigation
+    pub fn record_visit(
+        &self,
+        url: &str,
+        title: &str,
+        source_label: &str,
+        visit_time: i64,
+    ) -> rusqlite::Result<()> {
+        let domain = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(String::from))
+            .unwrap_or_default();
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO pages (url, title, domain, visit_count, last_visit_time)
+             VALUES (?1, ?2, ?3, 1, ?4)
+             ON CONFLICT(url) DO UPDATE SET
+                 visit_count = visit_count + 1,
+                 last_visit_time = excluded.last_visit_time,
+                 title = CASE WHEN excluded.title = '' THEN title ELSE excluded.title END",
+            params![url, title, domain, visit_time],
+        )?;
+        let page_id: i64 = tx.query_row(
+            "SELECT id FROM pages WHERE url = ?1",
+            params![url],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "INSERT INTO visits (page_id, visit_time, source_label) VALUES (?1, ?2, ?3)",
+            params![page_id, visit_time, source_label],
+        )?;
+        tx.commit()
+    }
+
+    /// Titles usually arrive after the navigation is committed
+    pub fn update_title(&self, url: &str, title: &str) -> rusqlite::Result<()> {
+        self.conn.lock().unwrap().execute(
+            "UPDATE pages SET title = ?2 WHERE url = ?1",
+            params![url, title],
+        )?;
+        Ok(())
+    }
+
+    /// Newest-first visits matching the query
+    pub fn query(&self, query: &HistoryQuery) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let text = query.text.as_deref().map(str::trim).filter(|t| !t.is_empty());
+        let domain = query.domain.as_deref().map(str::trim).filter(|d| !d.is_empty());
+
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT v.id, p.url, p.title, p.domain, v.visit_time, p.visit_count, v.source_label
+             FROM visits v JOIN pages p ON p.id = v.page_id
+             WHERE (?1 IS NULL OR p.url LIKE '%' || ?1 || '%' ESCAPE '\\'
+                               OR p.title LIKE '%' || ?1 || '%' ESCAPE '\\')
+               AND (?2 IS NULL OR v.visit_time >= ?2)
+               AND (?3 IS NULL OR v.visit_time < ?3)
+               AND (?4 IS NULL OR p.domain = ?4 OR p.domain LIKE '%.' || ?6 ESCAPE '\\')
+             ORDER BY v.visit_time DESC
+             LIMIT ?5",
+        )?;
+
+        let rows = statement.query_map(
+            params![
+                text.map(escape_like),
+                query.start_time,
+                query.end_time,
+                domain,
+                query.limit.unwrap_or(DEFAULT_QUERY_LIMIT),
+                domain.map(escape_like),
+            ],
+            |row| {
+                Ok(HistoryEntry {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    title: row.get(2)?,
+                    domain: row.get(3)?,
+                    visit_time: row.get(4)?,
+                    visit_count: row.get(5)?,
+                    source_label: row.get(6)?,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
+    /// Most visited pages whose URL or title contains `text`
+    pub fn search_pages(&self, text: &str, limit: u32) -> rusqlite::Result<Vec<PageMatch>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT url, title, visit_count, last_visit_time FROM pages
+             WHERE url LIKE '%' || ?1 || '%' ESCAPE '\\'
+                OR title LIKE '%' || ?1 || '%' ESCAPE '\\'
+             ORDER BY visit_count DESC, last_visit_time DESC
+             LIMIT ?2",
+        )?;
+        let rows = statement.query_map(params![escape_like(text), limit], |row| {
+            Ok(PageMatch {
+                url: row.get(0)?,
+                title: row.get(1)?,
+                visit_count: row.get(2)?,
+                last_visit_time: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Delete a single visit
+    pub fn delete_visit(&self, id: i64) -> rusqlite::Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let page_id: Option<i64> = tx
+            .query_row("SELECT page_id FROM visits WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        if let Some(page_id) = page_id {
+            tx.execute("DELETE FROM visits WHERE id = ?1", params![id])?;
+            recompute_aggregates(&tx, Some(page_id))?;
+            remove_orphaned_pages(&tx)?;
+        }
+        tx.commit()
+    }
+
+    /// Delete every visit in `[start_time, end_time)`
+    pub fn delete_range(&self, start_time: i64, end_time: i64) -> rusqlite::Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM visits WHERE visit_time >= ?1 AND visit_time < ?2",
+            params![start_time, end_time],
+        )?;
+        recompute_aggregates(&tx, None)?;
+        remove_orphaned_pages(&tx)?;
+        tx.commit()
+    }
+
+    pub fn clear(&self) -> rusqlite::Result<()> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute_batch("DELETE FROM visits; DELETE FROM pages;")
+    }
+}
+
+/// Recompute visit counts and last visit times from the visits that remain,
+/// for one page or all of them
+fn recompute_aggregates(conn: &Connection, page_id: Option<i64>) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE pages SET
+             visit_count = (SELECT COUNT(*) FROM visits WHERE page_id = pages.id),
+             last_visit_time = COALESCE((SELECT MAX(visit_time) FROM visits WHERE page_id = pages.id), 0)
+         WHERE ?1 IS NULL OR id = ?1",
+        params![page_id],
+    )?;
+    Ok(())
+}
+
+/// Pages with no visits left shouldn't linger in search results
+fn remove_orphaned_pages(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM pages WHERE NOT EXISTS (SELECT 1 FROM visits WHERE page_id = pages.id)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Escape LIKE wildcards so user text is matched literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Only real pages belong in history
+fn is_recordable(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://") || url.starts_with("file://")
+}
+
+/// Record a navigation committed in a content webview in its profile's history (called by the URL monitor)
+pub fn record_navigation(app: &tauri::AppHandle, url: &str, title: &str, source_label: &str) {
+    if !is_recordable(url) {
+        return;
+    }
+    profiles::with_tab_history(app, source_label, |store| {
+        let _ = store.record_visit(url, title, source_label, now_millis());
+    });
+}
+
+/// Update the stored title once the page reports it (called by the URL monitor)
+pub fn record_title(app: &tauri::AppHandle, url: &str, title: &str, source_label: &str) {
+    if title.is_empty() || !is_recordable(url) {
+        return;
+    }
+    profiles::with_tab_history(app, source_label, |store| {
+        let _ = store.update_title(url, title);
+    });
+}
+
+#[tauri::command]
+pub async fn query_history(
+    store: tauri::State<'_, HistoryStore>,
+    query: HistoryQuery,
+) -> Result<Vec<HistoryEntry>, String> {
+    store.query(&query).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_history_entry(
+    store: tauri::State<'_, HistoryStore>,
+    id: i64,
+) -> Result<(), String> {
+    store.delete_visit(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_history_range(
+    store: tauri::State<'_, HistoryStore>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<(), String> {
+    store.delete_range(start_time, end_time).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_history(store: tauri::State<'_, HistoryStore>) -> Result<(), String> {
+    store.clear().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_with(visits: &[(&str, &str, i64)]) -> HistoryStore {
+        let store = HistoryStore::open_in_memory().unwrap();
+        for (url, title, time) in visits {
+            store.record_visit(url, title, "content-1", *time).unwrap();
+        }
+        store
+    }
+
+    fn urls(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.url.as_str()).collect()
+    }
+
+    fn text_query(text: &str) -> HistoryQuery {
+        HistoryQuery { text: Some(text.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn visits_are_listed_newest_first_with_page_aggregates() {
+        let store = store_with(&[
+            ("https://a.com/", "A", 1),
+            ("https://b.com/", "B", 2),
+            ("https://a.com/", "", 3),
+        ]);
+        let entries = store.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(urls(&entries), ["https://a.com/", "https://b.com/", "https://a.com/"]);
+        assert_eq!(entries[0].visit_count, 2);
+        // A visit without a title keeps the one already known
+        assert_eq!(entries[0].title, "A");
+        assert_eq!(entries[0].domain, "a.com");
+        assert_eq!(entries[0].source_label, "content-1");
+    }
+
+    #[test]
+    fn query_filters_by_text_time_and_limit() {
+        let store = store_with(&[
+            ("https://rust-lang.org/", "Rust", 10),
+            ("https://example.com/rusty", "Example", 20),
+            ("https://example.com/other", "Other", 30),
+        ]);
+        assert_eq!(urls(&store.query(&text_query("rust")).unwrap()), ["https://example.com/rusty", "https://rust-lang.org/"]);
+        assert_eq!(urls(&store.query(&text_query("OTHER")).unwrap()), ["https://example.com/other"]);
+        assert_eq!(store.query(&text_query("   ")).unwrap().len(), 3);
+
+        let window = HistoryQuery { start_time: Some(10), end_time: Some(30), ..Default::default() };
+        assert_eq!(urls(&store.query(&window).unwrap()), ["https://example.com/rusty", "https://rust-lang.org/"]);
+
+        let limited = HistoryQuery { limit: Some(1), ..Default::default() };
+        assert_eq!(urls(&store.query(&limited).unwrap()), ["https://example.com/other"]);
+    }
+
+    #[test]
+    fn like_wildcards_are_matched_literally() {
+        let store = store_with(&[
+            ("https://a.com/100%25", "100% done", 1),
+            ("https://a.com/1000", "1000", 2),
+            ("https://a.com/snake_case", "snake_case", 3),
+            ("https://a.com/snakeXcase", "snakeXcase", 4),
+            ("https://a.com/back", "C:\\path", 5),
+        ]);
+        assert_eq!(urls(&store.query(&text_query("100%")).unwrap()), ["https://a.com/100%25"]);
+        assert_eq!(urls(&store.query(&text_query("e_c")).unwrap()), ["https://a.com/snake_case"]);
+        assert_eq!(urls(&store.query(&text_query("C:\\")).unwrap()), ["https://a.com/back"]);
+        assert_eq!(escape_like("a%b_c\\"), "a\\%b\\_c\\\\");
+    }
+
+    #[test]
+    fn domain_matches_subdomains_only() {
+        let store = store_with(&[
+            ("https://example.com/", "", 1),
+            ("https://docs.example.com/", "", 2),
+            ("https://notexample.com/", "", 3),
+        ]);
+        let query = HistoryQuery { domain: Some("example.com".to_string()), ..Default::default() };
+        assert_eq!(urls(&store.query(&query).unwrap()), ["https://docs.example.com/", "https://example.com/"]);
+        // Wildcards in the domain are matched literally
+        for domain in ["%", "e_ample.com", "%example.com"] {
+            let query = HistoryQuery { domain: Some(domain.to_string()), ..Default::default() };
+            assert!(store.query(&query).unwrap().is_empty(), "{}", domain);
+        }
+    }
+
+    #[test]
+    fn search_pages_ranks_by_visit_count() {
+        let store = store_with(&[
+            ("https://a.com/docs", "Docs", 1),
+            ("https://b.com/docs", "Docs", 2),
+            ("https://a.com/docs", "Docs", 3),
+            ("https://c.com/", "Unrelated", 4),
+        ]);
+        let pages = store.search_pages("docs", 10).unwrap();
+        let found: Vec<_> = pages.iter().map(|page| (page.url.as_str(), page.visit_count)).collect();
+        assert_eq!(found, [("https://a.com/docs", 2), ("https://b.com/docs", 1)]);
+        assert_eq!(pages[0].last_visit_time, 3);
+        assert_eq!(store.search_pages("docs", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn deleting_a_visit_updates_and_drops_pages() {
+        let store = store_with(&[
+            ("https://a.com/", "A", 1),
+            ("https://a.com/", "A", 2),
+            ("https://b.com/", "B", 3),
+        ]);
+        let entries = store.query(&HistoryQuery::default()).unwrap();
+
+        // Deleting the newest visit of a page moves its last visit time back
+        store.delete_visit(entries[1].id).unwrap();
+        let remaining = store.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(urls(&remaining), ["https://b.com/", "https://a.com/"]);
+        assert_eq!(remaining[1].visit_count, 1);
+        let page = &store.search_pages("a.com", 10).unwrap()[0];
+        assert_eq!((page.visit_count, page.last_visit_time), (1, 1));
+
+        store.delete_visit(entries[0].id).unwrap();
+        assert!(store.search_pages("b.com", 10).unwrap().is_empty());
+
+        // Unknown IDs are ignored
+        store.delete_visit(9999).unwrap();
+        assert_eq!(store.query(&HistoryQuery::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn deleting_a_range_recomputes_aggregates() {
+        let store = store_with(&[
+            ("https://a.com/", "A", 10),
+            ("https://a.com/", "A", 20),
+            ("https://b.com/", "B", 25),
+            ("https://a.com/", "A", 30),
+        ]);
+        store.delete_range(20, 30).unwrap();
+
+        let pages = store.search_pages("", 10).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!((pages[0].url.as_str(), pages[0].visit_count, pages[0].last_visit_time), ("https://a.com/", 2, 30));
+    }
+
+    #[test]
+    fn clear_removes_everything() {
+        let store = store_with(&[("https://a.com/", "A", 1), ("https://b.com/", "B", 2)]);
+        store.clear().unwrap();
+        assert!(store.query(&HistoryQuery::default()).unwrap().is_empty());
+        assert!(store.search_pages("", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_web_and_file_pages_are_recorded() {
+        assert!(is_recordable("https://a.com/"));
+        assert!(is_recordable("file:///home/me/page.html"));
+        assert!(!is_recordable("about:blank"));
+        assert!(!is_recordable("data:text/html,hi"));
+    }
+}
@@ -1,12 +1,27 @@
-use tauri::{Emitter, Manager, LogicalPosition, LogicalSize, PhysicalSize};
+use tauri::{Emitter, Manager, PhysicalSize};
 
 mod constants;
+mod clock;
 mod window;
 mod navigation;
 mod window_controls;
 mod url_monitor;
 mod titlebar;
 mod search;
+mod omnibox;
+mod history;
+mod storage;
+mod bookmarks;
+mod settings;
+mod popup_rules;
+mod popup_blocker;
+mod tabs;
+mod split_view;
+mod hibernation;
+mod session;
+mod closed_windows;
+mod profiles;
+mod containers;
 
 use constants::{
     TITLE_BAR_HEIGHT,
@@ -21,6 +36,13 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(url_monitor::UrlMonitorRegistry::default())
+        .manage(search::SuggestionService::default())
+        .manage(search::OpenSearchDiscovery::default())
+        .manage(tabs::TabRegistry::default())
+        .manage(hibernation::HibernationManager::default())
+        .manage(closed_windows::ClosedWindows::default())
+        .manage(containers::ContainerRegistry::default())
         .setup(|app| {
             // Resize main window to percentage-based size BEFORE React loads
             // This eliminates the size flash that would occur if done in React
@@ -35,10 +57,30 @@ pub fn run() {
                     let _ = main_window.center();
                 }
             }
+
+            // Bookmarks, settings and history are the active profile's
+            let app_data = app.path().app_data_dir()?;
+            std::fs::create_dir_all(&app_data)?;
+            let profiles = profiles::ProfileManager::load(&app_data);
+            let profile_dir = profiles.active_dir();
+            std::fs::create_dir_all(&profile_dir)?;
+            app.manage(profiles);
+
+            // Open the history database, falling back to memory so a bad file never blocks startup
+            let history_store = history::HistoryStore::open(&profile_dir.join(history::HISTORY_DB_FILE))
+                .or_else(|_| history::HistoryStore::open_in_memory())?;
+            app.manage(history_store);
+            app.manage(bookmarks::BookmarkStore::load(&profile_dir));
+            app.manage(settings::SettingsStore::load(&profile_dir));
+            hibernation::start(app.handle());
+            app.manage(session::SessionManager::load(&app_data));
+            session::start(app.handle());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             window::create_content_window,
+            window::create_private_window,
             navigation::navigate_to_url,
             navigation::go_back,
             navigation::go_forward,
@@ -48,65 +90,148 @@ pub fn run() {
             window_controls::close_browser_window,
             window_controls::minimize_browser_window,
             window_controls::toggle_maximize_browser_window,
-            url_monitor::get_current_url,
-            url_monitor::setup_url_monitor,
+            url_monitor::get_page_state,
+            url_monitor::report_page_info,
+            hibernation::report_scroll_position,
+            tabs::get_tabs,
+            tabs::create_tab,
+            tabs::close_tab,
+            tabs::activate_tab,
+            tabs::move_tab,
+            split_view::enter_split_view,
+            split_view::leave_split_view,
+            split_view::swap_split_panes,
+            split_view::resize_split_divider,
             titlebar::titlebar_minimize,
             titlebar::titlebar_maximize,
             titlebar::titlebar_close,
             titlebar::titlebar_drag,
-            search::get_search_suggestions
+            search::get_search_suggestions,
+            search::engines::get_search_engines,
+            search::engines::add_search_engine,
+            search::engines::update_search_engine,
+            search::engines::remove_search_engine,
+            search::opensearch::get_search_engine_offers,
+            search::opensearch::accept_search_engine_offer,
+            search::opensearch::ignore_search_engine_offer,
+            omnibox::resolve_omnibox_input,
+            history::query_history,
+            history::delete_history_entry,
+            history::delete_history_range,
+            history::clear_history,
+            bookmarks::get_bookmarks,
+            bookmarks::add_bookmark,
+            bookmarks::update_bookmark,
+            bookmarks::delete_bookmark,
+            bookmarks::reorder_bookmarks,
+            bookmarks::set_bookmark_starred,
+            bookmarks::set_bookmark_tags,
+            bookmarks::list_bookmark_tags,
+            bookmarks::create_bookmark_folder,
+            bookmarks::rename_bookmark_folder,
+            bookmarks::delete_bookmark_folder,
+            bookmarks::move_bookmark_item,
+            bookmarks::get_bookmark_folder,
+            bookmarks::query_bookmarks,
+            bookmarks::clear_bookmarks,
+            bookmarks::import_bookmarks_file,
+            bookmarks::export_bookmarks_html,
+            settings::get_settings,
+            settings::set_setting,
+            settings::reset_settings,
+            popup_blocker::open_blocked_popup,
+            popup_blocker::allow_popups_for_site,
+            session::get_session_restore_offer,
+            session::restore_last_session,
+            session::dismiss_session_restore,
+            session::save_session,
+            session::list_sessions,
+            session::restore_session,
+            session::delete_session,
+            closed_windows::list_closed_windows,
+            closed_windows::reopen_closed_window,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::rename_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            containers::get_containers,
+            containers::create_container,
+            containers::update_container,
+            containers::delete_container
         ])
         .on_window_event(|window, event| {
             match event {
+                // Closing the main window quits: record the session while its windows still exist
+                tauri::WindowEvent::CloseRequested { .. } if window.label() == "main" => {
+                    session::save_on_exit(window.app_handle());
+                }
+                // Remember the window for reopening while its tabs are still there
+                tauri::WindowEvent::CloseRequested { .. } if window.label().starts_with("window-") => {
+                    closed_windows::closing(window.app_handle(), window.label());
+                }
                 tauri::WindowEvent::Destroyed => {
                     let label = window.label().to_string();
+                    // The panel's webview shares the main window's label
+                    if label == "main" {
+                        window.state::<search::SuggestionService>().forget(&label);
+                    }
                     // Emit for window- prefixed windows (multi-webview windows)
                     if label.starts_with("window-") {
-                        // Extract the ID and emit with the content webview label
-                        let id = label.trim_start_matches("window-");
-                        let content_label = format!("content-{}", id);
+                        let app = window.app_handle();
+                        closed_windows::closed(app, &label);
+                        let private = tabs::is_private_window(app, &label);
+                        let active_tab = app.state::<tabs::TabRegistry>().active_tab(&label);
+                        let mut closed_tabs = app.state::<tabs::TabRegistry>().remove_window(&label);
+                        if closed_tabs.is_empty() {
+                            // Not registered: the first tab shares the window's ID
+                            closed_tabs.push(format!("content-{}", label.trim_start_matches("window-")));
+                        }
+                        for tab in &closed_tabs {
+                            app.state::<url_monitor::UrlMonitorRegistry>().unsubscribe(tab);
+                            app.state::<hibernation::HibernationManager>().forget(tab);
+                            app.state::<search::SuggestionService>().forget(tab);
+                        }
+                        app.state::<profiles::ProfileManager>().forget_window(&label);
+                        app.state::<containers::ContainerRegistry>().forget_window(&label);
                         let _ = window.emit("window-closed", serde_json::json!({
-                            "windowLabel": content_label
+                            "windowLabel": active_tab.unwrap_or_else(|| closed_tabs[0].clone()),
+                            "parentLabel": label,
+                            "tabs": closed_tabs,
+                            "private": private
                         }));
                     }
                 }
-                tauri::WindowEvent::Resized(size) => {
+                tauri::WindowEvent::Resized(_) => {
                     let label = window.label().to_string();
                     // Handle resize for multi-webview windows
                     if label.starts_with("window-") {
-                        let id = label.trim_start_matches("window-");
-                        // COMMENTED OUT: Title bar webview resize (using native decorations now)
-                        // let titlebar_label = format!("titlebar-{}", id);
-                        let content_label = format!("content-{}", id);
-                        
-                        let width = size.width as f64;
-                        let height = size.height as f64;
-                        
                         // ============================================================================
-                        // COMMENTED OUT: Title bar webview bounds update
+                        // COMMENTED OUT: Title bar webview bounds update (using native decorations now)
                         // Uncomment this section to restore custom React title bar
                         // ============================================================================
                         // // Update title bar webview bounds
+                        // let titlebar_label = format!("titlebar-{}", label.trim_start_matches("window-"));
                         // if let Some(titlebar) = window.app_handle().get_webview(&titlebar_label) {
                         //     let _ = titlebar.set_bounds(tauri::Rect {
                         //         position: LogicalPosition::new(0.0, 0.0).into(),
-                        //         size: LogicalSize::new(width, TITLE_BAR_HEIGHT).into(),
+                        //         size: LogicalSize::new(size.width as f64, TITLE_BAR_HEIGHT).into(),
                         //     });
                         // }
                         // ============================================================================
-                        
-                        // Update content webview bounds (now fills entire window)
-                        if let Some(content) = window.app_handle().get_webview(&content_label) {
-                            let _ = content.set_bounds(tauri::Rect {
-                                position: LogicalPosition::new(0.0, 0.0).into(),
-                                size: LogicalSize::new(width, height).into(),
-                            });
-                        }
+
+                        // Size the active tab (or split view panes) to the window; the others are sized when shown
+                        tabs::layout_window(window.app_handle(), &label);
                     }
                 }
                 _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                session::save_on_exit(app);
+            }
+        });
 }
@@ -1,54 +1,192 @@
-use tauri::Manager;
+use std::fmt;
+
+use serde::Serialize;
+use tauri::{Manager, Url};
+
+/// Schemes a content webview is allowed to be pointed at. `file` stays: only the panel
+/// can call the navigation commands, and a typed `file:` URL there is deliberate.
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "file", "about"];
+
+/// Structured navigation failure returned to the frontend
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum NavigationError {
+    /// The input could not be parsed as a URL
+    InvalidUrl(String),
+    /// The URL parsed but uses a scheme we refuse to load (javascript:, data:, ...)
+    BlockedScheme(String),
+    /// No webview is registered under the given label
+    WebviewNotFound(String),
+    /// The webview rejected the operation
+    Webview(String),
+}
+
+impl fmt::Display for NavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NavigationError::InvalidUrl(url) => write!(f, "Invalid URL: {}", url),
+            NavigationError::BlockedScheme(scheme) => write!(f, "Blocked URL scheme: {}", scheme),
+            NavigationError::WebviewNotFound(label) => write!(f, "Webview not found: {}", label),
+            NavigationError::Webview(message) => write!(f, "Webview error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for NavigationError {}
+
+/// Validate and normalize a URL before handing it to a webview.
+/// Inputs without a scheme (e.g. `example.com/path`) are treated as https.
+pub fn normalize_url(input: &str) -> Result<Url, NavigationError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(NavigationError::InvalidUrl(input.to_string()));
+    }
+
+    let url = match Url::parse(trimmed) {
+        // `localhost:1420` parses with "localhost" as the scheme - it's really a host and port
+        Ok(_) if is_host_with_port(trimmed) => {
+            Url::parse(&format!("https://{}", trimmed))
+                .map_err(|_| NavigationError::InvalidUrl(input.to_string()))?
+        }
+        Ok(url) => url,
+        // No scheme at all - assume https like the address bar does
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Url::parse(&format!("https://{}", trimmed))
+                .map_err(|_| NavigationError::InvalidUrl(input.to_string()))?
+        }
+        Err(_) => return Err(NavigationError::InvalidUrl(input.to_string())),
+    };
+
+    if !ALLOWED_SCHEMES.contains(&url.scheme()) {
+        return Err(NavigationError::BlockedScheme(url.scheme().to_string()));
+    }
+
+    // Web URLs must point somewhere
+    if matches!(url.scheme(), "http" | "https") && url.host_str().is_none_or(str::is_empty) {
+        return Err(NavigationError::InvalidUrl(input.to_string()));
+    }
+
+    Ok(url)
+}
+
+/// True for `host:port[/...]` inputs, where the part after the colon is a port number
+fn is_host_with_port(input: &str) -> bool {
+    match input.split_once(':') {
+        Some((_, rest)) => {
+            let port = rest.split(['/', '?', '#']).next().unwrap_or("");
+            !port.is_empty() && port.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Look up a content webview by label
+fn get_content_webview(
+    app: &tauri::AppHandle,
+    window_label: &str,
+) -> Result<tauri::Webview, NavigationError> {
+    // Use get_webview for child webviews in multi-webview architecture
+    app.get_webview(window_label)
+        .ok_or_else(|| NavigationError::WebviewNotFound(window_label.to_string()))
+}
 
 #[tauri::command]
 pub async fn navigate_to_url(
     app: tauri::AppHandle,
     window_label: String,
     url: String,
-) -> Result<(), String> {
-    // Use get_webview for child webviews in multi-webview architecture
-    if let Some(webview) = app.get_webview(&window_label) {
-        webview.eval(&format!("window.location.href = '{}'", url))
-            .map_err(|e| e.to_string())?;
-    }
-    Ok(())
+) -> Result<(), NavigationError> {
+    let url = normalize_url(&url)?;
+    let webview = get_content_webview(&app, &window_label)?;
+    webview.navigate(url)
+        .map_err(|e| NavigationError::Webview(e.to_string()))
 }
 
 #[tauri::command]
 pub async fn go_back(
     app: tauri::AppHandle,
     window_label: String,
-) -> Result<(), String> {
-    // Use get_webview for child webviews in multi-webview architecture
-    if let Some(webview) = app.get_webview(&window_label) {
-        webview.eval("window.history.back()")
-            .map_err(|e| e.to_string())?;
-    }
-    Ok(())
+) -> Result<(), NavigationError> {
+    // The webview has no native history API, so this is a fixed script with no user input
+    let webview = get_content_webview(&app, &window_label)?;
+    webview.eval("window.history.back()")
+        .map_err(|e| NavigationError::Webview(e.to_string()))
 }
 
 #[tauri::command]
 pub async fn go_forward(
     app: tauri::AppHandle,
     window_label: String,
-) -> Result<(), String> {
-    // Use get_webview for child webviews in multi-webview architecture
-    if let Some(webview) = app.get_webview(&window_label) {
-        webview.eval("window.history.forward()")
-            .map_err(|e| e.to_string())?;
-    }
-    Ok(())
+) -> Result<(), NavigationError> {
+    let webview = get_content_webview(&app, &window_label)?;
+    webview.eval("window.history.forward()")
+        .map_err(|e| NavigationError::Webview(e.to_string()))
 }
 
 #[tauri::command]
 pub async fn reload_page(
     app: tauri::AppHandle,
     window_label: String,
-) -> Result<(), String> {
-    // Use get_webview for child webviews in multi-webview architecture
-    if let Some(webview) = app.get_webview(&window_label) {
-        webview.eval("window.location.reload()")
-            .map_err(|e| e.to_string())?;
+) -> Result<(), NavigationError> {
+    let webview = get_content_webview(&app, &window_label)?;
+    webview.reload()
+        .map_err(|e| NavigationError::Webview(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocked_scheme(input: &str) -> Option<String> {
+        match normalize_url(input) {
+            Err(NavigationError::BlockedScheme(scheme)) => Some(scheme),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn inputs_without_a_scheme_become_https() {
+        assert_eq!(normalize_url("example.com").unwrap().as_str(), "https://example.com/");
+        assert_eq!(normalize_url("  example.com/a?b=1  ").unwrap().as_str(), "https://example.com/a?b=1");
+        assert_eq!(normalize_url("http://example.com/").unwrap().scheme(), "http");
+    }
+
+    #[test]
+    fn host_and_port_inputs_are_not_schemes() {
+        let url = normalize_url("localhost:3000").unwrap();
+        assert_eq!((url.scheme(), url.host_str(), url.port()), ("https", Some("localhost"), Some(3000)));
+        let url = normalize_url("example.com:8080/path").unwrap();
+        assert_eq!(url.as_str(), "https://example.com:8080/path");
+
+        assert!(is_host_with_port("localhost:3000"));
+        assert!(is_host_with_port("example.com:8080/path?q#f"));
+        assert!(!is_host_with_port("mailto:someone@example.com"));
+        assert!(!is_host_with_port("localhost:"));
+        assert!(!is_host_with_port("example.com"));
+    }
+
+    #[test]
+    fn quotes_are_escaped_not_interpreted() {
+        let url = normalize_url(r#"https://example.com/it's "quoted"?q='x'"#).unwrap();
+        assert_eq!(url.host_str(), Some("example.com"));
+        assert!(!url.as_str().contains('"'), "{}", url);
+        assert!(normalize_url("example.com/a'b").is_ok());
+    }
+
+    #[test]
+    fn script_and_data_urls_are_blocked() {
+        assert_eq!(blocked_scheme("javascript:alert(1)").as_deref(), Some("javascript"));
+        assert_eq!(blocked_scheme("JavaScript:alert(document.cookie)").as_deref(), Some("javascript"));
+        assert_eq!(blocked_scheme("data:text/html,<script>alert(1)</script>").as_deref(), Some("data"));
+        assert_eq!(blocked_scheme("blob:https://example.com/uuid").as_deref(), Some("blob"));
+        assert!(normalize_url("about:blank").is_ok());
+        assert!(normalize_url("file:///tmp/page.html").is_ok());
+    }
+
+    #[test]
+    fn empty_and_hostless_inputs_are_invalid() {
+        for input in ["", "   ", "\t\n", "https://"] {
+            assert!(matches!(normalize_url(input), Err(NavigationError::InvalidUrl(_))), "{:?}", input);
+        }
     }
-    Ok(())
 }
@@ -0,0 +1,389 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde::Serialize;
+use tauri::Url;
+
+use crate::navigation::normalize_url;
+use crate::search;
+use crate::settings::{self, Settings};
+
+/// What the user typed into an address/search box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OmniboxInputKind {
+    /// Full URL with an explicit scheme (`https://example.com`)
+    Url,
+    /// Host without a scheme (`example.com/docs`)
+    BareHost,
+    /// `localhost`, optionally with a port and path
+    LocalhostPort,
+    /// IPv4 or bracketed IPv6 address, optionally with a port
+    IpLiteral,
+    /// Local file (`/home/me/a.html`, `C:\a.html`, `file:///...`)
+    FilePath,
+    /// Browser-internal page such as `about:blank`
+    AboutPage,
+    /// Search with an engine picked by keyword (`gh rust`) or bang (`!gh rust`)
+    Keyword,
+    /// Anything else is sent to the search engine
+    Search,
+}
+
+/// Result of resolving omnibox input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OmniboxResolution {
+    pub kind: OmniboxInputKind,
+    /// Final URL to load
+    pub url: String,
+}
+
+impl OmniboxResolution {
+    fn new(kind: OmniboxInputKind, url: impl Into<String>) -> Self {
+        Self { kind, url: url.into() }
+    }
+}
+
+/// Classify input and build the URL to load, searching with the default engine when it isn't a URL
+pub fn resolve(input: &str, settings: &Settings) -> Option<OmniboxResolution> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    if let Some(explicit) = search::explicit_search(input, &settings.engines()) {
+        return Some(OmniboxResolution::new(OmniboxInputKind::Keyword, explicit.url()));
+    }
+
+    let engine = settings.default_engine();
+    let search = || OmniboxResolution::new(OmniboxInputKind::Search, engine.search_url(input));
+
+    if input.len() > 6 && input.get(..6).is_some_and(|prefix| prefix.eq_ignore_ascii_case("about:")) {
+        return Some(OmniboxResolution::new(OmniboxInputKind::AboutPage, input.to_ascii_lowercase()));
+    }
+
+    if let Some(url) = file_url(input) {
+        return Some(OmniboxResolution::new(OmniboxInputKind::FilePath, url));
+    }
+
+    // Explicit scheme: trust it if navigation would accept it, otherwise search for the text
+    if input.contains("://") {
+        return Some(match normalize_url(input) {
+            Ok(url) if url.scheme() == "file" => {
+                OmniboxResolution::new(OmniboxInputKind::FilePath, url.to_string())
+            }
+            Ok(url) => OmniboxResolution::new(OmniboxInputKind::Url, url.to_string()),
+            Err(_) => search(),
+        });
+    }
+
+    // Whitespace is only acceptable after the host, e.g. in a query string
+    let (authority, rest) = split_authority(input);
+    if authority.is_empty() || authority.chars().any(char::is_whitespace) {
+        return Some(search());
+    }
+
+    let Some((host, port)) = split_port(authority) else {
+        return Some(search());
+    };
+
+    let kind = if host.eq_ignore_ascii_case("localhost") {
+        OmniboxInputKind::LocalhostPort
+    } else if is_ip_literal(host) {
+        OmniboxInputKind::IpLiteral
+    } else if is_domain(host, port.is_some() || !rest.is_empty()) {
+        OmniboxInputKind::BareHost
+    } else {
+        return Some(search());
+    };
+
+    // Local and IP targets are usually dev servers without TLS
+    let scheme = match kind {
+        OmniboxInputKind::BareHost => "https",
+        _ => "http",
+    };
+
+    match Url::parse(&format!("{}://{}", scheme, input)) {
+        Ok(url) => Some(OmniboxResolution::new(kind, url.to_string())),
+        Err(_) => Some(search()),
+    }
+}
+
+/// Split `host[:port]` from the path/query/fragment that follows it
+fn split_authority(input: &str) -> (&str, &str) {
+    match input.find(['/', '?', '#']) {
+        Some(index) => input.split_at(index),
+        None => (input, ""),
+    }
+}
+
+/// Split `host:port`, returning `None` when the port isn't a valid number
+fn split_port(authority: &str) -> Option<(&str, Option<u16>)> {
+    // Bracketed IPv6: `[::1]` or `[::1]:8080`
+    if authority.starts_with('[') {
+        let end = authority.find(']')?;
+        let (host, after) = authority.split_at(end + 1);
+        return match after.strip_prefix(':') {
+            Some(port) => Some((host, Some(port.parse().ok()?))),
+            None if after.is_empty() => Some((host, None)),
+            None => None,
+        };
+    }
+
+    match authority.rsplit_once(':') {
+        Some((host, port)) => Some((host, Some(port.parse().ok()?))),
+        None => Some((authority, None)),
+    }
+}
+
+fn is_ip_literal(host: &str) -> bool {
+    if let Some(inner) = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        return inner.parse::<Ipv6Addr>().is_ok();
+    }
+    host.parse::<Ipv4Addr>().is_ok()
+}
+
+/// Dotted hostname with valid labels and an alphabetic TLD.
+/// Single-letter TLDs (`e.g`) only count when a port or path makes the intent clear.
+fn is_domain(host: &str, has_url_parts: bool) -> bool {
+    let labels: Vec<&str> = host.trim_end_matches('.').split('.').collect();
+    if labels.len() < 2 {
+        return false;
+    }
+
+    let valid_labels = labels.iter().all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    });
+
+    let tld = labels[labels.len() - 1];
+    let valid_tld = tld.starts_with("xn--")
+        || (tld.chars().all(char::is_alphabetic) && (tld.chars().count() >= 2 || has_url_parts));
+
+    valid_labels && valid_tld
+}
+
+/// Convert local file paths (Unix, Windows drive, `file:` URLs) into a file URL
+fn file_url(input: &str) -> Option<String> {
+    if input.get(..5).is_some_and(|prefix| prefix.eq_ignore_ascii_case("file:")) {
+        return Url::parse(input).ok().map(|url| url.to_string());
+    }
+
+    let bytes = input.as_bytes();
+    let is_windows_drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+
+    let path = if is_windows_drive {
+        format!("/{}", input.replace('\\', "/"))
+    } else if input.starts_with('/') && !input.starts_with("//") {
+        input.to_string()
+    } else {
+        return None;
+    };
+
+    Url::parse(&format!("file://{}", path)).ok().map(|url| url.to_string())
+}
+
+#[tauri::command]
+pub async fn resolve_omnibox_input(
+    app: tauri::AppHandle,
+    input: String,
+) -> Result<OmniboxResolution, String> {
+    resolve(&input, &settings::current(&app)).ok_or_else(|| "Empty input".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve_google(input: &str) -> OmniboxResolution {
+        resolve(input, &Settings::default()).expect("non-empty input")
+    }
+
+    #[test]
+    fn empty_input_resolves_to_nothing() {
+        assert_eq!(resolve("   ", &Settings::default()), None);
+    }
+
+    #[test]
+    fn explicit_scheme_is_a_url() {
+        let result = resolve_google("https://example.com/a?b=c");
+        assert_eq!(result.kind, OmniboxInputKind::Url);
+        assert_eq!(result.url, "https://example.com/a?b=c");
+    }
+
+    #[test]
+    fn bare_host_gets_https() {
+        let result = resolve_google("example.com");
+        assert_eq!(result.kind, OmniboxInputKind::BareHost);
+        assert_eq!(result.url, "https://example.com/");
+    }
+
+    #[test]
+    fn space_in_host_is_a_search() {
+        let result = resolve_google("foo.bar baz");
+        assert_eq!(result.kind, OmniboxInputKind::Search);
+        assert_eq!(result.url, "https://www.google.com/search?q=foo.bar%20baz");
+    }
+
+    #[test]
+    fn space_in_query_string_is_still_a_url() {
+        let result = resolve_google("a.b/c?d=e f");
+        assert_eq!(result.kind, OmniboxInputKind::BareHost);
+        assert_eq!(result.url, "https://a.b/c?d=e%20f");
+    }
+
+    #[test]
+    fn localhost_with_port() {
+        let result = resolve_google("localhost:1420");
+        assert_eq!(result.kind, OmniboxInputKind::LocalhostPort);
+        assert_eq!(result.url, "http://localhost:1420/");
+    }
+
+    #[test]
+    fn plain_localhost() {
+        let result = resolve_google("localhost/admin");
+        assert_eq!(result.kind, OmniboxInputKind::LocalhostPort);
+        assert_eq!(result.url, "http://localhost/admin");
+    }
+
+    #[test]
+    fn ipv6_literal() {
+        let result = resolve_google("[::1]");
+        assert_eq!(result.kind, OmniboxInputKind::IpLiteral);
+        assert_eq!(result.url, "http://[::1]/");
+
+        let result = resolve_google("[::1]:8080/x");
+        assert_eq!(result.kind, OmniboxInputKind::IpLiteral);
+        assert_eq!(result.url, "http://[::1]:8080/x");
+    }
+
+    #[test]
+    fn ipv4_literal() {
+        let result = resolve_google("192.168.1.10:3000");
+        assert_eq!(result.kind, OmniboxInputKind::IpLiteral);
+        assert_eq!(result.url, "http://192.168.1.10:3000/");
+    }
+
+    #[test]
+    fn decimal_number_is_a_search() {
+        assert_eq!(resolve_google("3.14").kind, OmniboxInputKind::Search);
+    }
+
+    #[test]
+    fn single_letter_tld_without_path_is_a_search() {
+        assert_eq!(resolve_google("e.g").kind, OmniboxInputKind::Search);
+    }
+
+    #[test]
+    fn invalid_port_is_a_search() {
+        assert_eq!(resolve_google("localhost:abc").kind, OmniboxInputKind::Search);
+    }
+
+    #[test]
+    fn unix_file_path() {
+        let result = resolve_google("/home/me/My Page.html");
+        assert_eq!(result.kind, OmniboxInputKind::FilePath);
+        assert_eq!(result.url, "file:///home/me/My%20Page.html");
+    }
+
+    #[test]
+    fn windows_file_path() {
+        let result = resolve_google(r"C:\Users\me\page.html");
+        assert_eq!(result.kind, OmniboxInputKind::FilePath);
+        assert_eq!(result.url, "file:///C:/Users/me/page.html");
+    }
+
+    #[test]
+    fn about_page() {
+        let result = resolve_google("about:Blank");
+        assert_eq!(result.kind, OmniboxInputKind::AboutPage);
+        assert_eq!(result.url, "about:blank");
+    }
+
+    #[test]
+    fn blocked_scheme_is_searched_instead() {
+        assert_eq!(resolve_google("javascript://alert(1)").kind, OmniboxInputKind::Search);
+    }
+
+    fn with_custom_engine() -> Settings {
+        Settings {
+            search_engine: "code".to_string(),
+            custom_search_engines: vec![search::SearchEngine {
+                id: "code".to_string(),
+                name: "Code Search".to_string(),
+                keyword: Some("cs".to_string()),
+                search_url: "https://code.example.com/search?q={searchTerms}&lang=rust".to_string(),
+                suggest_url: None,
+                icon_url: None,
+            }],
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn search_uses_configured_engine() {
+        let settings = Settings {
+            search_engine: "duckduckgo".to_string(),
+            ..Settings::default()
+        };
+        let result = resolve("rust tauri", &settings).unwrap();
+        assert_eq!(result.kind, OmniboxInputKind::Search);
+        assert_eq!(result.url, "https://duckduckgo.com/?q=rust%20tauri");
+    }
+
+    #[test]
+    fn custom_engine_template_as_default() {
+        let result = resolve("async fn", &with_custom_engine()).unwrap();
+        assert_eq!(result.kind, OmniboxInputKind::Search);
+        assert_eq!(result.url, "https://code.example.com/search?q=async%20fn&lang=rust");
+    }
+
+    #[test]
+    fn keyword_searches_its_engine() {
+        let result = resolve("cs tokio select", &with_custom_engine()).unwrap();
+        assert_eq!(result.kind, OmniboxInputKind::Keyword);
+        assert_eq!(result.url, "https://code.example.com/search?q=tokio%20select&lang=rust");
+    }
+
+    #[test]
+    fn keyword_alone_is_an_ordinary_search() {
+        let result = resolve("cs", &with_custom_engine()).unwrap();
+        assert_eq!(result.kind, OmniboxInputKind::Search);
+    }
+
+    #[test]
+    fn leading_and_trailing_bangs() {
+        let result = resolve_google("!gh rust tauri");
+        assert_eq!(result.kind, OmniboxInputKind::Keyword);
+        assert_eq!(result.url, "https://github.com/search?q=rust%20tauri");
+
+        let result = resolve_google("rust tauri !W");
+        assert_eq!(result.kind, OmniboxInputKind::Keyword);
+        assert_eq!(result.url, "https://en.wikipedia.org/wiki/Special:Search?search=rust%20tauri");
+    }
+
+    #[test]
+    fn keyword_works_as_a_bang() {
+        let result = resolve("!cs lifetimes", &with_custom_engine()).unwrap();
+        assert_eq!(result.url, "https://code.example.com/search?q=lifetimes&lang=rust");
+    }
+
+    #[test]
+    fn bang_without_query_opens_the_site() {
+        let result = resolve_google("!yt");
+        assert_eq!(result.kind, OmniboxInputKind::Keyword);
+        assert_eq!(result.url, "https://www.youtube.com/");
+    }
+
+    #[test]
+    fn unknown_bang_is_searched_as_typed() {
+        let result = resolve_google("!important css");
+        assert_eq!(result.kind, OmniboxInputKind::Search);
+        assert_eq!(result.url, "https://www.google.com/search?q=%21important%20css");
+    }
+}
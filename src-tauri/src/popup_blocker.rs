@@ -0,0 +1,245 @@
+use serde::Serialize;
+use tauri::{Emitter, Manager, Url};
+
+use crate::navigation::{self, NavigationError};
+use crate::popup_rules::{self, PopupAction};
+use crate::settings::{self, SettingsStore};
+use crate::window;
+
+/// Scheme of the stand-in URL the blocker script opens instead of a popup without a user gesture
+const BLOCKED_SCHEME: &str = "meikai-popup-blocked";
+
+/// How long after a click or key press a popup still counts as user-initiated,
+/// where the webview doesn't support `navigator.userActivation`
+const GESTURE_WINDOW_MS: u32 = 1000;
+
+/// Injected into every frame of content webviews. `on_new_window` can't tell whether a page
+/// asked for a popup on its own, so `window.open()` calls without a user gesture are turned
+/// into a request for a `meikai-popup-blocked:` URL carrying the real one.
+pub fn blocker_script() -> String {
+    format!(
+        r#"(function () {{
+  var open = window.open;
+  var lastGesture = 0;
+  ["pointerdown", "keydown", "click", "touchend"].forEach(function (type) {{
+    window.addEventListener(type, function (event) {{
+      if (event.isTrusted) lastGesture = Date.now();
+    }}, true);
+  }});
+  function hasGesture() {{
+    if (navigator.userActivation) return navigator.userActivation.isActive;
+    return Date.now() - lastGesture < {gesture_ms};
+  }}
+  window.open = function (url) {{
+    if (hasGesture()) return open.apply(window, arguments);
+    try {{
+      var target = new URL(url === undefined || url === "" ? "about:blank" : String(url), location.href).href;
+      open.call(window, "{scheme}:" + encodeURIComponent(target), "_blank");
+    }} catch (error) {{}}
+    return null;
+  }};
+}})();"#,
+        gesture_ms = GESTURE_WINDOW_MS,
+        scheme = BLOCKED_SCHEME,
+    )
+}
+
+/// The popup a page tried to open without a user gesture, if this is the blocker's stand-in URL
+pub fn blocked_target(url: &Url) -> Option<Url> {
+    if url.scheme() != BLOCKED_SCHEME {
+        return None;
+    }
+    let target = urlencoding::decode(url.path()).ok()?;
+    Url::parse(&target).ok()
+}
+
+/// Allowlist entry for a page: its host without `www.`
+fn site_of(url: &Url) -> Option<String> {
+    url.host_str().map(|host| host.trim_start_matches("www.").to_ascii_lowercase())
+}
+
+/// `example.com` on the allowlist also covers its subdomains
+pub fn is_site_allowed(host: &str, allowlist: &[String]) -> bool {
+    allowlist.iter().any(|site| {
+        host.eq_ignore_ascii_case(site)
+            || (host.len() > site.len()
+                && host[host.len() - site.len()..].eq_ignore_ascii_case(site)
+                && host[..host.len() - site.len()].ends_with('.'))
+    })
+}
+
+/// Check the allowlist, for settings
+pub fn validate_allowlist(allowlist: &[String]) -> Result<(), String> {
+    for site in allowlist {
+        if site.is_empty() || site.contains(['/', ':', '*']) || site.chars().any(char::is_whitespace) {
+            return Err(format!("Popup allowlist entries must be host names like example.com (got \"{}\")", site));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the page in a content webview may open popups without a user gesture
+pub fn opener_allowed(app: &tauri::AppHandle, opener_label: &str, allowlist: &[String]) -> bool {
+    app.get_webview(opener_label)
+        .and_then(|webview| webview.url().ok())
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| is_site_allowed(&host, allowlist))
+}
+
+/// Where a popup that was held back goes once it's allowed. The webview's own request was for
+/// the stand-in URL, so there's no native popup left to allow and it opens in a tab instead.
+pub fn released_action(url: &Url, rules: &popup_rules::CompiledRules) -> PopupAction {
+    match popup_rules::route(url, rules) {
+        PopupAction::NativePopup => PopupAction::NewTab,
+        action => action,
+    }
+}
+
+/// Payload of `popup-blocked`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedPopup {
+    /// Content webview whose page asked for the popup
+    pub window_label: String,
+    pub url: String,
+    /// Allowlist entry that would let the page's popups through
+    pub site: Option<String>,
+}
+
+/// Tell the dock a popup was blocked so it can offer to open it anyway
+pub fn report_blocked(app: &tauri::AppHandle, opener_label: &str, url: &Url) {
+    let site = app
+        .get_webview(opener_label)
+        .and_then(|webview| webview.url().ok())
+        .and_then(|opener| site_of(&opener));
+    let _ = app.emit("popup-blocked", BlockedPopup {
+        window_label: opener_label.to_string(),
+        url: url.to_string(),
+        site,
+    });
+}
+
+/// Check a popup URL handed back by the frontend like any other navigation. Local files are
+/// refused as well, since the URL came from a web page.
+fn parse_popup_url(url: &str) -> Result<Url, String> {
+    let url = navigation::normalize_url(url).map_err(|e| e.to_string())?;
+    if url.scheme() == "file" {
+        return Err(NavigationError::BlockedScheme(url.scheme().to_string()).to_string());
+    }
+    Ok(url)
+}
+
+/// "Open anyway" for a blocked popup
+#[tauri::command]
+pub async fn open_blocked_popup(
+    app: tauri::AppHandle,
+    window_label: String,
+    url: String,
+) -> Result<(), String> {
+    let url = parse_popup_url(&url)?;
+    let action = released_action(&url, &settings::popup_rules(&app));
+    window::open_popup(&app, &window_label, url, action);
+    Ok(())
+}
+
+/// "Always allow for this site": allowlist the site, then open the popup that was blocked
+#[tauri::command]
+pub async fn allow_popups_for_site(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    window_label: String,
+    site: String,
+    url: String,
+) -> Result<(), String> {
+    let url = parse_popup_url(&url)?;
+    let site = site.trim().trim_start_matches("www.").to_ascii_lowercase();
+    store
+        .update(&app, |settings| {
+            let mut updated = settings.clone();
+            if !updated.popup_allowlist.contains(&site) {
+                updated.popup_allowlist.push(site.clone());
+            }
+            Ok(updated)
+        })
+        .map_err(|e| e.to_string())?;
+
+    window::open_popup(&app, &window_label, url.clone(), released_action(&url, &store.popup_rules()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(text: &str) -> Url {
+        Url::parse(text).unwrap()
+    }
+
+    #[test]
+    fn blocked_target_unwraps_the_stand_in_url() {
+        let target = "https://example.com/offer?id=1&ref=a b";
+        let stand_in = format!("{}:{}", BLOCKED_SCHEME, urlencoding::encode(target));
+        assert_eq!(
+            blocked_target(&url(&stand_in)).unwrap().as_str(),
+            "https://example.com/offer?id=1&ref=a%20b"
+        );
+        assert_eq!(blocked_target(&url("https://example.com/")), None);
+        assert_eq!(blocked_target(&url(&format!("{}:not%20a%20url", BLOCKED_SCHEME))), None);
+    }
+
+    #[test]
+    fn allowlisted_sites_include_subdomains() {
+        let allowlist = vec!["example.com".to_string(), "Bank.co.uk".to_string()];
+        assert!(is_site_allowed("example.com", &allowlist));
+        assert!(is_site_allowed("app.example.com", &allowlist));
+        assert!(is_site_allowed("a.b.EXAMPLE.com", &allowlist));
+        assert!(is_site_allowed("online.bank.co.uk", &allowlist));
+        assert!(!is_site_allowed("notexample.com", &allowlist));
+        assert!(!is_site_allowed("example.com.evil.net", &allowlist));
+        assert!(!is_site_allowed("com", &allowlist));
+        assert!(!is_site_allowed("example.com", &[]));
+    }
+
+    #[test]
+    fn sites_drop_www() {
+        assert_eq!(site_of(&url("https://www.Example.com/a")).as_deref(), Some("example.com"));
+        assert_eq!(site_of(&url("https://app.example.com/")).as_deref(), Some("app.example.com"));
+        assert_eq!(site_of(&url("about:blank")), None);
+    }
+
+    #[test]
+    fn allowlist_entries_must_be_host_names() {
+        assert_eq!(validate_allowlist(&["example.com".to_string(), "localhost".to_string()]), Ok(()));
+        let invalid = ["", "https://example.com", "example.com/path", "*.example.com", "exa mple.com", "example.com:80"];
+        for entry in invalid {
+            assert!(validate_allowlist(&[entry.to_string()]).is_err(), "{:?}", entry);
+        }
+    }
+
+    #[test]
+    fn popup_urls_from_the_frontend_are_checked() {
+        assert_eq!(parse_popup_url("https://example.com/a").unwrap().as_str(), "https://example.com/a");
+        assert_eq!(parse_popup_url("example.com").unwrap().as_str(), "https://example.com/");
+        assert!(parse_popup_url("javascript:alert(document.cookie)").is_err());
+        assert!(parse_popup_url("file:///etc/passwd").is_err());
+        assert!(parse_popup_url("data:text/html,<script>alert(1)</script>").is_err());
+        assert!(parse_popup_url("").is_err());
+    }
+
+    #[test]
+    fn released_popups_never_open_natively() {
+        let rules = popup_rules::CompiledRules::default();
+        assert_eq!(
+            released_action(&url("https://github.com/login/oauth/authorize?client_id=1"), &rules),
+            PopupAction::NewTab
+        );
+        let block = popup_rules::CompiledRules::new(&[popup_rules::PopupRule {
+            name: "Ads".to_string(),
+            host: Some("*.ads.example".to_string()),
+            path: None,
+            regex: None,
+            action: PopupAction::Block,
+        }]);
+        assert_eq!(released_action(&url("https://x.ads.example/"), &block), PopupAction::Block);
+    }
+}
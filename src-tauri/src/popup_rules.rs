@@ -0,0 +1,302 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::Url;
+
+/// What to do with a `window.open()` or `target="_blank"` request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PopupAction {
+    /// Let the webview open its own popup, keeping `window.opener` (OAuth, payment flows)
+    NativePopup,
+    /// Open a tab next to the opener, in the same window
+    NewTab,
+    /// Open a separate Meikai window
+    NewWindow,
+    /// Navigate the page that asked for the popup
+    SameWindow,
+    /// Ignore the request
+    Block,
+}
+
+/// Routes matching popups to an action. Every matcher that's set has to match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PopupRule {
+    /// Shown in settings, e.g. "Google sign-in"
+    pub name: String,
+    /// `accounts.google.com`, or `*.okta.com` for any subdomain
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Glob on the URL path: `*` stays within a segment, `**` spans segments
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Regular expression searched in the whole URL
+    #[serde(default)]
+    pub regex: Option<String>,
+    pub action: PopupAction,
+}
+
+impl PopupRule {
+    fn new(name: &str, host: Option<&str>, path: Option<&str>, regex: Option<&str>, action: PopupAction) -> Self {
+        Self {
+            name: name.to_string(),
+            host: host.map(str::to_string),
+            path: path.map(str::to_string),
+            regex: regex.map(str::to_string),
+            action,
+        }
+    }
+
+    /// Checks for a user rule; the message names the rule
+    pub fn validate(&self) -> Result<(), String> {
+        let describe = |problem: &str| format!("Popup rule \"{}\": {}", self.name, problem);
+
+        if self.host.is_none() && self.path.is_none() && self.regex.is_none() {
+            return Err(describe("needs a host, path or regex to match"));
+        }
+        if let Some(host) = &self.host {
+            let bare = host.strip_prefix("*.").unwrap_or(host);
+            if bare.is_empty() || bare.contains(['/', ':', '*']) || bare.chars().any(char::is_whitespace) {
+                return Err(describe("host must be a host name like example.com or *.example.com"));
+            }
+        }
+        if self.path.as_deref().is_some_and(|path| !path.starts_with('/')) {
+            return Err(describe("path must start with \"/\""));
+        }
+        if let Some(pattern) = &self.regex {
+            Regex::new(pattern).map_err(|e| describe(&format!("invalid regex: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// A rule with its regex compiled
+struct CompiledRule {
+    rule: PopupRule,
+    regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, url: &Url) -> bool {
+        let host_matches = self.rule.host.as_deref().is_none_or(|pattern| {
+            url.host_str().is_some_and(|host| host_matches(pattern, host))
+        });
+        let path_matches = self.rule.path.as_deref().is_none_or(|pattern| glob_matches(pattern, url.path()));
+        let regex_matches = self.regex.as_ref().is_none_or(|regex| regex.is_match(url.as_str()));
+        host_matches && path_matches && regex_matches
+    }
+}
+
+/// Rules ready to route popups, compiled once rather than for every popup
+#[derive(Default)]
+pub struct CompiledRules(Vec<CompiledRule>);
+
+impl CompiledRules {
+    /// Rules with an invalid regex are left out, so they never match; settings reject them anyway
+    pub fn new(rules: &[PopupRule]) -> Self {
+        Self(
+            rules
+                .iter()
+                .filter_map(|rule| {
+                    let regex = match rule.regex.as_deref() {
+                        Some(pattern) => Some(Regex::new(pattern).ok()?),
+                        None => None,
+                    };
+                    Some(CompiledRule { rule: rule.clone(), regex })
+                })
+                .collect(),
+        )
+    }
+}
+
+/// `example.com` matches only itself; `*.example.com` matches its subdomains
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let host = host.trim_end_matches('.');
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.len() > domain.len()
+            && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+            && host[..host.len() - domain.len()].ends_with('.'),
+        None => host.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Path glob: `?` is one character, `*` any run within a segment, `**` any run at all
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_from(&pattern, &path)
+}
+
+fn glob_from(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|skip| glob_from(rest, &path[skip..])),
+        ['*', rest @ ..] => {
+            let segment = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+            (0..=segment).any(|skip| glob_from(rest, &path[skip..]))
+        }
+        ['?', rest @ ..] => path.first().is_some_and(|&c| c != '/') && glob_from(rest, &path[1..]),
+        [c, rest @ ..] => path.first() == Some(c) && glob_from(rest, &path[1..]),
+    }
+}
+
+/// Sign-in flows that need a real popup with `window.opener`. Anything else opens a new tab.
+pub fn default_rules() -> Vec<PopupRule> {
+    use PopupAction::NativePopup;
+    vec![
+        PopupRule::new("Google sign-in", Some("accounts.google.com"), Some("/o/oauth2/**"), None, NativePopup),
+        PopupRule::new("Google sign-in", Some("accounts.google.com"), Some("/signin/oauth/**"), None, NativePopup),
+        PopupRule::new("Google One Tap", Some("accounts.google.com"), Some("/gsi/**"), None, NativePopup),
+        PopupRule::new("Microsoft sign-in", Some("login.microsoftonline.com"), Some("/*/oauth2/**"), None, NativePopup),
+        PopupRule::new("Microsoft account sign-in", Some("login.live.com"), Some("/oauth20_authorize.srf"), None, NativePopup),
+        PopupRule::new("Apple sign-in", Some("appleid.apple.com"), Some("/auth/**"), None, NativePopup),
+        PopupRule::new("GitHub sign-in", Some("github.com"), Some("/login/oauth/**"), None, NativePopup),
+        PopupRule::new(
+            "Facebook sign-in",
+            None,
+            None,
+            Some(r"^https://(www\.|m\.)?facebook\.com/(v[0-9.]+/)?dialog/oauth"),
+            NativePopup,
+        ),
+        PopupRule::new("Twitter sign-in", Some("api.twitter.com"), Some("/oauth/**"), None, NativePopup),
+        PopupRule::new("X sign-in", Some("twitter.com"), Some("/i/oauth2/**"), None, NativePopup),
+        PopupRule::new("X sign-in", Some("x.com"), Some("/i/oauth2/**"), None, NativePopup),
+        PopupRule::new("Discord sign-in", Some("discord.com"), Some("/oauth2/**"), None, NativePopup),
+        PopupRule::new("Slack sign-in", Some("slack.com"), Some("/oauth/**"), None, NativePopup),
+        PopupRule::new("Okta sign-in", Some("*.okta.com"), Some("/oauth2/**"), None, NativePopup),
+        PopupRule::new("Auth0 sign-in", Some("*.auth0.com"), Some("/authorize"), None, NativePopup),
+        PopupRule::new("PayPal checkout", Some("www.paypal.com"), Some("/checkoutnow**"), None, NativePopup),
+        // Any OAuth 2.0 / OpenID Connect authorization request carries both parameters
+        PopupRule::new(
+            "OAuth authorization request",
+            None,
+            None,
+            Some(r"[?&]client_id=[^&#]*.*[?&]response_type=|[?&]response_type=[^&#]*.*[?&]client_id="),
+            NativePopup,
+        ),
+        // Sign-in libraries often open an empty popup and navigate it once they know where to go
+        PopupRule::new("Scripted popup", None, None, Some("^about:blank$"), NativePopup),
+    ]
+}
+
+static DEFAULT_RULES: LazyLock<CompiledRules> = LazyLock::new(|| CompiledRules::new(&default_rules()));
+
+/// The first matching user rule, then the first matching default, then a new tab
+pub fn route(url: &Url, overrides: &CompiledRules) -> PopupAction {
+    overrides
+        .0
+        .iter()
+        .chain(DEFAULT_RULES.0.iter())
+        .find(|compiled| compiled.matches(url))
+        .map(|compiled| compiled.rule.action)
+        .unwrap_or(PopupAction::NewTab)
+}
+
+/// Check the user's rules, for settings
+pub fn validate_rules(rules: &[PopupRule]) -> Result<(), String> {
+    rules.iter().try_for_each(PopupRule::validate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route_default(url: &str) -> PopupAction {
+        route(&Url::parse(url).unwrap(), &CompiledRules::default())
+    }
+
+    #[test]
+    fn real_oauth_popups_stay_native() {
+        let urls = [
+            "https://accounts.google.com/o/oauth2/v2/auth?client_id=123.apps.googleusercontent.com&redirect_uri=https%3A%2F%2Fapp.example.com%2Fcallback&response_type=code&scope=openid%20email",
+            "https://accounts.google.com/gsi/select?client_id=123.apps.googleusercontent.com&ux_mode=popup",
+            "https://login.microsoftonline.com/common/oauth2/v2.0/authorize?client_id=6731de76&response_type=code&redirect_uri=https%3A%2F%2Fapp.example.com",
+            "https://appleid.apple.com/auth/authorize?client_id=com.example.web&redirect_uri=https%3A%2F%2Fexample.com%2Fapple&response_type=code%20id_token&response_mode=web_message",
+            "https://github.com/login/oauth/authorize?client_id=Iv1.8a61f9b3a7aba766&scope=read%3Auser&state=xyz",
+            "https://www.facebook.com/v18.0/dialog/oauth?client_id=1234567890&redirect_uri=https%3A%2F%2Fexample.com%2Ffb&state=abc",
+            "https://api.twitter.com/oauth/authenticate?oauth_token=NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0",
+            "https://x.com/i/oauth2/authorize?response_type=code&client_id=M1M5R3BMVy13QmpScXkzTUt5OE46MTpjaQ&scope=tweet.read",
+            "https://discord.com/oauth2/authorize?client_id=157730590492196864&scope=bot&permissions=8",
+            "https://dev-123456.okta.com/oauth2/default/v1/authorize?client_id=0oa1&response_type=code&scope=openid",
+            "https://example.us.auth0.com/authorize?response_type=code&client_id=abc&redirect_uri=https%3A%2F%2Fexample.com",
+            "https://auth.example-idp.com/realms/main/protocol/openid-connect/auth?client_id=web&response_type=code&scope=openid",
+            "about:blank",
+        ];
+        for url in urls {
+            assert_eq!(route_default(url), PopupAction::NativePopup, "{}", url);
+        }
+    }
+
+    #[test]
+    fn links_that_merely_mention_auth_open_a_tab() {
+        let urls = [
+            "https://github.com/oauth-org/oauth-toolkit",
+            "https://docs.github.com/en/apps/oauth-apps/building-oauth-apps/authorizing-oauth-apps",
+            "https://www.example.com/blog/how-to-authorize-payments",
+            "https://news.example.com/signin-tips-for-2024",
+            "https://www.facebook.com/somepage/posts/123",
+            "https://twitter.com/rustlang/status/1",
+            "https://support.google.com/accounts/answer/185839",
+            "https://accounts.google.com/ServiceLogin?continue=https%3A%2F%2Fmail.google.com",
+            "https://example.com/search?q=client_id",
+        ];
+        for url in urls {
+            assert_eq!(route_default(url), PopupAction::NewTab, "{}", url);
+        }
+    }
+
+    #[test]
+    fn user_rules_win_over_defaults() {
+        let overrides = CompiledRules::new(&[
+            PopupRule::new("Ads", Some("*.doubleclick.net"), None, None, PopupAction::Block),
+            PopupRule::new("Docs inline", Some("docs.example.com"), Some("/guide/*"), None, PopupAction::SameWindow),
+            PopupRule::new("Google in a window", Some("accounts.google.com"), None, None, PopupAction::NewWindow),
+            PopupRule::new("Broken", None, None, Some("(unclosed"), PopupAction::Block),
+        ]);
+        let route_with = |url: &str| route(&Url::parse(url).unwrap(), &overrides);
+
+        assert_eq!(route_with("https://ad.doubleclick.net/ddm/clk/1"), PopupAction::Block);
+        assert_eq!(route_with("https://docs.example.com/guide/intro"), PopupAction::SameWindow);
+        assert_eq!(route_with("https://docs.example.com/guide/intro/more"), PopupAction::NewTab);
+        assert_eq!(route_with("https://accounts.google.com/o/oauth2/v2/auth?client_id=1"), PopupAction::NewWindow);
+        assert_eq!(route_with("https://github.com/login/oauth/authorize?client_id=1"), PopupAction::NativePopup);
+    }
+
+    #[test]
+    fn wildcard_hosts_match_subdomains_only() {
+        assert!(host_matches("*.okta.com", "dev-1.okta.com"));
+        assert!(host_matches("*.okta.com", "a.b.OKTA.com"));
+        assert!(!host_matches("*.okta.com", "okta.com"));
+        assert!(!host_matches("*.okta.com", "notokta.com"));
+        assert!(host_matches("github.com", "GitHub.com"));
+        assert!(!host_matches("github.com", "gist.github.com"));
+    }
+
+    #[test]
+    fn path_globs() {
+        assert!(glob_matches("/login/oauth/**", "/login/oauth/authorize"));
+        assert!(glob_matches("/login/oauth/**", "/login/oauth/"));
+        assert!(!glob_matches("/login/oauth/**", "/login/oauthx"));
+        assert!(glob_matches("/guide/*", "/guide/intro"));
+        assert!(!glob_matches("/guide/*", "/guide/intro/more"));
+        assert!(glob_matches("/v?/auth", "/v2/auth"));
+        assert!(glob_matches("/**/authorize", "/oauth2/default/v1/authorize"));
+        assert!(!glob_matches("/authorize", "/authorize/extra"));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let rule = |host: Option<&str>, path: Option<&str>, regex: Option<&str>| {
+            PopupRule::new("Mine", host, path, regex, PopupAction::Block).validate()
+        };
+        assert!(rule(None, None, None).unwrap_err().contains("needs a host"));
+        assert!(rule(Some("https://example.com"), None, None).is_err());
+        assert!(rule(Some("*"), None, None).is_err());
+        assert!(rule(None, Some("login/*"), None).unwrap_err().contains("start with"));
+        assert!(rule(None, None, Some("(unclosed")).unwrap_err().contains("invalid regex"));
+        assert_eq!(rule(Some("*.example.com"), Some("/a/**"), Some("token=")), Ok(()));
+        assert_eq!(validate_rules(&default_rules()), Ok(()));
+    }
+}
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use crate::bookmarks::{self, BookmarkStore};
+use crate::clock::now_millis;
+use crate::history::{self, HistoryStore};
+use crate::settings::{self, Settings, SettingsStore};
+use crate::storage::{self, FileState};
+use crate::tabs;
+
+/// File name of the profile list inside the app data directory
+pub const PROFILES_FILE: &str = "profiles.json";
+
+/// Directory under the app data directory holding one directory per profile
+const PROFILES_DIR: &str = "profiles";
+
+/// Webview cookies, cache and storage inside a profile's directory
+const WEBVIEW_DATA_DIR: &str = "webview-data";
+
+/// The profile created from the data that existed before profiles
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Current on-disk schema version of `profiles.json`
+const SCHEMA_VERSION: u32 = 1;
+
+/// Longest allowed profile name
+const MAX_NAME_LENGTH: usize = 50;
+
+/// Data files that lived directly in the app data directory before profiles
+const LEGACY_FILES: [&str; 3] = [bookmarks::BOOKMARKS_FILE, settings::SETTINGS_FILE, history::HISTORY_DB_FILE];
+
+/// Backups and SQLite journals that move along with each legacy file
+const LEGACY_FILE_SUFFIXES: [&str; 5] = ["", ".bak", "-journal", "-wal", "-shm"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilesFile {
+    version: u32,
+    profiles: Vec<Profile>,
+    /// Profile whose bookmarks, settings and history the panel shows
+    active_id: String,
+}
+
+/// Result of `list_profiles` and the `profiles-changed` payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+    pub active_id: String,
+}
+
+fn default_profile() -> Profile {
+    Profile {
+        id: DEFAULT_PROFILE_ID.to_string(),
+        name: "Default".to_string(),
+        created_at: now_millis(),
+    }
+}
+
+/// A fresh profile list holding only the default profile
+fn default_profiles_file() -> ProfilesFile {
+    ProfilesFile {
+        version: SCHEMA_VERSION,
+        profiles: vec![default_profile()],
+        active_id: DEFAULT_PROFILE_ID.to_string(),
+    }
+}
+
+fn parse_profiles(content: &str) -> Result<(ProfilesFile, FileState), String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > u64::from(SCHEMA_VERSION) {
+        // A layout this build can't read still mustn't be replaced, so make do with the default profile
+        let file = serde_json::from_value(value).unwrap_or_else(|_| default_profiles_file());
+        return Ok((file, FileState::Newer));
+    }
+    let file = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok((file, FileState::Current))
+}
+
+/// Move the files of a pre-profiles install into the default profile's directory.
+/// Files that can't be moved stay where they are and the profile starts without them.
+fn migrate_legacy_files(app_data: &Path, default_dir: &Path) {
+    for file in LEGACY_FILES {
+        for suffix in LEGACY_FILE_SUFFIXES {
+            let from = storage::with_suffix(&app_data.join(file), suffix);
+            let to = storage::with_suffix(&default_dir.join(file), suffix);
+            if from.exists() && !to.exists() {
+                let _ = fs::rename(&from, &to);
+            }
+        }
+    }
+}
+
+/// Owns `profiles.json` and the `profiles/<id>` directories. Each profile has its own
+/// bookmarks, settings, history and webview data; the managed `BookmarkStore`,
+/// `SettingsStore` and `HistoryStore` hold the active profile's.
+pub struct ProfileManager {
+    app_data: PathBuf,
+    file: Mutex<ProfilesFile>,
+    /// The file is from a newer build: changes only last until the app quits
+    read_only: bool,
+    /// Profile of each browser window, fixed when it opens
+    windows: Mutex<HashMap<String, String>>,
+    /// History of inactive profiles that still have windows open, opened on first visit
+    histories: Mutex<HashMap<String, Arc<HistoryStore>>>,
+}
+
+impl ProfileManager {
+    /// Load the profile list from `app_data`, creating the default profile from
+    /// the existing data files on first run
+    pub fn load(app_data: &Path) -> Self {
+        let path = app_data.join(PROFILES_FILE);
+        let (mut file, state) = storage::load_with_backup(&path, parse_profiles)
+            .unwrap_or_else(|| (default_profiles_file(), FileState::Outdated));
+        if !file.profiles.iter().any(|profile| profile.id == DEFAULT_PROFILE_ID) {
+            file.profiles.insert(0, default_profile());
+        }
+        if !file.profiles.iter().any(|profile| profile.id == file.active_id) {
+            file.active_id = DEFAULT_PROFILE_ID.to_string();
+        }
+
+        let manager = Self {
+            app_data: app_data.to_path_buf(),
+            file: Mutex::new(file),
+            read_only: state == FileState::Newer,
+            windows: Mutex::new(HashMap::new()),
+            histories: Mutex::new(HashMap::new()),
+        };
+        let default_dir = manager.dir(DEFAULT_PROFILE_ID);
+        let _ = fs::create_dir_all(&default_dir);
+        migrate_legacy_files(app_data, &default_dir);
+        if state == FileState::Outdated {
+            let _ = manager.save(&manager.file.lock().unwrap());
+        }
+        manager
+    }
+
+    fn save(&self, file: &ProfilesFile) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+        let content = serde_json::to_vec_pretty(file).map_err(|e| e.to_string())?;
+        storage::write_atomic(&self.app_data.join(PROFILES_FILE), &content).map_err(|e| e.to_string())
+    }
+
+    /// Apply a change to the profile list and persist it. Nothing is kept in memory if the write fails.
+    fn mutate<T>(&self, change: impl FnOnce(&mut ProfilesFile) -> Result<T, String>) -> Result<T, String> {
+        let mut file = self.file.lock().unwrap();
+        let mut updated = file.clone();
+        let result = change(&mut updated)?;
+        self.save(&updated)?;
+        *file = updated;
+        Ok(result)
+    }
+
+    pub fn list(&self) -> ProfileList {
+        let file = self.file.lock().unwrap();
+        ProfileList {
+            profiles: file.profiles.clone(),
+            active_id: file.active_id.clone(),
+        }
+    }
+
+    pub fn active_id(&self) -> String {
+        self.file.lock().unwrap().active_id.clone()
+    }
+
+    pub fn exists(&self, profile_id: &str) -> bool {
+        self.file.lock().unwrap().profiles.iter().any(|profile| profile.id == profile_id)
+    }
+
+    /// Directory holding a profile's data files
+    pub fn dir(&self, profile_id: &str) -> PathBuf {
+        self.app_data.join(PROFILES_DIR).join(profile_id)
+    }
+
+    /// The active profile's directory, for loading the managed stores
+    pub fn active_dir(&self) -> PathBuf {
+        self.dir(&self.active_id())
+    }
+
+    /// Webview data directory of a profile. The default profile keeps the platform's
+    /// default location so logins from before profiles survive.
+    pub fn webview_data_dir(&self, profile_id: &str) -> Option<PathBuf> {
+        (profile_id != DEFAULT_PROFILE_ID).then(|| self.dir(profile_id).join(WEBVIEW_DATA_DIR))
+    }
+
+    /// Profile of a browser window; the active one for windows opened before it was recorded
+    pub fn window_profile(&self, parent_label: &str) -> String {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(parent_label)
+            .cloned()
+            .unwrap_or_else(|| self.active_id())
+    }
+
+    pub fn assign_window(&self, parent_label: &str, profile_id: &str) {
+        self.windows.lock().unwrap().insert(parent_label.to_string(), profile_id.to_string());
+    }
+
+    pub fn forget_window(&self, parent_label: &str) {
+        self.windows.lock().unwrap().remove(parent_label);
+    }
+
+    fn has_windows(&self, profile_id: &str) -> bool {
+        self.windows.lock().unwrap().values().any(|profile| profile == profile_id)
+    }
+
+    /// History store of an inactive profile
+    fn inactive_history(&self, profile_id: &str) -> Option<Arc<HistoryStore>> {
+        let mut histories = self.histories.lock().unwrap();
+        if let Some(store) = histories.get(profile_id) {
+            return Some(store.clone());
+        }
+        let store = Arc::new(HistoryStore::open(&self.dir(profile_id).join(history::HISTORY_DB_FILE)).ok()?);
+        histories.insert(profile_id.to_string(), store.clone());
+        Some(store)
+    }
+}
+
+/// Profile a tab belongs to, through its window
+pub fn tab_profile(app: &tauri::AppHandle, tab_label: &str) -> String {
+    app.state::<ProfileManager>()
+        .window_profile(&tabs::parent_window_label(app, tab_label))
+}
+
+/// Settings of a profile: the loaded ones for the active profile, otherwise read from its directory
+pub fn settings_of(app: &tauri::AppHandle, profile_id: &str) -> Settings {
+    let manager = app.state::<ProfileManager>();
+    if profile_id == manager.active_id() {
+        return settings::current(app);
+    }
+    SettingsStore::load(&manager.dir(profile_id)).snapshot()
+}
+
+/// Whether a tab belongs to the profile whose bookmarks and settings are loaded
+pub fn in_active_profile(app: &tauri::AppHandle, tab_label: &str) -> bool {
+    tab_profile(app, tab_label) == app.state::<ProfileManager>().active_id()
+}
+
+/// Run `f` on the history of the profile a tab belongs to
+pub fn with_tab_history<T>(
+    app: &tauri::AppHandle,
+    tab_label: &str,
+    f: impl FnOnce(&HistoryStore) -> T,
+) -> Option<T> {
+    let manager = app.try_state::<ProfileManager>()?;
+    let profile_id = tab_profile(app, tab_label);
+    if profile_id == manager.active_id() {
+        return app.try_state::<HistoryStore>().map(|store| f(&store));
+    }
+    manager.inactive_history(&profile_id).map(|store| f(&store))
+}
+
+fn emit_changed(app: &tauri::AppHandle, list: &ProfileList) {
+    let _ = app.emit("profiles-changed", list);
+}
+
+/// Trimmed name, unique among the other profiles regardless of case
+fn validate_name(file: &ProfilesFile, name: &str, except_id: Option<&str>) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Profile names must be 1 to {} characters", MAX_NAME_LENGTH));
+    }
+    let taken = file
+        .profiles
+        .iter()
+        .any(|profile| Some(profile.id.as_str()) != except_id && profile.name.to_lowercase() == name.to_lowercase());
+    if taken {
+        return Err(format!("There is already a profile named \"{}\"", name));
+    }
+    Ok(name.to_string())
+}
+
+#[tauri::command]
+pub async fn list_profiles(manager: tauri::State<'_, ProfileManager>) -> Result<ProfileList, String> {
+    Ok(manager.list())
+}
+
+/// Add an empty profile; it gets default bookmarks and settings when first used
+#[tauri::command]
+pub async fn create_profile(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, ProfileManager>,
+    name: String,
+) -> Result<Profile, String> {
+    let profile = manager.mutate(|file| {
+        let profile = Profile {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: validate_name(file, &name, None)?,
+            created_at: now_millis(),
+        };
+        file.profiles.push(profile.clone());
+        Ok(profile)
+    })?;
+    fs::create_dir_all(manager.dir(&profile.id)).map_err(|e| e.to_string())?;
+    emit_changed(&app, &manager.list());
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn rename_profile(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, ProfileManager>,
+    id: String,
+    name: String,
+) -> Result<Profile, String> {
+    let profile = manager.mutate(|file| {
+        let name = validate_name(file, &name, Some(&id))?;
+        let profile = file
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+        profile.name = name;
+        Ok(profile.clone())
+    })?;
+    emit_changed(&app, &manager.list());
+    Ok(profile)
+}
+
+/// Remove a profile and all of its data. The default profile, the active one and
+/// profiles with open windows can't be deleted.
+#[tauri::command]
+pub async fn delete_profile(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, ProfileManager>,
+    id: String,
+) -> Result<(), String> {
+    if id == DEFAULT_PROFILE_ID {
+        return Err("The default profile can't be deleted".to_string());
+    }
+    if manager.has_windows(&id) {
+        return Err("Close the profile's windows before deleting it".to_string());
+    }
+    manager.mutate(|file| {
+        if file.active_id == id {
+            return Err("Switch to another profile before deleting this one".to_string());
+        }
+        let index = file
+            .profiles
+            .iter()
+            .position(|profile| profile.id == id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+        file.profiles.remove(index);
+        Ok(())
+    })?;
+    manager.histories.lock().unwrap().remove(&id);
+    let _ = fs::remove_dir_all(manager.dir(&id));
+    emit_changed(&app, &manager.list());
+    Ok(())
+}
+
+/// Make a profile the active one: the panel's bookmarks, settings and history
+/// become that profile's, and new windows open in it. Open windows keep their profile.
+#[tauri::command]
+pub async fn switch_profile(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, ProfileManager>,
+    id: String,
+) -> Result<ProfileList, String> {
+    manager.mutate(|file| {
+        if !file.profiles.iter().any(|profile| profile.id == id) {
+            return Err(format!("Profile not found: {}", id));
+        }
+        file.active_id = id.clone();
+        Ok(())
+    })?;
+    let dir = manager.dir(&id);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    // The managed store takes over from the inactive one
+    manager.histories.lock().unwrap().remove(&id);
+    app.state::<HistoryStore>()
+        .reopen(&dir.join(history::HISTORY_DB_FILE))
+        .map_err(|e| e.to_string())?;
+    app.state::<BookmarkStore>().reload(&app, &dir);
+    app.state::<SettingsStore>().reload(&app, &dir);
+
+    let list = manager.list();
+    emit_changed(&app, &list);
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("meikai-profiles-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn profiles_file(version: u32, active_id: &str) -> String {
+        format!(
+            r#"{{"version": {}, "profiles": [{{"id": "work", "name": "Work", "createdAt": 1700000000000}}],
+                "activeId": "{}"}}"#,
+            version, active_id
+        )
+    }
+
+    #[test]
+    fn profiles_files_parse() {
+        let (file, state) = parse_profiles(&profiles_file(SCHEMA_VERSION, "work")).unwrap();
+        assert_eq!(file.profiles[0].name, "Work");
+        assert_eq!(file.active_id, "work");
+        assert_eq!(state, FileState::Current);
+    }
+
+    #[test]
+    fn future_profiles_files_load_what_this_build_understands() {
+        let (file, state) = parse_profiles(&profiles_file(SCHEMA_VERSION + 1, "work")).unwrap();
+        assert_eq!(state, FileState::Newer);
+        assert_eq!(file.active_id, "work");
+
+        let future = format!(r#"{{"version": {}, "profileSets": []}}"#, SCHEMA_VERSION + 1);
+        let (file, state) = parse_profiles(&future).unwrap();
+        assert_eq!(state, FileState::Newer);
+        assert_eq!(file.profiles[0].id, DEFAULT_PROFILE_ID);
+    }
+
+    #[test]
+    fn malformed_profiles_files_are_rejected() {
+        assert!(parse_profiles(r#"{"version": 1, "profiles": ["#).is_err());
+        assert!(parse_profiles(r#"{"version": 1, "profiles": []}"#).is_err());
+    }
+
+    #[test]
+    fn future_profiles_files_are_never_overwritten() {
+        let app_data = temp_dir();
+        let future = profiles_file(SCHEMA_VERSION + 1, "work");
+        fs::write(app_data.join(PROFILES_FILE), &future).unwrap();
+
+        let manager = ProfileManager::load(&app_data);
+        assert_eq!(manager.active_id(), "work");
+        manager
+            .mutate(|file| {
+                file.active_id = DEFAULT_PROFILE_ID.to_string();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(manager.active_id(), DEFAULT_PROFILE_ID);
+        assert_eq!(fs::read_to_string(app_data.join(PROFILES_FILE)).unwrap(), future);
+        fs::remove_dir_all(app_data).unwrap();
+    }
+
+    #[test]
+    fn legacy_files_and_their_companions_move_to_the_default_profile() {
+        let app_data = temp_dir();
+        let default_dir = app_data.join(PROFILES_DIR).join(DEFAULT_PROFILE_ID);
+        fs::create_dir_all(&default_dir).unwrap();
+        let moved = ["bookmarks.json", "bookmarks.json.bak", "settings.json", "history.db", "history.db-wal"];
+        for name in moved {
+            fs::write(app_data.join(name), name).unwrap();
+        }
+        fs::write(app_data.join("session.json"), "session").unwrap();
+
+        migrate_legacy_files(&app_data, &default_dir);
+        for name in moved {
+            assert!(!app_data.join(name).exists(), "{} was left behind", name);
+            assert_eq!(fs::read_to_string(default_dir.join(name)).unwrap(), name);
+        }
+        // Only the per-profile files move
+        assert!(app_data.join("session.json").exists());
+        fs::remove_dir_all(app_data).unwrap();
+    }
+
+    #[test]
+    fn migration_never_overwrites_profile_data() {
+        let app_data = temp_dir();
+        let default_dir = app_data.join(PROFILES_DIR).join(DEFAULT_PROFILE_ID);
+        fs::create_dir_all(&default_dir).unwrap();
+        fs::write(app_data.join("settings.json"), "legacy").unwrap();
+        fs::write(default_dir.join("settings.json"), "current").unwrap();
+
+        migrate_legacy_files(&app_data, &default_dir);
+        assert_eq!(fs::read_to_string(default_dir.join("settings.json")).unwrap(), "current");
+        assert_eq!(fs::read_to_string(app_data.join("settings.json")).unwrap(), "legacy");
+        fs::remove_dir_all(app_data).unwrap();
+    }
+
+    #[test]
+    fn first_load_creates_the_default_profile_from_legacy_data() {
+        let app_data = temp_dir();
+        fs::write(app_data.join("bookmarks.json"), "[]").unwrap();
+
+        let manager = ProfileManager::load(&app_data);
+        let list = manager.list();
+        assert_eq!(list.profiles.len(), 1);
+        assert_eq!(list.profiles[0].id, DEFAULT_PROFILE_ID);
+        assert_eq!(list.active_id, DEFAULT_PROFILE_ID);
+        assert!(manager.dir(DEFAULT_PROFILE_ID).join("bookmarks.json").exists());
+        assert!(app_data.join(PROFILES_FILE).exists());
+        fs::remove_dir_all(app_data).unwrap();
+    }
+
+    #[test]
+    fn load_restores_a_missing_default_profile_and_active_id() {
+        let app_data = temp_dir();
+        fs::write(app_data.join(PROFILES_FILE), profiles_file(SCHEMA_VERSION, "gone")).unwrap();
+
+        let list = ProfileManager::load(&app_data).list();
+        let ids: Vec<&str> = list.profiles.iter().map(|profile| profile.id.as_str()).collect();
+        assert_eq!(ids, [DEFAULT_PROFILE_ID, "work"]);
+        assert_eq!(list.active_id, DEFAULT_PROFILE_ID);
+        fs::remove_dir_all(app_data).unwrap();
+    }
+
+    #[test]
+    fn profile_names_are_trimmed_and_unique_ignoring_case() {
+        let (file, _) = parse_profiles(&profiles_file(SCHEMA_VERSION, "work")).unwrap();
+        assert_eq!(validate_name(&file, "  Home ", None).unwrap(), "Home");
+        assert!(validate_name(&file, "WORK", None).is_err());
+        assert_eq!(validate_name(&file, "WORK", Some("work")).unwrap(), "WORK");
+        assert!(validate_name(&file, "   ", None).is_err());
+        assert!(validate_name(&file, &"x".repeat(MAX_NAME_LENGTH + 1), None).is_err());
+    }
+}
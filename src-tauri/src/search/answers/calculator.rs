@@ -0,0 +1,313 @@
+use std::f64::consts::{E, PI};
+
+use super::{format_number, Answer, AnswerKind};
+
+/// Enough to hide float noise like `0.1 + 0.2 = 0.30000000000000004`
+const SIGNIFICANT_DIGITS: i32 = 12;
+
+/// Largest n whose factorial fits in an f64
+const MAX_FACTORIAL: f64 = 170.0;
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Number(f64),
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Power,
+    Percent,
+    Factorial,
+    /// `17% of 240`
+    Of,
+    Open,
+    Close,
+    Function(fn(f64) -> f64),
+}
+
+fn word_token(word: &str) -> Option<Token> {
+    Some(match word {
+        "of" => Token::Of,
+        "x" => Token::Times,
+        "pi" | "π" => Token::Number(PI),
+        "e" => Token::Number(E),
+        "sqrt" => Token::Function(f64::sqrt),
+        "abs" => Token::Function(f64::abs),
+        "exp" => Token::Function(f64::exp),
+        "ln" => Token::Function(f64::ln),
+        "log" => Token::Function(f64::log10),
+        "sin" => Token::Function(f64::sin),
+        "cos" => Token::Function(f64::cos),
+        "tan" => Token::Function(f64::tan),
+        "asin" => Token::Function(f64::asin),
+        "acos" => Token::Function(f64::acos),
+        "atan" => Token::Function(f64::atan),
+        _ => return None,
+    })
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Number(number.parse().ok()?));
+        } else if c.is_alphabetic() {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphabetic()) {
+                word.extend(c.to_lowercase());
+                chars.next();
+            }
+            tokens.push(word_token(&word)?);
+        } else {
+            chars.next();
+            tokens.push(match c {
+                '+' => Token::Plus,
+                '-' | '−' => Token::Minus,
+                '*' | '×' | '·' => Token::Times,
+                '/' | '÷' => Token::Divide,
+                '^' => Token::Power,
+                '%' => Token::Percent,
+                '!' => Token::Factorial,
+                '(' => Token::Open,
+                ')' => Token::Close,
+                '√' => Token::Function(f64::sqrt),
+                _ => return None,
+            });
+        }
+    }
+    Some(tokens)
+}
+
+/// Whether the input actually computes something. A bare number, constant or
+/// negative number is just text the user is typing.
+fn has_operation(tokens: &[Token]) -> bool {
+    let mut after_operand = false;
+    for token in tokens {
+        match token {
+            Token::Plus | Token::Minus if after_operand => return true,
+            Token::Plus | Token::Minus | Token::Open => after_operand = false,
+            Token::Number(_) | Token::Close => after_operand = true,
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// A value, remembering whether it was written as a percentage
+#[derive(Debug, Clone, Copy)]
+struct Operand {
+    value: f64,
+    percent: bool,
+}
+
+impl Operand {
+    fn plain(value: f64) -> Self {
+        Self { value, percent: false }
+    }
+}
+
+fn factorial(n: f64) -> Option<f64> {
+    if n < 0.0 || n.fract() != 0.0 || n > MAX_FACTORIAL {
+        return None;
+    }
+    Some((2..=n as u32).map(f64::from).product())
+}
+
+/// Recursive descent, loosest binding first:
+/// `+ -`, then `* / of`, then unary minus, then `^` (right-associative), then `% !`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Option<f64> {
+        let mut value = self.term()?.value;
+        while let Some(op @ (Token::Plus | Token::Minus)) = self.peek() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            // `100 + 10%` adds ten percent of 100, like a desk calculator
+            let rhs = if rhs.percent { value * rhs.value } else { rhs.value };
+            value = if matches!(op, Token::Plus) { value + rhs } else { value - rhs };
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<Operand> {
+        let mut lhs = self.unary()?;
+        loop {
+            lhs = match self.peek() {
+                Some(Token::Times) => {
+                    self.pos += 1;
+                    Operand::plain(lhs.value * self.unary()?.value)
+                }
+                Some(Token::Divide) => {
+                    self.pos += 1;
+                    Operand::plain(lhs.value / self.unary()?.value)
+                }
+                // Only a percentage can be taken "of" something
+                Some(Token::Of) if lhs.percent => {
+                    self.pos += 1;
+                    Operand::plain(lhs.value * self.unary()?.value)
+                }
+                _ => return Some(lhs),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Option<Operand> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.pos += 1;
+                let operand = self.unary()?;
+                Some(Operand { value: -operand.value, ..operand })
+            }
+            Some(Token::Plus) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Option<Operand> {
+        let base = self.postfix()?;
+        if let Some(Token::Power) = self.peek() {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Some(Operand::plain(base.value.powf(exponent.value)));
+        }
+        Some(base)
+    }
+
+    fn postfix(&mut self) -> Option<Operand> {
+        let mut operand = self.primary()?;
+        loop {
+            operand = match self.peek() {
+                Some(Token::Percent) => Operand { value: operand.value / 100.0, percent: true },
+                Some(Token::Factorial) => Operand::plain(factorial(operand.value)?),
+                _ => return Some(operand),
+            };
+            self.pos += 1;
+        }
+    }
+
+    fn primary(&mut self) -> Option<Operand> {
+        match self.next()? {
+            Token::Number(value) => Some(Operand::plain(value)),
+            Token::Open => {
+                let value = self.expression()?;
+                matches!(self.next()?, Token::Close).then_some(Operand::plain(value))
+            }
+            Token::Function(function) => Some(Operand::plain(function(self.postfix()?.value))),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluate an arithmetic expression such as `17% of 240` or `sqrt(2) * 3^2`
+pub fn evaluate(query: &str) -> Option<Answer> {
+    let tokens = tokenize(query)?;
+    if !has_operation(&tokens) {
+        return None;
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let value = parser.expression()?;
+    if parser.pos != parser.tokens.len() {
+        return None;
+    }
+
+    let value = format_number(value, SIGNIFICANT_DIGITS)?;
+    Some(Answer {
+        kind: AnswerKind::Calculation,
+        display: format!("{} = {}", query, value),
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(query: &str) -> Option<String> {
+        evaluate(query).map(|answer| answer.value)
+    }
+
+    #[test]
+    fn basic_arithmetic_respects_precedence() {
+        assert_eq!(value("1 + 2 * 3").as_deref(), Some("7"));
+        assert_eq!(value("(1 + 2) * 3").as_deref(), Some("9"));
+        assert_eq!(value("10 / 4 - 1").as_deref(), Some("1.5"));
+        assert_eq!(value("8 - 3 - 2").as_deref(), Some("3"));
+        assert_eq!(value("0.1 + 0.2").as_deref(), Some("0.3"));
+    }
+
+    #[test]
+    fn powers_are_right_associative_and_bind_tighter_than_minus() {
+        assert_eq!(value("2^3^2").as_deref(), Some("512"));
+        assert_eq!(value("-2^2").as_deref(), Some("-4"));
+        assert_eq!(value("2^-1").as_deref(), Some("0.5"));
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(value("17% of 240").as_deref(), Some("40.8"));
+        assert_eq!(value("100 + 10%").as_deref(), Some("110"));
+        assert_eq!(value("80 - 25%").as_deref(), Some("60"));
+        assert_eq!(value("50% * 8").as_deref(), Some("4"));
+        assert_eq!(value("50%").as_deref(), Some("0.5"));
+    }
+
+    #[test]
+    fn functions_constants_and_factorials() {
+        assert_eq!(value("sqrt(16)").as_deref(), Some("4"));
+        assert_eq!(value("√9 + 1").as_deref(), Some("4"));
+        assert_eq!(value("sin(pi / 2)").as_deref(), Some("1"));
+        assert_eq!(value("log 1000").as_deref(), Some("3"));
+        assert_eq!(value("5!").as_deref(), Some("120"));
+        assert_eq!(value("2 × 3 ÷ 4").as_deref(), Some("1.5"));
+        assert_eq!(value("3 x 4").as_deref(), Some("12"));
+    }
+
+    #[test]
+    fn display_restates_the_input() {
+        let answer = evaluate("17% of 240").unwrap();
+        assert_eq!(answer.kind, AnswerKind::Calculation);
+        assert_eq!(answer.display, "17% of 240 = 40.8");
+    }
+
+    #[test]
+    fn undefined_results_have_no_answer() {
+        assert_eq!(value("1 / 0"), None);
+        assert_eq!(value("sqrt(-1)"), None);
+        assert_eq!(value("2.5!"), None);
+        assert_eq!(value("171!"), None);
+    }
+
+    #[test]
+    fn malformed_or_trivial_input_has_no_answer() {
+        for query in ["42", "-5", "(7)", "pi", "1 +", "(1 + 2", "2 of 3", "1..2 + 1", "rust + tauri", "3 % % of"] {
+            assert_eq!(value(query), None, "{:?}", query);
+        }
+    }
+}
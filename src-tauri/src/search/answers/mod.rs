@@ -0,0 +1,108 @@
+use serde::Serialize;
+
+mod calculator;
+mod timezones;
+mod units;
+
+/// Longer inputs are never answers, and this bounds the calculator's recursion
+const MAX_QUERY_LEN: usize = 200;
+
+/// Which engine produced an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnswerKind {
+    /// Arithmetic, e.g. `17% of 240`
+    Calculation,
+    /// e.g. `5 mi in km`
+    UnitConversion,
+    /// e.g. `3pm PST in IST`
+    TimeZone,
+}
+
+/// An instant answer shown at the top of the omnibox dropdown
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Answer {
+    pub kind: AnswerKind,
+    /// The result on its own, e.g. `8.04672 km`
+    pub value: String,
+    /// The input restated with its result, e.g. `5 mi = 8.04672 km`
+    pub display: String,
+}
+
+/// Answer a query locally, without network. Time zones and units go first:
+/// they contain words the calculator would reject anyway.
+pub fn answer(query: &str) -> Option<Answer> {
+    let query = query.trim();
+    if query.is_empty() || query.len() > MAX_QUERY_LEN {
+        return None;
+    }
+    timezones::convert(query)
+        .or_else(|| units::convert(query))
+        .or_else(|| calculator::evaluate(query))
+}
+
+/// Round to `significant` digits (never inside the integer part) and drop trailing zeros.
+/// `None` for infinities and NaN, e.g. after a division by zero.
+fn format_number(value: f64, significant: i32) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    if value == 0.0 {
+        return Some("0".to_string());
+    }
+
+    let magnitude = value.abs().log10().floor() as i32;
+    if !(-6..15).contains(&magnitude) {
+        let formatted = format!("{:.*e}", (significant - 1) as usize, value);
+        let (mantissa, exponent) = formatted.split_once('e')?;
+        return Some(format!("{}e{}", trim_fraction(mantissa), exponent));
+    }
+
+    let decimals = (significant - 1 - magnitude).max(0) as usize;
+    let formatted = format!("{:.*}", decimals, value);
+    let trimmed = trim_fraction(&formatted);
+    Some(if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() })
+}
+
+fn trim_fraction(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_without_float_noise() {
+        assert_eq!(format_number(0.1 + 0.2, 12).as_deref(), Some("0.3"));
+        assert_eq!(format_number(40.8, 12).as_deref(), Some("40.8"));
+        assert_eq!(format_number(1609344.0, 6).as_deref(), Some("1609344"));
+        assert_eq!(format_number(-0.0000001, 6).as_deref(), Some("-1e-7"));
+        assert_eq!(format_number(2f64.powi(60), 6).as_deref(), Some("1.15292e18"));
+    }
+
+    #[test]
+    fn non_finite_numbers_are_not_answers() {
+        assert_eq!(format_number(f64::INFINITY, 12), None);
+        assert_eq!(format_number(f64::NAN, 12), None);
+    }
+
+    #[test]
+    fn dispatches_to_each_engine() {
+        assert_eq!(answer("17% of 240").map(|a| a.kind), Some(AnswerKind::Calculation));
+        assert_eq!(answer("5 mi in km").map(|a| a.kind), Some(AnswerKind::UnitConversion));
+        assert_eq!(answer("3pm PST in IST").map(|a| a.kind), Some(AnswerKind::TimeZone));
+    }
+
+    #[test]
+    fn ordinary_searches_have_no_answer() {
+        for query in ["", "rust tauri", "e", "42", "how to convert km in miles", "what time is it in tokyo"] {
+            assert_eq!(answer(query), None, "{:?}", query);
+        }
+    }
+}
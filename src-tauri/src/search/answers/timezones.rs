@@ -0,0 +1,213 @@
+use super::{Answer, AnswerKind};
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// Words between the source time and the target zone, e.g. `3pm PST in IST`
+const SEPARATORS: &[&str] = &["in", "to"];
+
+/// Common abbreviations and their UTC offsets in minutes. Abbreviations are fixed
+/// offsets (PST is always UTC-8, PDT is UTC-7), so no time zone database is needed.
+/// Where an abbreviation is ambiguous the most searched meaning wins: IST is India,
+/// CST is US Central.
+const ZONES: &[(&str, i32)] = &[
+    ("UTC", 0),
+    ("GMT", 0),
+    ("WET", 0),
+    ("WEST", 60),
+    ("BST", 60),
+    ("CET", 60),
+    ("CEST", 120),
+    ("EET", 120),
+    ("EEST", 180),
+    ("MSK", 180),
+    ("GST", 240),
+    ("PKT", 300),
+    ("IST", 330),
+    ("NPT", 345),
+    ("ICT", 420),
+    ("WIB", 420),
+    ("SGT", 480),
+    ("HKT", 480),
+    ("AWST", 480),
+    ("JST", 540),
+    ("KST", 540),
+    ("ACST", 570),
+    ("ACDT", 630),
+    ("AEST", 600),
+    ("AEDT", 660),
+    ("NZST", 720),
+    ("NZDT", 780),
+    ("HST", -600),
+    ("AKST", -540),
+    ("AKDT", -480),
+    ("PST", -480),
+    ("PDT", -420),
+    ("MST", -420),
+    ("MDT", -360),
+    ("CST", -360),
+    ("CDT", -300),
+    ("EST", -300),
+    ("EDT", -240),
+    ("AST", -240),
+    ("ADT", -180),
+    ("NST", -210),
+    ("NDT", -150),
+    ("BRT", -180),
+    ("ART", -180),
+];
+
+/// A zone's display name and UTC offset in minutes: an abbreviation from `ZONES`
+/// or an explicit offset such as `UTC+5:30` or `GMT-3`
+fn parse_zone(text: &str) -> Option<(String, i32)> {
+    let name = text.to_uppercase();
+    if let Some(&(abbreviation, offset)) = ZONES.iter().find(|(abbreviation, _)| *abbreviation == name) {
+        return Some((abbreviation.to_string(), offset));
+    }
+
+    let offset = name.strip_prefix("UTC").or_else(|| name.strip_prefix("GMT"))?;
+    let (sign, offset) = if let Some(rest) = offset.strip_prefix('+') {
+        (1, rest)
+    } else {
+        (-1, offset.strip_prefix(['-', '−'])?)
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    Some((name, sign * (hours * 60 + minutes)))
+}
+
+/// Minutes since midnight and whether the time was written 12-hour style.
+/// Accepts `3pm`, `3:30 pm`, `15:30`, `noon` and `midnight`; a bare `15` is too
+/// likely to be something else.
+fn parse_time(text: &str) -> Option<(i32, bool)> {
+    let text: String = text.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    match text.as_str() {
+        "noon" => return Some((12 * 60, true)),
+        "midnight" => return Some((0, true)),
+        _ => {}
+    }
+
+    let (clock, pm) = if let Some(clock) = text.strip_suffix("pm").or(text.strip_suffix("p.m.")) {
+        (clock, Some(true))
+    } else if let Some(clock) = text.strip_suffix("am").or(text.strip_suffix("a.m.")) {
+        (clock, Some(false))
+    } else {
+        (text.as_str(), None)
+    };
+
+    let (hours, minutes) = match clock.split_once(':') {
+        Some((hours, minutes)) if minutes.len() == 2 => (hours, minutes),
+        Some(_) => return None,
+        None if pm.is_some() => (clock, "00"),
+        None => return None,
+    };
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if minutes > 59 {
+        return None;
+    }
+
+    match pm {
+        Some(pm) if (1..=12).contains(&hours) => Some((hours % 12 * 60 + minutes + if pm { 720 } else { 0 }, true)),
+        None if hours <= 23 => Some((hours * 60 + minutes, false)),
+        _ => None,
+    }
+}
+
+fn format_time(minutes: i32, twelve_hour: bool) -> String {
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    if twelve_hour {
+        let suffix = if hours < 12 { "AM" } else { "PM" };
+        let hours = if hours % 12 == 0 { 12 } else { hours % 12 };
+        format!("{}:{:02} {}", hours, minutes, suffix)
+    } else {
+        format!("{:02}:{:02}", hours, minutes)
+    }
+}
+
+fn day_shift(days: i32) -> String {
+    match days {
+        0 => String::new(),
+        1 => " (next day)".to_string(),
+        -1 => " (previous day)".to_string(),
+        _ => format!(" ({:+} days)", days),
+    }
+}
+
+/// Convert a time between zones, e.g. `3pm PST in IST`
+pub fn convert(query: &str) -> Option<Answer> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+
+    // `<time> <zone> in <zone>`: the source zone is the word before the separator
+    (2..words.len().saturating_sub(1))
+        .filter(|&i| SEPARATORS.contains(&words[i].to_lowercase().as_str()))
+        .find_map(|i| {
+            let (time, twelve_hour) = parse_time(&words[..i - 1].join(" "))?;
+            let (from_name, from_offset) = parse_zone(words[i - 1])?;
+            let (to_name, to_offset) = parse_zone(&words[i + 1..].join(""))?;
+
+            let converted = time - from_offset + to_offset;
+            let value = format!(
+                "{} {}{}",
+                format_time(converted.rem_euclid(MINUTES_PER_DAY), twelve_hour),
+                to_name,
+                day_shift(converted.div_euclid(MINUTES_PER_DAY))
+            );
+            Some(Answer {
+                kind: AnswerKind::TimeZone,
+                display: format!("{} {} = {}", format_time(time, twelve_hour), from_name, value),
+                value,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(query: &str) -> Option<String> {
+        convert(query).map(|answer| answer.value)
+    }
+
+    #[test]
+    fn converts_between_abbreviations() {
+        assert_eq!(value("3pm PST in IST").as_deref(), Some("4:30 AM IST (next day)"));
+        assert_eq!(value("9:00 am est to cet").as_deref(), Some("3:00 PM CET"));
+        assert_eq!(value("15:30 UTC in JST").as_deref(), Some("00:30 JST (next day)"));
+        assert_eq!(value("1am jst in pdt").as_deref(), Some("9:00 AM PDT (previous day)"));
+    }
+
+    #[test]
+    fn explicit_offsets() {
+        assert_eq!(value("noon utc to utc+5:30").as_deref(), Some("5:30 PM UTC+5:30"));
+        assert_eq!(value("08:00 GMT-3 in UTC").as_deref(), Some("11:00 UTC"));
+        assert_eq!(value("midnight utc in utc + 14").as_deref(), Some("2:00 PM UTC+14"));
+    }
+
+    #[test]
+    fn parses_twelve_and_twenty_four_hour_times() {
+        assert_eq!(parse_time("3pm"), Some((15 * 60, true)));
+        assert_eq!(parse_time("3:30 pm"), Some((15 * 60 + 30, true)));
+        assert_eq!(parse_time("12am"), Some((0, true)));
+        assert_eq!(parse_time("12 p.m."), Some((12 * 60, true)));
+        assert_eq!(parse_time("23:59"), Some((23 * 60 + 59, false)));
+        for text in ["15", "13pm", "0am", "24:00", "3:5pm", "3:60"] {
+            assert_eq!(parse_time(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn display_restates_the_source_time() {
+        let answer = convert("3pm PST in IST").unwrap();
+        assert_eq!(answer.kind, AnswerKind::TimeZone);
+        assert_eq!(answer.display, "3:00 PM PST = 4:30 AM IST (next day)");
+    }
+
+    #[test]
+    fn unknown_zones_and_non_times_have_no_answer() {
+        for query in ["3pm xyz in ist", "3pm pst in mars", "3 pst in ist", "events in pst", "5 mi in km"] {
+            assert_eq!(value(query), None, "{:?}", query);
+        }
+    }
+}
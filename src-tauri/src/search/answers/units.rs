@@ -0,0 +1,209 @@
+use super::{format_number, Answer, AnswerKind};
+
+/// Conversions are rounded to this many significant digits
+const SIGNIFICANT_DIGITS: i32 = 6;
+
+/// Words between the quantity and the target unit, e.g. `5 mi in km`
+const SEPARATORS: &[&str] = &["in", "to", "as", "into"];
+
+/// Units only convert within a dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Area,
+    Speed,
+    Time,
+    Data,
+    Temperature,
+}
+
+struct Unit {
+    /// Shown in answers
+    symbol: &'static str,
+    dimension: Dimension,
+    /// Size in the dimension's base unit (meter, gram, liter, m², m/s, second, byte, kelvin)
+    factor: f64,
+    /// Added after scaling to the base unit; only temperatures have one
+    offset: f64,
+    /// Lowercase spellings accepted besides the symbol
+    names: &'static [&'static str],
+}
+
+const fn unit(symbol: &'static str, dimension: Dimension, factor: f64, names: &'static [&'static str]) -> Unit {
+    Unit { symbol, dimension, factor, offset: 0.0, names }
+}
+
+const UNITS: &[Unit] = &[
+    unit("mm", Dimension::Length, 0.001, &["millimeter", "millimeters", "millimetre", "millimetres"]),
+    unit("cm", Dimension::Length, 0.01, &["centimeter", "centimeters", "centimetre", "centimetres"]),
+    unit("m", Dimension::Length, 1.0, &["meter", "meters", "metre", "metres"]),
+    unit("km", Dimension::Length, 1000.0, &["kilometer", "kilometers", "kilometre", "kilometres"]),
+    unit("in", Dimension::Length, 0.0254, &["inch", "inches"]),
+    unit("ft", Dimension::Length, 0.3048, &["foot", "feet"]),
+    unit("yd", Dimension::Length, 0.9144, &["yard", "yards"]),
+    unit("mi", Dimension::Length, 1609.344, &["mile", "miles"]),
+    unit("nmi", Dimension::Length, 1852.0, &["nautical mile", "nautical miles"]),
+    unit("mg", Dimension::Mass, 0.001, &["milligram", "milligrams"]),
+    unit("g", Dimension::Mass, 1.0, &["gram", "grams"]),
+    unit("kg", Dimension::Mass, 1000.0, &["kilogram", "kilograms", "kilo", "kilos"]),
+    unit("t", Dimension::Mass, 1_000_000.0, &["tonne", "tonnes", "metric ton", "metric tons"]),
+    unit("oz", Dimension::Mass, 28.349523125, &["ounce", "ounces"]),
+    unit("lb", Dimension::Mass, 453.59237, &["lbs", "pound", "pounds"]),
+    unit("st", Dimension::Mass, 6350.29318, &["stone", "stones"]),
+    unit("ml", Dimension::Volume, 0.001, &["milliliter", "milliliters", "millilitre", "millilitres"]),
+    unit("l", Dimension::Volume, 1.0, &["liter", "liters", "litre", "litres"]),
+    unit("gal", Dimension::Volume, 3.785411784, &["gallon", "gallons"]),
+    unit("qt", Dimension::Volume, 0.946352946, &["quart", "quarts"]),
+    unit("pt", Dimension::Volume, 0.473176473, &["pint", "pints"]),
+    unit("cup", Dimension::Volume, 0.2365882365, &["cups"]),
+    unit("fl oz", Dimension::Volume, 0.0295735295625, &["floz", "fluid ounce", "fluid ounces"]),
+    unit("tbsp", Dimension::Volume, 0.01478676478125, &["tablespoon", "tablespoons"]),
+    unit("tsp", Dimension::Volume, 0.00492892159375, &["teaspoon", "teaspoons"]),
+    unit("m²", Dimension::Area, 1.0, &["m2", "sq m", "square meter", "square meters", "square metre", "square metres"]),
+    unit("km²", Dimension::Area, 1_000_000.0, &["km2", "sq km", "square kilometer", "square kilometers"]),
+    unit("ft²", Dimension::Area, 0.09290304, &["ft2", "sq ft", "square foot", "square feet"]),
+    unit("mi²", Dimension::Area, 2_589_988.110336, &["mi2", "sq mi", "square mile", "square miles"]),
+    unit("ha", Dimension::Area, 10_000.0, &["hectare", "hectares"]),
+    unit("acre", Dimension::Area, 4046.8564224, &["acres", "ac"]),
+    unit("m/s", Dimension::Speed, 1.0, &["mps", "meters per second"]),
+    unit("km/h", Dimension::Speed, 1.0 / 3.6, &["kph", "kmh", "kmph", "kilometers per hour"]),
+    unit("mph", Dimension::Speed, 0.44704, &["miles per hour"]),
+    unit("kn", Dimension::Speed, 1852.0 / 3600.0, &["knot", "knots", "kt"]),
+    unit("ms", Dimension::Time, 0.001, &["millisecond", "milliseconds"]),
+    unit("s", Dimension::Time, 1.0, &["sec", "secs", "second", "seconds"]),
+    unit("min", Dimension::Time, 60.0, &["mins", "minute", "minutes"]),
+    unit("h", Dimension::Time, 3600.0, &["hr", "hrs", "hour", "hours"]),
+    unit("d", Dimension::Time, 86_400.0, &["day", "days"]),
+    unit("wk", Dimension::Time, 604_800.0, &["week", "weeks"]),
+    unit("yr", Dimension::Time, 31_556_952.0, &["year", "years"]),
+    unit("B", Dimension::Data, 1.0, &["byte", "bytes"]),
+    unit("KB", Dimension::Data, 1e3, &["kilobyte", "kilobytes"]),
+    unit("MB", Dimension::Data, 1e6, &["megabyte", "megabytes"]),
+    unit("GB", Dimension::Data, 1e9, &["gigabyte", "gigabytes"]),
+    unit("TB", Dimension::Data, 1e12, &["terabyte", "terabytes"]),
+    unit("KiB", Dimension::Data, 1024.0, &["kibibyte", "kibibytes"]),
+    unit("MiB", Dimension::Data, 1_048_576.0, &["mebibyte", "mebibytes"]),
+    unit("GiB", Dimension::Data, 1_073_741_824.0, &["gibibyte", "gibibytes"]),
+    unit("TiB", Dimension::Data, 1_099_511_627_776.0, &["tebibyte", "tebibytes"]),
+    Unit {
+        symbol: "°C",
+        dimension: Dimension::Temperature,
+        factor: 1.0,
+        offset: 273.15,
+        names: &["c", "celsius", "degc", "degrees celsius"],
+    },
+    Unit {
+        symbol: "°F",
+        dimension: Dimension::Temperature,
+        factor: 5.0 / 9.0,
+        offset: 273.15 - 32.0 * 5.0 / 9.0,
+        names: &["f", "fahrenheit", "degf", "degrees fahrenheit"],
+    },
+    Unit {
+        symbol: "K",
+        dimension: Dimension::Temperature,
+        factor: 1.0,
+        offset: 0.0,
+        names: &["kelvin", "kelvins"],
+    },
+];
+
+fn lookup(name: &str) -> Option<&'static Unit> {
+    let name = name.trim().to_lowercase();
+    UNITS
+        .iter()
+        .find(|unit| unit.symbol.to_lowercase() == name || unit.names.contains(&name.as_str()))
+}
+
+/// `5 mi`, `5mi`, `-40 f` or `1,000 ft`
+fn parse_quantity(text: &str) -> Option<(f64, &'static Unit)> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+')))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let amount = number.replace(',', "").parse().ok()?;
+    Some((amount, lookup(unit)?))
+}
+
+/// Convert a quantity such as `5 mi in km` or `100 f to c`
+pub fn convert(query: &str) -> Option<Answer> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+
+    // `5 in in cm`: the separator is whichever "in" leaves two valid halves
+    (1..words.len().saturating_sub(1))
+        .filter(|&i| SEPARATORS.contains(&words[i].to_lowercase().as_str()))
+        .find_map(|i| {
+            let (amount, from) = parse_quantity(&words[..i].join(" "))?;
+            let to = lookup(&words[i + 1..].join(" "))?;
+            if from.dimension != to.dimension {
+                return None;
+            }
+
+            let base = amount * from.factor + from.offset;
+            let result = format_number((base - to.offset) / to.factor, SIGNIFICANT_DIGITS)?;
+            let amount = format_number(amount, SIGNIFICANT_DIGITS)?;
+            let value = format!("{} {}", result, to.symbol);
+            Some(Answer {
+                kind: AnswerKind::UnitConversion,
+                display: format!("{} {} = {}", amount, from.symbol, value),
+                value,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(query: &str) -> Option<String> {
+        convert(query).map(|answer| answer.value)
+    }
+
+    #[test]
+    fn length_mass_and_volume() {
+        assert_eq!(value("5 mi in km").as_deref(), Some("8.04672 km"));
+        assert_eq!(value("1 ft to cm").as_deref(), Some("30.48 cm"));
+        assert_eq!(value("10 kg in lbs").as_deref(), Some("22.0462 lb"));
+        assert_eq!(value("1 gallon in liters").as_deref(), Some("3.78541 l"));
+        assert_eq!(value("2 cups in fl oz").as_deref(), Some("16 fl oz"));
+    }
+
+    #[test]
+    fn temperatures_use_offsets() {
+        assert_eq!(value("100 f in c").as_deref(), Some("37.7778 °C"));
+        assert_eq!(value("-40 celsius to fahrenheit").as_deref(), Some("-40 °F"));
+        assert_eq!(value("0 c in kelvin").as_deref(), Some("273.15 K"));
+    }
+
+    #[test]
+    fn other_dimensions() {
+        assert_eq!(value("60 mph in km/h").as_deref(), Some("96.5606 km/h"));
+        assert_eq!(value("1 acre in sq ft").as_deref(), Some("43560 ft²"));
+        assert_eq!(value("90 minutes in hours").as_deref(), Some("1.5 h"));
+        assert_eq!(value("1 GiB in MB").as_deref(), Some("1073.74 MB"));
+    }
+
+    #[test]
+    fn number_formats_and_inch_ambiguity() {
+        assert_eq!(value("5mi in km").as_deref(), Some("8.04672 km"));
+        assert_eq!(value("1,000 ft in m").as_deref(), Some("304.8 m"));
+        assert_eq!(value("12 in in cm").as_deref(), Some("30.48 cm"));
+        assert_eq!(value("30.48 cm in in").as_deref(), Some("12 in"));
+    }
+
+    #[test]
+    fn display_restates_the_quantity() {
+        let answer = convert("5 miles into kilometers").unwrap();
+        assert_eq!(answer.kind, AnswerKind::UnitConversion);
+        assert_eq!(answer.display, "5 mi = 8.04672 km");
+    }
+
+    #[test]
+    fn mismatched_or_unknown_units_have_no_answer() {
+        for query in ["5 kg in km", "5 parsecs in km", "mi in km", "5 mi in", "things to do in paris"] {
+            assert_eq!(value(query), None, "{:?}", query);
+        }
+    }
+}
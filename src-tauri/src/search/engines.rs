@@ -0,0 +1,418 @@
+use serde::{Deserialize, Serialize};
+use tauri::Url;
+
+use crate::settings::{SettingsError, SettingsStore};
+
+/// Placeholder for the encoded query in engine URL templates (OpenSearch syntax)
+pub const SEARCH_TERMS: &str = "{searchTerms}";
+
+/// Used when the configured engine no longer exists
+pub const DEFAULT_ENGINE_ID: &str = "google";
+
+/// A search engine, built in or added by the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchEngine {
+    /// Stored in settings: `google` for built-in engines, a UUID for custom ones
+    pub id: String,
+    pub name: String,
+    /// Typed before a query to search this engine once (`gh rust tauri`); also works as `!gh`
+    pub keyword: Option<String>,
+    /// Results page with a `{searchTerms}` placeholder
+    pub search_url: String,
+    /// OpenSearch JSON suggestion endpoint with a `{searchTerms}` placeholder.
+    /// Built-in engines leave this empty and use their own providers.
+    pub suggest_url: Option<String>,
+    /// Site icon from an OpenSearch description, as an http(s) or `data:` URL
+    #[serde(default)]
+    pub icon_url: Option<String>,
+}
+
+impl SearchEngine {
+    fn builtin(id: &str, name: &str, search_url: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            keyword: None,
+            search_url: search_url.to_string(),
+            suggest_url: None,
+            icon_url: None,
+        }
+    }
+
+    /// Build the results page URL for a query
+    pub fn search_url(&self, query: &str) -> String {
+        self.search_url.replace(SEARCH_TERMS, &urlencoding::encode(query))
+    }
+
+    /// The engine's front page, for a keyword or bang without a query
+    fn home_url(&self) -> String {
+        Url::parse(&self.search_url)
+            .map(|url| format!("{}/", url.origin().ascii_serialization()))
+            .unwrap_or_else(|_| self.search_url(""))
+    }
+
+    /// Checks for a custom engine; the message names the engine
+    pub fn validate(&self) -> Result<(), String> {
+        let describe = |problem: &str| format!("Search engine \"{}\": {}", self.name, problem);
+
+        if self.name.trim().is_empty() {
+            return Err("Search engines need a name".to_string());
+        }
+        if !is_template(&self.search_url) {
+            return Err(describe("search URL must be an http(s) URL containing {searchTerms}"));
+        }
+        if self.suggest_url.as_deref().is_some_and(|url| !is_template(url)) {
+            return Err(describe("suggestion URL must be an http(s) URL containing {searchTerms}"));
+        }
+        let icon_valid = |icon: &str| {
+            Url::parse(icon).is_ok_and(|url| matches!(url.scheme(), "http" | "https" | "data"))
+        };
+        if self.icon_url.as_deref().is_some_and(|icon| !icon_valid(icon)) {
+            return Err(describe("icon must be an http(s) or data: URL"));
+        }
+        if let Some(keyword) = &self.keyword {
+            if keyword.is_empty() || keyword.starts_with('!') || keyword.chars().any(char::is_whitespace) {
+                return Err(describe("keyword must be a single word and can't start with \"!\""));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_template(url: &str) -> bool {
+    url.contains(SEARCH_TERMS)
+        && Url::parse(&url.replace(SEARCH_TERMS, "test"))
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Engines offered out of the box, in settings order
+pub fn builtin_engines() -> Vec<SearchEngine> {
+    vec![
+        SearchEngine::builtin("google", "Google", "https://www.google.com/search?q={searchTerms}"),
+        SearchEngine::builtin("duckduckgo", "DuckDuckGo", "https://duckduckgo.com/?q={searchTerms}"),
+        SearchEngine::builtin("bing", "Bing", "https://www.bing.com/search?q={searchTerms}"),
+        SearchEngine::builtin("yahoo", "Yahoo", "https://search.yahoo.com/search?p={searchTerms}"),
+        SearchEngine::builtin("brave", "Brave", "https://search.brave.com/search?q={searchTerms}"),
+    ]
+}
+
+/// Check a list of custom engines against each other and the built-in ones
+pub fn validate_custom(engines: &[SearchEngine]) -> Result<(), String> {
+    let builtin = builtin_engines();
+    for (index, engine) in engines.iter().enumerate() {
+        engine.validate()?;
+        let earlier = &engines[..index];
+
+        if engine.id.trim().is_empty()
+            || builtin.iter().chain(earlier).any(|other| other.id == engine.id)
+        {
+            return Err(format!("Search engine \"{}\" needs a unique id", engine.name));
+        }
+        if let Some(keyword) = &engine.keyword {
+            let taken = earlier
+                .iter()
+                .filter_map(|other| other.keyword.as_deref())
+                .any(|other| other.eq_ignore_ascii_case(keyword));
+            if taken {
+                return Err(format!("The keyword \"{}\" is already used by another engine", keyword));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// DuckDuckGo-style `!bang` shortcuts that work without configuring an engine:
+/// (bang, name, results page template). An engine keyword with the same name wins.
+const BANGS: &[(&str, &str, &str)] = &[
+    ("g", "Google", "https://www.google.com/search?q={searchTerms}"),
+    ("ddg", "DuckDuckGo", "https://duckduckgo.com/?q={searchTerms}"),
+    ("b", "Bing", "https://www.bing.com/search?q={searchTerms}"),
+    ("y", "Yahoo", "https://search.yahoo.com/search?p={searchTerms}"),
+    ("brave", "Brave", "https://search.brave.com/search?q={searchTerms}"),
+    ("w", "Wikipedia", "https://en.wikipedia.org/wiki/Special:Search?search={searchTerms}"),
+    ("gh", "GitHub", "https://github.com/search?q={searchTerms}"),
+    ("yt", "YouTube", "https://www.youtube.com/results?search_query={searchTerms}"),
+    ("so", "Stack Overflow", "https://stackoverflow.com/search?q={searchTerms}"),
+    ("mdn", "MDN", "https://developer.mozilla.org/en-US/search?q={searchTerms}"),
+    ("rs", "Docs.rs", "https://docs.rs/releases/search?query={searchTerms}"),
+    ("crates", "crates.io", "https://crates.io/search?q={searchTerms}"),
+    ("npm", "npm", "https://www.npmjs.com/search?q={searchTerms}"),
+    ("r", "Reddit", "https://www.reddit.com/search/?q={searchTerms}"),
+    ("a", "Amazon", "https://www.amazon.com/s?k={searchTerms}"),
+    ("maps", "Google Maps", "https://www.google.com/maps/search/{searchTerms}"),
+    ("imdb", "IMDb", "https://www.imdb.com/find/?q={searchTerms}"),
+];
+
+fn bang_engine(bang: &str, engines: &[SearchEngine]) -> Option<SearchEngine> {
+    if bang.is_empty() {
+        return None;
+    }
+    if let Some(engine) = engines
+        .iter()
+        .find(|engine| engine.keyword.as_deref().is_some_and(|keyword| keyword.eq_ignore_ascii_case(bang)))
+    {
+        return Some(engine.clone());
+    }
+    BANGS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(bang))
+        .map(|(name, title, template)| SearchEngine {
+            id: format!("!{}", name),
+            name: title.to_string(),
+            keyword: None,
+            search_url: template.to_string(),
+            suggest_url: None,
+            icon_url: None,
+        })
+}
+
+/// A query that names its engine instead of using the default one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplicitSearch {
+    pub engine: SearchEngine,
+    /// The query without its keyword or bang
+    pub terms: String,
+    /// `gh` or `!gh`, as typed
+    trigger: String,
+}
+
+impl ExplicitSearch {
+    /// Results page, or the engine's front page when nothing follows the keyword
+    pub fn url(&self) -> String {
+        if self.terms.is_empty() {
+            self.engine.home_url()
+        } else {
+            self.engine.search_url(&self.terms)
+        }
+    }
+
+    /// What to type to search `terms` with the same engine, e.g. for a suggestion
+    pub fn requery(&self, terms: &str) -> String {
+        format!("{} {}", self.trigger, terms)
+    }
+}
+
+/// `gh rust tauri` (keyword), `!gh rust tauri` or `rust tauri !gh` (bang).
+/// A keyword on its own is an ordinary search, so typing a word that happens to be one still works.
+pub fn explicit_search(input: &str, engines: &[SearchEngine]) -> Option<ExplicitSearch> {
+    let input = input.trim();
+    let (first, rest) = match input.split_once(char::is_whitespace) {
+        Some((first, rest)) => (first, rest.trim()),
+        None => (input, ""),
+    };
+    let explicit = |engine: SearchEngine, terms: &str, trigger: &str| ExplicitSearch {
+        engine,
+        terms: terms.to_string(),
+        trigger: trigger.to_string(),
+    };
+
+    if let Some(engine) = first.strip_prefix('!').and_then(|bang| bang_engine(bang, engines)) {
+        return Some(explicit(engine, rest, first));
+    }
+
+    if !rest.is_empty() {
+        let keyword_engine = engines
+            .iter()
+            .find(|engine| engine.keyword.as_deref().is_some_and(|keyword| keyword.eq_ignore_ascii_case(first)));
+        if let Some(engine) = keyword_engine {
+            return Some(explicit(engine.clone(), rest, first));
+        }
+    }
+
+    let (terms, last) = input.rsplit_once(char::is_whitespace)?;
+    let engine = last.strip_prefix('!').and_then(|bang| bang_engine(bang, engines))?;
+    Some(explicit(engine, terms.trim(), last))
+}
+
+/// Built-in and custom engines, for the settings UI
+#[tauri::command]
+pub async fn get_search_engines(
+    store: tauri::State<'_, SettingsStore>,
+) -> Result<Vec<SearchEngine>, SettingsError> {
+    Ok(store.snapshot().engines())
+}
+
+/// Add a custom engine. Blank optional fields are treated as missing.
+#[tauri::command]
+pub async fn add_search_engine(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    name: String,
+    keyword: Option<String>,
+    search_url: String,
+    suggest_url: Option<String>,
+) -> Result<SearchEngine, SettingsError> {
+    let optional = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let engine = SearchEngine {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        keyword: optional(keyword),
+        search_url: search_url.trim().to_string(),
+        suggest_url: optional(suggest_url),
+        icon_url: None,
+    };
+    add_custom(&app, &store, engine)
+}
+
+/// Save a new custom engine
+pub fn add_custom(
+    app: &tauri::AppHandle,
+    store: &SettingsStore,
+    engine: SearchEngine,
+) -> Result<SearchEngine, SettingsError> {
+    store.update(app, |settings| {
+        let mut updated = settings.clone();
+        updated.custom_search_engines.push(engine.clone());
+        Ok(updated)
+    })?;
+    Ok(engine)
+}
+
+/// Replace a custom engine with the same id
+#[tauri::command]
+pub async fn update_search_engine(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    engine: SearchEngine,
+) -> Result<SearchEngine, SettingsError> {
+    store.update(&app, |settings| {
+        let mut updated = settings.clone();
+        let existing = updated
+            .custom_search_engines
+            .iter_mut()
+            .find(|existing| existing.id == engine.id)
+            .ok_or_else(|| SettingsError::UnknownEngine(engine.id.clone()))?;
+        *existing = engine.clone();
+        Ok(updated)
+    })?;
+    Ok(engine)
+}
+
+/// Remove a custom engine. If it was the default, the default goes back to Google.
+#[tauri::command]
+pub async fn remove_search_engine(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    id: String,
+) -> Result<(), SettingsError> {
+    store.update(&app, |settings| {
+        let mut updated = settings.clone();
+        let before = updated.custom_search_engines.len();
+        updated.custom_search_engines.retain(|engine| engine.id != id);
+        if updated.custom_search_engines.len() == before {
+            return Err(SettingsError::UnknownEngine(id.clone()));
+        }
+        if updated.search_engine == id {
+            updated.search_engine = DEFAULT_ENGINE_ID.to_string();
+        }
+        Ok(updated)
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(id: &str, keyword: Option<&str>, search_url: &str) -> SearchEngine {
+        SearchEngine {
+            id: id.to_string(),
+            name: format!("Engine {}", id),
+            keyword: keyword.map(str::to_string),
+            search_url: search_url.to_string(),
+            suggest_url: None,
+            icon_url: None,
+        }
+    }
+
+    fn engine_with(keyword: Option<&str>) -> SearchEngine {
+        custom("custom-1", keyword, "https://search.example/?q={searchTerms}")
+    }
+
+    #[test]
+    fn templates_need_search_terms_and_a_web_scheme() {
+        assert_eq!(engine_with(Some("ex")).validate(), Ok(()));
+        for search_url in [
+            "https://search.example/?q=",
+            "ftp://search.example/{searchTerms}",
+            "javascript:alert('{searchTerms}')",
+            "search.example/?q={searchTerms}",
+        ] {
+            let error = custom("custom-1", None, search_url).validate().unwrap_err();
+            assert!(error.contains("search URL"), "{}: {}", search_url, error);
+        }
+
+        let mut engine = engine_with(None);
+        engine.suggest_url = Some("https://search.example/suggest".to_string());
+        assert!(engine.validate().unwrap_err().contains("suggestion URL"));
+        let mut engine = engine_with(None);
+        engine.icon_url = Some("file:///icon.png".to_string());
+        assert!(engine.validate().unwrap_err().contains("icon"));
+        engine.icon_url = Some("data:image/png;base64,AA==".to_string());
+        assert_eq!(engine.validate(), Ok(()));
+        assert!(SearchEngine { name: " ".to_string(), ..engine_with(None) }.validate().is_err());
+    }
+
+    #[test]
+    fn keywords_are_single_words_without_a_bang() {
+        for keyword in ["", "!ex", "two words"] {
+            assert!(engine_with(Some(keyword)).validate().unwrap_err().contains("keyword"), "{:?}", keyword);
+        }
+    }
+
+    #[test]
+    fn custom_engines_need_unique_ids_and_keywords() {
+        let first = custom("custom-1", Some("ex"), "https://one.example/?q={searchTerms}");
+        let second = custom("custom-2", Some("other"), "https://two.example/?q={searchTerms}");
+        assert_eq!(validate_custom(&[first.clone(), second.clone()]), Ok(()));
+
+        let clash = SearchEngine { keyword: Some("EX".to_string()), ..second.clone() };
+        assert!(validate_custom(&[first.clone(), clash]).unwrap_err().contains("already used"));
+        let same_id = SearchEngine { id: "custom-1".to_string(), ..second.clone() };
+        assert!(validate_custom(&[first.clone(), same_id]).unwrap_err().contains("unique id"));
+        let builtin_id = SearchEngine { id: "google".to_string(), ..second };
+        assert!(validate_custom(&[builtin_id]).unwrap_err().contains("unique id"));
+    }
+
+    #[test]
+    fn bangs_work_at_either_end_of_the_query() {
+        let search = explicit_search("!gh rust tauri", &[]).unwrap();
+        assert_eq!((search.engine.name.as_str(), search.terms.as_str()), ("GitHub", "rust tauri"));
+        assert_eq!(search.url(), "https://github.com/search?q=rust%20tauri");
+
+        let search = explicit_search("rust tauri !GH", &[]).unwrap();
+        assert_eq!((search.engine.name.as_str(), search.terms.as_str()), ("GitHub", "rust tauri"));
+
+        // A bang on its own opens the engine's front page
+        let search = explicit_search("!w", &[]).unwrap();
+        assert_eq!(search.url(), "https://en.wikipedia.org/");
+
+        assert_eq!(explicit_search("!nope rust", &[]), None);
+        assert_eq!(explicit_search("rust !nope", &[]), None);
+        assert_eq!(explicit_search("rust tauri", &[]), None);
+        assert_eq!(explicit_search("rust! tauri", &[]), None);
+    }
+
+    #[test]
+    fn keywords_need_a_query_and_take_over_bangs_of_the_same_name() {
+        let engines = [engine_with(Some("g"))];
+        let search = explicit_search("G rust", &engines).unwrap();
+        assert_eq!((search.engine.id.as_str(), search.terms.as_str()), ("custom-1", "rust"));
+        assert_eq!(explicit_search("!g rust", &engines).unwrap().engine.id, "custom-1");
+        assert_eq!(explicit_search("rust !g", &engines).unwrap().engine.id, "custom-1");
+        // Typing just the keyword is an ordinary search for that word
+        assert_eq!(explicit_search("g", &engines), None);
+        assert_eq!(explicit_search("!g rust", &[]).unwrap().engine.name, "Google");
+    }
+
+    #[test]
+    fn requery_keeps_the_keyword_or_bang() {
+        let engines = [engine_with(Some("ex"))];
+        let keyword = explicit_search("ex rust", &engines).unwrap();
+        assert_eq!(keyword.requery("rust book"), "ex rust book");
+        let bang = explicit_search("rust !so", &engines).unwrap();
+        assert_eq!(bang.requery("rust borrow checker"), "!so rust borrow checker");
+        assert_eq!(explicit_search(&bang.requery("x"), &engines).unwrap().engine.name, "Stack Overflow");
+    }
+}
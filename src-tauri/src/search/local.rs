@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tauri::{Manager, Url};
+
+use crate::bookmarks::BookmarkStore;
+use crate::clock;
+use crate::history::HistoryStore;
+use crate::profiles;
+use crate::url_monitor::UrlMonitorRegistry;
+
+use super::answers::Answer;
+
+/// Maximum number of entries in the merged list
+pub const MAX_RESULTS: usize = 8;
+
+/// History pages considered per query before ranking
+const HISTORY_CANDIDATES: u32 = 50;
+
+/// Age at which a visit counts half as much as one made now
+const RECENCY_HALF_LIFE_DAYS: f64 = 7.0;
+
+/// What selecting a suggestion does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SuggestionKind {
+    /// Search for `title` with the configured engine
+    Search,
+    /// Open a page from history
+    History,
+    /// Open a bookmark
+    Bookmark,
+    /// Bring an open content window to the front
+    SwitchToWindow,
+    /// A calculation, unit conversion or time zone answer computed locally
+    Answer,
+}
+
+/// One entry of the omnibox dropdown
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    /// Search phrase, page title or bookmark name
+    pub title: String,
+    /// Page to open; `None` for searches
+    pub url: Option<String>,
+    /// Content webview label for `switch-to-window`
+    pub window_label: Option<String>,
+    /// The computed result for `answer`
+    pub answer: Option<Answer>,
+    /// Relevance, higher first
+    pub score: f64,
+}
+
+impl Suggestion {
+    pub fn search(phrase: String, rank: usize) -> Self {
+        Self {
+            kind: SuggestionKind::Search,
+            title: phrase,
+            url: None,
+            window_label: None,
+            answer: None,
+            // Keep the engine's order; a strong local match outranks all of them
+            score: 1.0 - rank as f64 * 0.1,
+        }
+    }
+
+    fn page(kind: SuggestionKind, title: &str, url: &str, score: f64) -> Self {
+        Self {
+            kind,
+            title: if title.is_empty() { url.to_string() } else { title.to_string() },
+            url: Some(url.to_string()),
+            window_label: None,
+            answer: None,
+            score,
+        }
+    }
+
+    /// Answers are pinned above the ranked list rather than scored against it
+    pub fn answer(answer: Answer) -> Self {
+        Self {
+            kind: SuggestionKind::Answer,
+            title: answer.display.clone(),
+            url: None,
+            window_label: None,
+            answer: Some(answer),
+            score: 0.0,
+        }
+    }
+}
+
+/// How well `query` matches a page: host prefix > word prefix in the title > substring anywhere
+fn match_quality(query: &str, title: &str, url: &str) -> Option<f64> {
+    let title = title.to_lowercase();
+    let url = url.to_lowercase();
+    let host = Url::parse(&url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.trim_start_matches("www.").to_string()))
+        .unwrap_or_default();
+
+    if host.starts_with(query) {
+        Some(1.0)
+    } else if title.split(|c: char| !c.is_alphanumeric()).any(|word| word.starts_with(query)) {
+        Some(0.8)
+    } else if title.contains(query) || url.contains(query) {
+        Some(0.5)
+    } else {
+        None
+    }
+}
+
+/// 1.0 for now, halving every `RECENCY_HALF_LIFE_DAYS`
+fn recency(timestamp: i64, now: i64) -> f64 {
+    let age_days = (now - timestamp).max(0) as f64 / 86_400_000.0;
+    0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+/// Frequent and recent pages score highest; old one-off visits fade below search suggestions
+fn frecency(visit_count: i64, last_visit: i64, now: i64) -> f64 {
+    (1.0 + (visit_count.max(0) as f64).ln_1p()) * (0.25 + 0.75 * recency(last_visit, now))
+}
+
+/// Matches from open windows, bookmarks and history
+pub fn local_suggestions(app: &tauri::AppHandle, query: &str) -> Vec<Suggestion> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let now = clock::now_millis();
+    let mut suggestions = Vec::new();
+
+    let history_matches = app
+        .try_state::<HistoryStore>()
+        .and_then(|store| store.search_pages(&query, HISTORY_CANDIDATES).ok())
+        .unwrap_or_default();
+    let visits: HashMap<&str, i64> = history_matches
+        .iter()
+        .map(|page| (page.url.as_str(), page.visit_count))
+        .collect();
+
+    if let Some(registry) = app.try_state::<UrlMonitorRegistry>() {
+        // Private windows never show up outside themselves, nor do other profiles' windows
+        let pages = registry
+            .pages()
+            .into_iter()
+            .filter(|page| !page.private && profiles::in_active_profile(app, &page.window_label));
+        for page in pages {
+            if let Some(quality) = match_quality(&query, &page.title, &page.url) {
+                // An already open page is almost always what the user is after
+                let score = quality * 3.0;
+                let mut suggestion =
+                    Suggestion::page(SuggestionKind::SwitchToWindow, &page.title, &page.url, score);
+                suggestion.window_label = Some(page.window_label);
+                suggestions.push(suggestion);
+            }
+        }
+    }
+
+    if let Some(store) = app.try_state::<BookmarkStore>() {
+        for bookmark in store.snapshot().bookmarks {
+            let tagged = bookmark.tags.iter().any(|tag| tag.to_lowercase().starts_with(&query));
+            let quality = match_quality(&query, &bookmark.name, &bookmark.url)
+                .or(tagged.then_some(0.8));
+            if let Some(quality) = quality {
+                let visit_count = visits.get(bookmark.url.as_str()).copied().unwrap_or(0);
+                let last_used = bookmark.last_visited_at.unwrap_or(bookmark.created_at);
+                // Bookmarking a page is a strong signal on its own
+                let score = quality * 1.5 * frecency(visit_count.max(1), last_used, now);
+                suggestions.push(Suggestion::page(
+                    SuggestionKind::Bookmark,
+                    &bookmark.name,
+                    &bookmark.url,
+                    score,
+                ));
+            }
+        }
+    }
+
+    for page in &history_matches {
+        if let Some(quality) = match_quality(&query, &page.title, &page.url) {
+            let score = quality * frecency(page.visit_count, page.last_visit_time, now);
+            suggestions.push(Suggestion::page(
+                SuggestionKind::History,
+                &page.title,
+                &page.url,
+                score,
+            ));
+        }
+    }
+
+    suggestions
+}
+
+/// One ranked list: a URL appears once (an open window beats a bookmark beats history)
+/// and search phrases are de-duplicated case-insensitively
+pub fn merge(local: Vec<Suggestion>, remote: Vec<Suggestion>) -> Vec<Suggestion> {
+    let mut by_key: HashMap<String, Suggestion> = HashMap::new();
+
+    for suggestion in local.into_iter().chain(remote) {
+        let key = match &suggestion.url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("search:{}", suggestion.title.to_lowercase()),
+        };
+        match by_key.get_mut(&key) {
+            Some(existing) => {
+                // Keep the most actionable kind with the best score among duplicates
+                let score = existing.score.max(suggestion.score);
+                if kind_priority(suggestion.kind) > kind_priority(existing.kind) {
+                    *existing = suggestion;
+                }
+                existing.score = score;
+            }
+            None => {
+                by_key.insert(key, suggestion);
+            }
+        }
+    }
+
+    let mut merged: Vec<Suggestion> = by_key.into_values().collect();
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged.truncate(MAX_RESULTS);
+    merged
+}
+
+fn kind_priority(kind: SuggestionKind) -> u8 {
+    match kind {
+        SuggestionKind::Search => 0,
+        SuggestionKind::History => 1,
+        SuggestionKind::Bookmark => 2,
+        SuggestionKind::SwitchToWindow => 3,
+        SuggestionKind::Answer => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400_000;
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn page(kind: SuggestionKind, url: &str, score: f64) -> Suggestion {
+        Suggestion::page(kind, "", url, score)
+    }
+
+    #[test]
+    fn recency_halves_every_seven_days() {
+        assert_eq!(recency(NOW, NOW), 1.0);
+        assert!((recency(NOW - 7 * DAY, NOW) - 0.5).abs() < 1e-9);
+        assert!((recency(NOW - 14 * DAY, NOW) - 0.25).abs() < 1e-9);
+        // Clock skew never makes a visit count for more than now
+        assert_eq!(recency(NOW + DAY, NOW), 1.0);
+    }
+
+    #[test]
+    fn frequent_pages_win_until_they_go_stale() {
+        let rare_recent = frecency(1, NOW, NOW);
+        assert!(frecency(20, NOW - 3 * DAY, NOW) > rare_recent);
+        assert!(frecency(20, NOW - 30 * DAY, NOW) < rare_recent);
+
+        // Old pages fade but never drop to nothing
+        let ancient = frecency(1, NOW - 365 * DAY, NOW);
+        assert!(ancient > 0.25 && ancient < frecency(1, NOW - 7 * DAY, NOW));
+        assert_eq!(frecency(-3, NOW, NOW), frecency(0, NOW, NOW));
+    }
+
+    #[test]
+    fn host_matches_beat_title_matches_beat_substrings() {
+        assert_eq!(match_quality("git", "Some repo", "https://www.github.com/rust-lang"), Some(1.0));
+        assert_eq!(match_quality("rust", "The Rust Book", "https://doc.example.org/book/"), Some(0.8));
+        assert_eq!(match_quality("ust", "The Rust Book", "https://doc.example.org/book/"), Some(0.5));
+        assert_eq!(match_quality("lang", "Some repo", "https://www.github.com/rust-lang"), Some(0.5));
+        assert_eq!(match_quality("python", "The Rust Book", "https://doc.example.org/book/"), None);
+    }
+
+    #[test]
+    fn duplicates_keep_the_most_actionable_kind_and_best_score() {
+        let mut window = page(SuggestionKind::SwitchToWindow, "https://a.example/", 0.5);
+        window.window_label = Some("content-1".to_string());
+        let local = vec![
+            page(SuggestionKind::History, "https://a.example", 2.0),
+            page(SuggestionKind::Bookmark, "https://a.example/", 1.0),
+            window,
+            page(SuggestionKind::History, "https://b.example/", 0.1),
+        ];
+        let remote = ["Rust", "rust"].into_iter().enumerate();
+        let remote = remote.map(|(rank, phrase)| Suggestion::search(phrase.to_string(), rank)).collect();
+
+        let merged = merge(local, remote);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].kind, SuggestionKind::SwitchToWindow);
+        assert_eq!(merged[0].score, 2.0);
+        assert_eq!(merged[0].window_label.as_deref(), Some("content-1"));
+        assert_eq!((merged[1].kind, merged[1].title.as_str()), (SuggestionKind::Search, "Rust"));
+        assert_eq!(merged[2].url.as_deref(), Some("https://b.example/"));
+    }
+
+    #[test]
+    fn merged_list_is_sorted_and_truncated() {
+        let local = (0..MAX_RESULTS + 4)
+            .map(|index| page(SuggestionKind::History, &format!("https://{}.example/", index), index as f64))
+            .collect();
+        let merged = merge(local, Vec::new());
+        assert_eq!(merged.len(), MAX_RESULTS);
+        assert_eq!(merged[0].score, (MAX_RESULTS + 3) as f64);
+        assert!(merged.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+}
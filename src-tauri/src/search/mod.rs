@@ -0,0 +1,221 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use tauri::Manager;
+
+use crate::settings;
+use crate::tabs::TabRegistry;
+use crate::url_monitor::UrlMonitorRegistry;
+
+mod answers;
+pub mod engines;
+pub mod opensearch;
+mod local;
+mod providers;
+mod suggestions;
+
+pub use engines::{builtin_engines, explicit_search, validate_custom, SearchEngine, DEFAULT_ENGINE_ID};
+pub use local::Suggestion;
+pub use opensearch::OpenSearchDiscovery;
+pub use suggestions::SuggestionService;
+
+/// Structured suggestion failure returned to the frontend
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum SuggestionError {
+    /// A newer request from the same window replaced this one - drop it silently
+    Superseded,
+    /// The provider couldn't be reached or answered with something unexpected
+    Failed(String),
+}
+
+impl fmt::Display for SuggestionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuggestionError::Superseded => write!(f, "Superseded by a newer request"),
+            SuggestionError::Failed(message) => write!(f, "Suggestions failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SuggestionError {}
+
+/// Suggestions for one request, tagged with the caller's request ID
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionResponse {
+    pub request_id: u64,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Omnibox suggestions: an instant answer (calculation, unit or time zone conversion)
+/// first, then open windows, bookmarks and history merged with the search engine's
+/// suggestions in one ranked list. A keyword or `!bang` query only gets that engine's
+/// suggestions, phrased so selecting one searches the same engine.
+/// `request_id` must increase with every keystroke; once a newer request arrives from
+/// the same webview, older ones are cancelled and fail with `superseded`.
+/// `window_label` is the browser window (or one of its tabs) the query is typed for,
+/// absent when it opens a new window; queries for a private window are never cached.
+#[tauri::command]
+pub async fn get_search_suggestions(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    service: tauri::State<'_, SuggestionService>,
+    query: String,
+    request_id: u64,
+    window_label: Option<String>,
+) -> Result<SuggestionResponse, SuggestionError> {
+    // Every keystroke replaces the last, even one that never reaches a provider
+    let newer = service.begin(webview.label(), request_id)?;
+    if query.trim().is_empty() {
+        return Ok(SuggestionResponse { request_id, suggestions: Vec::new() });
+    }
+
+    let settings = settings::current(&app);
+    let explicit = explicit_search(&query, &settings.engines());
+
+    let (answer, local) = match explicit {
+        Some(_) => (None, Vec::new()),
+        None => (answers::answer(&query), local::local_suggestions(&app, &query)),
+    };
+
+    let (engine, terms) = match &explicit {
+        Some(explicit) => (explicit.engine.clone(), explicit.terms.clone()),
+        None => (settings.default_engine(), query.clone()),
+    };
+    let remote = match providers::provider_for(&engine).filter(|_| !terms.is_empty()) {
+        Some(provider) => match service
+            .fetch(
+                request_id,
+                newer,
+                provider.as_ref(),
+                settings.suggest_base_url.as_deref(),
+                &terms,
+                window_label.as_deref().is_some_and(|label| {
+                    is_private_target(&app.state::<TabRegistry>(), &app.state::<UrlMonitorRegistry>(), label)
+                }),
+            )
+            .await
+        {
+            Ok(phrases) => phrases
+                .into_iter()
+                .enumerate()
+                .map(|(rank, phrase)| {
+                    let phrase = match &explicit {
+                        Some(explicit) => explicit.requery(&phrase),
+                        None => phrase,
+                    };
+                    Suggestion::search(phrase, rank)
+                })
+                .collect(),
+            // Offline or a flaky engine shouldn't hide local matches
+            Err(SuggestionError::Failed(_)) => Vec::new(),
+            Err(error) => return Err(error),
+        },
+        None => Vec::new(),
+    };
+
+    let mut suggestions = local::merge(local, remote);
+    if let Some(answer) = answer {
+        suggestions.insert(0, Suggestion::answer(answer));
+        suggestions.truncate(local::MAX_RESULTS);
+    }
+    Ok(SuggestionResponse { request_id, suggestions })
+}
+
+/// Whether a browser window, named by its own label or one of its tabs', is private
+fn is_private_target(tabs: &TabRegistry, monitor: &UrlMonitorRegistry, label: &str) -> bool {
+    monitor.is_private(label) || tabs.tabs(&tabs.window_of(label)).iter().any(|tab| monitor.is_private(tab))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use super::providers::SuggestionProvider;
+    use super::*;
+
+    struct LinesProvider;
+
+    impl SuggestionProvider for LinesProvider {
+        fn name(&self) -> &'static str {
+            "lines"
+        }
+
+        fn default_base_url(&self) -> &'static str {
+            "http://127.0.0.1:9"
+        }
+
+        fn request_path(&self, encoded_query: &str) -> String {
+            format!("/suggest?q={}", encoded_query)
+        }
+
+        fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+            Ok(body.lines().map(String::from).collect())
+        }
+    }
+
+    /// Answer a single request with `body`, then stop listening
+    fn serve_once(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 4096]);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        });
+        base_url
+    }
+
+    /// `window-1` is a regular window, `window-2` a private one
+    fn windows() -> (TabRegistry, UrlMonitorRegistry) {
+        let tabs = TabRegistry::default();
+        let monitor = UrlMonitorRegistry::default();
+        tabs.add("window-1", "content-1", None, true);
+        monitor.subscribe("content-1", false);
+        tabs.add("window-2", "content-2", None, true);
+        tabs.add("window-2", "content-3", None, false);
+        monitor.subscribe("content-2", true);
+        monitor.subscribe("content-3", true);
+        (tabs, monitor)
+    }
+
+    #[test]
+    fn private_windows_are_recognised_by_any_of_their_labels() {
+        let (tabs, monitor) = windows();
+        assert!(is_private_target(&tabs, &monitor, "window-2"));
+        assert!(is_private_target(&tabs, &monitor, "content-3"));
+        assert!(!is_private_target(&tabs, &monitor, "window-1"));
+        assert!(!is_private_target(&tabs, &monitor, "content-1"));
+        assert!(!is_private_target(&tabs, &monitor, "window-9"));
+    }
+
+    #[test]
+    fn queries_for_private_windows_are_not_cached() {
+        let (tabs, monitor) = windows();
+        let service = SuggestionService::default();
+        let fetch = |request_id, base_url: &str, window_label| {
+            let newer = service.begin("main", request_id).unwrap();
+            tauri::async_runtime::block_on(service.fetch(
+                request_id,
+                newer,
+                &LinesProvider,
+                Some(base_url),
+                "secret",
+                is_private_target(&tabs, &monitor, window_label),
+            ))
+        };
+
+        let base_url = serve_once("secret plans");
+        assert_eq!(fetch(1, &base_url, "window-2").unwrap(), ["secret plans"]);
+        // The server is gone, so a cached answer is the only way this could succeed
+        assert!(matches!(fetch(2, &base_url, "window-1"), Err(SuggestionError::Failed(_))));
+    }
+}
@@ -0,0 +1,560 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use tauri::{Emitter, Manager, Url};
+
+use super::engines::{self, SearchEngine, SEARCH_TERMS};
+use crate::settings::{self, Settings, SettingsStore};
+
+/// Results page and suggestion `<Url>` types
+const RESULTS_TYPES: &[&str] = &["text/html", "application/xhtml+xml"];
+const SUGGESTIONS_TYPE: &str = "application/x-suggestions+json";
+
+/// Descriptions looked at per page
+const MAX_LINKS_PER_PAGE: usize = 4;
+
+/// Descriptions are tiny; anything bigger isn't one
+const MAX_DESCRIPTION_BYTES: usize = 64 * 1024;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A site's search engine as described by its OpenSearch XML
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenSearchDescription {
+    /// `ShortName`
+    pub name: String,
+    pub description: Option<String>,
+    /// Results page with a `{searchTerms}` placeholder
+    pub search_url: String,
+    /// OpenSearch JSON suggestions with a `{searchTerms}` placeholder
+    pub suggest_url: Option<String>,
+    /// Largest `Image`, as an absolute or `data:` URL
+    pub icon_url: Option<String>,
+}
+
+impl OpenSearchDescription {
+    fn into_engine(self) -> SearchEngine {
+        SearchEngine {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: self.name,
+            keyword: None,
+            search_url: self.search_url,
+            suggest_url: self.suggest_url,
+            icon_url: self.icon_url,
+        }
+    }
+}
+
+/// A discovered engine waiting for the user, emitted as `search-engine-discovered`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchEngineOffer {
+    /// Where the description came from; identifies the offer
+    pub description_url: String,
+    pub page_url: String,
+    /// Content webview the page was loaded in
+    pub window_label: String,
+    pub engine: OpenSearchDescription,
+}
+
+/// Description URLs a page script found in `<link rel="search">` tags: http(s) only,
+/// without duplicates and at most a few, so a page can't queue up fetches
+fn description_urls(links: &[String]) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::new();
+    for url in links.iter().filter_map(|link| Url::parse(link).ok()) {
+        if matches!(url.scheme(), "http" | "https") && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls.truncate(MAX_LINKS_PER_PAGE);
+    urls
+}
+
+/// One `<Url>` element
+#[derive(Default)]
+struct UrlElement {
+    kind: String,
+    method: String,
+    template: String,
+    /// Mozilla's `<Param name="q" value="{searchTerms}"/>` children
+    params: Vec<(String, String)>,
+}
+
+/// One `<Image>` element
+struct ImageElement {
+    width: u32,
+    url: String,
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref().eq_ignore_ascii_case(name.as_bytes()))
+        .and_then(|attr| attr.unescape_value().ok().map(Cow::into_owned))
+}
+
+/// Parse an OpenSearch description (`OpenSearchDescription`, or Mozilla's `SearchPlugin`).
+/// Relative URLs are resolved against `description_url`.
+pub fn parse_description(xml: &str, description_url: &Url) -> Result<OpenSearchDescription, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut open: Vec<String> = Vec::new();
+    let mut name = String::new();
+    let mut description = String::new();
+    let mut urls: Vec<UrlElement> = Vec::new();
+    let mut images: Vec<ImageElement> = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid OpenSearch XML: {}", e))?;
+        let (element, empty) = match &event {
+            Event::Start(element) => (Some(element), false),
+            Event::Empty(element) => (Some(element), true),
+            _ => (None, false),
+        };
+
+        if let Some(element) = element {
+            let local = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+            if open.is_empty() && local != "OpenSearchDescription" && local != "SearchPlugin" {
+                return Err("Not an OpenSearch description".to_string());
+            }
+            match local.as_str() {
+                "Url" => urls.push(UrlElement {
+                    kind: attribute(element, "type").unwrap_or_default(),
+                    method: attribute(element, "method").unwrap_or_default(),
+                    template: attribute(element, "template").unwrap_or_default(),
+                    params: Vec::new(),
+                }),
+                "Param" if open.last().is_some_and(|parent| parent == "Url") => {
+                    if let (Some(url), Some(param)) = (urls.last_mut(), attribute(element, "name")) {
+                        url.params.push((param, attribute(element, "value").unwrap_or_default()));
+                    }
+                }
+                "Image" => images.push(ImageElement {
+                    width: attribute(element, "width").and_then(|w| w.parse().ok()).unwrap_or(0),
+                    url: String::new(),
+                }),
+                _ => {}
+            }
+            if !empty {
+                open.push(local);
+            }
+            continue;
+        }
+
+        match event {
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| format!("Invalid OpenSearch XML: {}", e))?;
+                match open.last().map(String::as_str) {
+                    Some("ShortName") => name.push_str(&text),
+                    Some("Description") => description.push_str(&text),
+                    Some("Image") => {
+                        if let Some(image) = images.last_mut() {
+                            image.url.push_str(&text);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(_) => {
+                open.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("OpenSearch description has no ShortName".to_string());
+    }
+
+    let template_of = |types: &[&str]| {
+        urls.iter()
+            .filter(|url| types.iter().any(|kind| url.kind.eq_ignore_ascii_case(kind)))
+            .find_map(|url| build_template(url, description_url))
+    };
+    let search_url = template_of(RESULTS_TYPES)
+        .ok_or_else(|| "OpenSearch description has no usable results URL".to_string())?;
+    let suggest_url = template_of(&[SUGGESTIONS_TYPE]);
+
+    let icon_url = images
+        .iter()
+        .filter_map(|image| {
+            let url = description_url.join(image.url.trim()).ok()?;
+            matches!(url.scheme(), "http" | "https" | "data").then(|| (image.width, url.to_string()))
+        })
+        // Largest first; the first of equal sizes wins
+        .reduce(|best, next| if next.0 > best.0 { next } else { best })
+        .map(|(_, url)| url);
+
+    let description = description.trim();
+    Ok(OpenSearchDescription {
+        name,
+        description: (!description.is_empty()).then(|| description.to_string()),
+        search_url,
+        suggest_url,
+        icon_url,
+    })
+}
+
+/// Absolute GET template with `{searchTerms}` as the only placeholder left, or `None` if
+/// the URL is POST-only or needs a parameter we can't fill in
+fn build_template(url: &UrlElement, base: &Url) -> Option<String> {
+    if !url.method.is_empty() && !url.method.eq_ignore_ascii_case("get") {
+        return None;
+    }
+
+    let mut template = url.template.trim().to_string();
+    if !url.params.is_empty() {
+        let query: Vec<String> = url.params.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        template.push(if template.contains('?') { '&' } else { '?' });
+        template.push_str(&query.join("&"));
+    }
+
+    // Fill the other OpenSearch parameters, then protect `{searchTerms}` from percent-encoding
+    let marker = "OPENSEARCHTERMS";
+    let filled = fill_parameters(&template)?.replace(SEARCH_TERMS, marker);
+    let resolved = base.join(&filled).ok()?;
+    if !matches!(resolved.scheme(), "http" | "https") {
+        return None;
+    }
+    let resolved = resolved.to_string();
+    resolved.contains(marker).then(|| resolved.replace(marker, SEARCH_TERMS))
+}
+
+/// Replace every `{parameter}` except `{searchTerms}` with a sensible value.
+/// Optional ones (`{startPage?}`) become empty; unknown required ones fail.
+fn fill_parameters(template: &str) -> Option<String> {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let end = start + rest[start..].find('}')?;
+        let parameter = &rest[start + 1..end];
+        let (name, optional) = match parameter.strip_suffix('?') {
+            Some(name) => (name, true),
+            None => (parameter, false),
+        };
+        // Parameters may be namespaced, e.g. `{moz:locale}`
+        let value = match name.rsplit(':').next().unwrap_or(name) {
+            "searchTerms" => SEARCH_TERMS,
+            "inputEncoding" | "outputEncoding" => "UTF-8",
+            "language" | "locale" => "*",
+            "startIndex" | "startPage" => "1",
+            "count" => "10",
+            _ if optional => "",
+            _ => return None,
+        };
+        filled.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    filled.push_str(rest);
+    Some(filled)
+}
+
+/// Host of an engine's results page
+fn engine_host(template: &str) -> Option<String> {
+    let url = Url::parse(&template.replace(SEARCH_TERMS, "test")).ok()?;
+    url.host_str().map(|host| host.trim_start_matches("www.").to_string())
+}
+
+/// Fetches descriptions for loaded pages and keeps the offers the user hasn't answered
+pub struct OpenSearchDiscovery {
+    client: reqwest::Client,
+    /// Description URLs already fetched this session, so each is fetched once
+    checked_descriptions: Mutex<HashSet<String>>,
+    /// Offers waiting for an answer, by description URL
+    offers: Mutex<HashMap<String, SearchEngineOffer>>,
+}
+
+impl Default for OpenSearchDiscovery {
+    fn default() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent("Mozilla/5.0")
+                .build()
+                .unwrap_or_default(),
+            checked_descriptions: Mutex::default(),
+            offers: Mutex::default(),
+        }
+    }
+}
+
+impl OpenSearchDiscovery {
+    /// GET a URL and read at most `limit` bytes of it as text
+    async fn fetch_text(&self, url: &Url, limit: usize) -> Result<String, String> {
+        let mut response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            body.extend_from_slice(&chunk);
+            if body.len() >= limit {
+                body.truncate(limit);
+                break;
+            }
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// New offers from a page's description links: descriptions that parse, weren't ignored
+    /// and aren't for a site that already has an engine
+    async fn discover_offers(
+        &self,
+        page_url: &Url,
+        description_urls: Vec<Url>,
+        window_label: &str,
+        settings: &Settings,
+    ) -> Vec<SearchEngineOffer> {
+        let configured: HashSet<String> = settings
+            .engines()
+            .iter()
+            .filter_map(|engine| engine_host(&engine.search_url))
+            .collect();
+
+        let mut offers = Vec::new();
+        for description_url in description_urls {
+            let key = description_url.to_string();
+            if settings.ignored_search_engine_offers.contains(&key)
+                || self.offers.lock().unwrap().contains_key(&key)
+                || !self.checked_descriptions.lock().unwrap().insert(key.clone())
+            {
+                continue;
+            }
+            let xml = match self.fetch_text(&description_url, MAX_DESCRIPTION_BYTES).await {
+                Ok(xml) => xml,
+                Err(_) => {
+                    // Unreachable for now; a later visit may try again
+                    self.checked_descriptions.lock().unwrap().remove(&key);
+                    continue;
+                }
+            };
+            let Ok(engine) = parse_description(&xml, &description_url) else {
+                continue;
+            };
+            if engine_host(&engine.search_url).is_some_and(|host| configured.contains(&host)) {
+                continue;
+            }
+            offers.push(SearchEngineOffer {
+                description_url: key,
+                page_url: page_url.to_string(),
+                window_label: window_label.to_string(),
+                engine,
+            });
+        }
+        offers
+    }
+
+    fn take(&self, description_url: &str) -> Option<SearchEngineOffer> {
+        self.offers.lock().unwrap().remove(description_url)
+    }
+}
+
+/// Offer the OpenSearch descriptions a loaded page links to as engines. The links come from
+/// the page script, so only the descriptions are fetched, never the page itself.
+pub fn discover(app: &tauri::AppHandle, window_label: &str, page_url: &str, links: &[String]) {
+    let Ok(page_url) = Url::parse(page_url) else {
+        return;
+    };
+    if !matches!(page_url.scheme(), "http" | "https") {
+        return;
+    }
+    let description_urls = description_urls(links);
+    if description_urls.is_empty() || app.try_state::<OpenSearchDiscovery>().is_none() {
+        return;
+    }
+
+    let app = app.clone();
+    let window_label = window_label.to_string();
+    tauri::async_runtime::spawn(async move {
+        let discovery = app.state::<OpenSearchDiscovery>();
+        let settings = settings::current(&app);
+        for offer in discovery
+            .discover_offers(&page_url, description_urls, &window_label, &settings)
+            .await
+        {
+            discovery
+                .offers
+                .lock()
+                .unwrap()
+                .insert(offer.description_url.clone(), offer.clone());
+            let _ = app.emit("search-engine-discovered", offer);
+        }
+    });
+}
+
+/// Offers the user hasn't accepted or ignored yet
+#[tauri::command]
+pub async fn get_search_engine_offers(
+    discovery: tauri::State<'_, OpenSearchDiscovery>,
+) -> Result<Vec<SearchEngineOffer>, String> {
+    Ok(discovery.offers.lock().unwrap().values().cloned().collect())
+}
+
+/// Add a discovered engine to the custom engines
+#[tauri::command]
+pub async fn accept_search_engine_offer(
+    app: tauri::AppHandle,
+    discovery: tauri::State<'_, OpenSearchDiscovery>,
+    store: tauri::State<'_, SettingsStore>,
+    description_url: String,
+) -> Result<SearchEngine, String> {
+    let offer = discovery
+        .take(&description_url)
+        .ok_or_else(|| format!("No pending search engine from {}", description_url))?;
+    engines::add_custom(&app, &store, offer.engine.into_engine()).map_err(|e| e.to_string())
+}
+
+/// Dismiss an offer for good
+#[tauri::command]
+pub async fn ignore_search_engine_offer(
+    app: tauri::AppHandle,
+    discovery: tauri::State<'_, OpenSearchDiscovery>,
+    store: tauri::State<'_, SettingsStore>,
+    description_url: String,
+) -> Result<(), String> {
+    discovery.take(&description_url);
+    store
+        .update(&app, |settings| {
+            let mut updated = settings.clone();
+            if !updated.ignored_search_engine_offers.contains(&description_url) {
+                updated.ignored_search_engine_offers.push(description_url.clone());
+            }
+            Ok(updated)
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIKIPEDIA: &str = include_str!("../../tests/fixtures/opensearch/wikipedia.xml");
+    const GITHUB: &str = include_str!("../../tests/fixtures/opensearch/github.xml");
+    const MOZILLA_PARAMS: &str = include_str!("../../tests/fixtures/opensearch/mozilla-params.xml");
+    const RELATIVE: &str = include_str!("../../tests/fixtures/opensearch/relative.xml");
+    const POST_ONLY: &str = include_str!("../../tests/fixtures/opensearch/post-only.xml");
+    const NOT_OPENSEARCH: &str = include_str!("../../tests/fixtures/opensearch/not-opensearch.xml");
+
+    fn url(text: &str) -> Url {
+        Url::parse(text).unwrap()
+    }
+
+    #[test]
+    fn description_links_are_checked_and_capped() {
+        let links: Vec<String> = [
+            "https://forum.example.com/opensearch.xml",
+            "javascript:alert(1)",
+            "https://forum.example.com/opensearch.xml",
+            "file:///etc/passwd",
+            "not a url",
+            "https://cdn.example.net/osd.xml",
+            "http://forum.example.com/a.xml",
+            "https://forum.example.com/b.xml",
+            "https://forum.example.com/c.xml",
+        ]
+        .map(String::from)
+        .to_vec();
+        let urls: Vec<String> = description_urls(&links).iter().map(Url::to_string).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://forum.example.com/opensearch.xml",
+                "https://cdn.example.net/osd.xml",
+                "http://forum.example.com/a.xml",
+                "https://forum.example.com/b.xml",
+            ]
+        );
+        assert!(description_urls(&[]).is_empty());
+    }
+
+    #[test]
+    fn wikipedia_description() {
+        let engine = parse_description(WIKIPEDIA, &url("https://en.wikipedia.org/w/opensearch_desc.php")).unwrap();
+        assert_eq!(engine.name, "Wikipedia (en)");
+        assert_eq!(engine.description.as_deref(), Some("Wikipedia (en)"));
+        assert_eq!(
+            engine.search_url,
+            "https://en.wikipedia.org/w/index.php?title=Special:Search&search={searchTerms}"
+        );
+        assert_eq!(
+            engine.suggest_url.as_deref(),
+            Some("https://en.wikipedia.org/w/api.php?action=opensearch&search={searchTerms}&namespace=0")
+        );
+        assert_eq!(engine.icon_url.as_deref(), Some("https://en.wikipedia.org/static/favicon/wikipedia.ico"));
+    }
+
+    #[test]
+    fn github_description_without_suggestions() {
+        let engine = parse_description(GITHUB, &url("https://github.com/opensearch.xml")).unwrap();
+        assert_eq!(engine.name, "GitHub");
+        assert_eq!(engine.search_url, "https://github.com/search?q={searchTerms}&ref=opensearch");
+        assert_eq!(engine.suggest_url, None);
+        assert_eq!(engine.icon_url.as_deref(), Some("https://github.com/fluidicon.png"));
+    }
+
+    #[test]
+    fn mozilla_params_become_the_query_string() {
+        let engine = parse_description(MOZILLA_PARAMS, &url("https://docs.example.org/search.xml")).unwrap();
+        assert_eq!(engine.name, "Example Docs");
+        assert_eq!(engine.search_url, "https://docs.example.org/search?q={searchTerms}&lang=*");
+    }
+
+    #[test]
+    fn relative_templates_and_optional_parameters() {
+        let engine = parse_description(RELATIVE, &url("https://forum.example.com/opensearch.xml")).unwrap();
+        assert_eq!(engine.search_url, "https://forum.example.com/search/{searchTerms}?page=1&enc=UTF-8&geo=");
+        assert_eq!(
+            engine.suggest_url.as_deref(),
+            Some("https://forum.example.com/suggest?term={searchTerms}")
+        );
+        assert_eq!(engine.icon_url.as_deref(), Some("https://forum.example.com/static/icon-64.png"));
+        assert_eq!(engine.description, None);
+    }
+
+    #[test]
+    fn post_only_descriptions_are_unusable() {
+        let error = parse_description(POST_ONLY, &url("https://example.com/osd.xml")).unwrap_err();
+        assert!(error.contains("no usable results URL"), "{}", error);
+    }
+
+    #[test]
+    fn other_xml_is_rejected() {
+        let error = parse_description(NOT_OPENSEARCH, &url("https://example.com/feed.xml")).unwrap_err();
+        assert_eq!(error, "Not an OpenSearch description");
+        assert!(parse_description("<OpenSearchDescription><ShortName>x", &url("https://example.com/")).is_err());
+    }
+
+    #[test]
+    fn unknown_required_parameters_fail() {
+        assert_eq!(fill_parameters("/s?q={searchTerms}&geo={geo:box}"), None);
+        assert_eq!(
+            fill_parameters("/s?q={searchTerms}&geo={geo:box?}").as_deref(),
+            Some("/s?q={searchTerms}&geo=")
+        );
+    }
+
+    #[test]
+    fn accepted_descriptions_make_valid_engines() {
+        let engine = parse_description(WIKIPEDIA, &url("https://en.wikipedia.org/w/opensearch_desc.php"))
+            .unwrap()
+            .into_engine();
+        assert_eq!(engine.validate(), Ok(()));
+    }
+}
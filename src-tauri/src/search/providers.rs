@@ -0,0 +1,323 @@
+use serde_json::Value;
+
+use super::engines::{SearchEngine, SEARCH_TERMS};
+
+/// Maximum number of suggestions returned to the frontend
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// A search engine's autocomplete endpoint and response format
+pub trait SuggestionProvider: Send + Sync {
+    /// Stable identifier, used as part of the cache key
+    fn name(&self) -> &str;
+
+    /// Scheme and host of the public endpoint, e.g. `https://duckduckgo.com`
+    fn default_base_url(&self) -> &str;
+
+    /// Path and query string for a request, appended to the base URL
+    fn request_path(&self, encoded_query: &str) -> String;
+
+    /// Extract suggestion phrases from a response body
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String>;
+
+    /// Full request URL. `base_url` replaces the public host (e.g. a local mock server).
+    fn request_url(&self, base_url: Option<&str>, query: &str) -> String {
+        let base = base_url.unwrap_or(self.default_base_url()).trim_end_matches('/');
+        format!("{}{}", base, self.request_path(&urlencoding::encode(query)))
+    }
+}
+
+/// OpenSearch suggestions: `["query", ["suggestion1", "suggestion2", ...], ...]`
+fn parse_opensearch_json(body: &str) -> Result<Vec<String>, String> {
+    let parsed: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let suggestions = parsed
+        .get(1)
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| "Unexpected suggestion response".to_string())?;
+    Ok(suggestions.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+}
+
+/// Google's suggest endpoint with the Firefox client, which answers in OpenSearch format
+pub struct GoogleSuggestions;
+
+impl SuggestionProvider for GoogleSuggestions {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://suggestqueries.google.com"
+    }
+
+    fn request_path(&self, encoded_query: &str) -> String {
+        format!("/complete/search?client=firefox&q={}", encoded_query)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+        parse_opensearch_json(body)
+    }
+}
+
+/// DuckDuckGo autocomplete: `[{"phrase": "..."}, ...]`
+pub struct DuckDuckGoSuggestions;
+
+impl SuggestionProvider for DuckDuckGoSuggestions {
+    fn name(&self) -> &'static str {
+        "duckduckgo"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://duckduckgo.com"
+    }
+
+    fn request_path(&self, encoded_query: &str) -> String {
+        format!("/ac/?q={}", encoded_query)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+        let parsed: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        let entries = parsed
+            .as_array()
+            .ok_or_else(|| "Unexpected suggestion response".to_string())?;
+        Ok(entries
+            .iter()
+            .filter_map(|entry| entry.get("phrase").and_then(|p| p.as_str()).map(String::from))
+            .collect())
+    }
+}
+
+/// Bing's OpenSearch endpoint
+pub struct BingSuggestions;
+
+impl SuggestionProvider for BingSuggestions {
+    fn name(&self) -> &'static str {
+        "bing"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.bing.com"
+    }
+
+    fn request_path(&self, encoded_query: &str) -> String {
+        format!("/osjson.aspx?query={}", encoded_query)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+        parse_opensearch_json(body)
+    }
+}
+
+/// Yahoo's "gossip" endpoint: `{"r": [{"k": "..."}, ...]}`
+pub struct YahooSuggestions;
+
+impl SuggestionProvider for YahooSuggestions {
+    fn name(&self) -> &'static str {
+        "yahoo"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://search.yahoo.com"
+    }
+
+    fn request_path(&self, encoded_query: &str) -> String {
+        format!("/sugg/gossip/gossip-us-ura/?output=sd1&command={}", encoded_query)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+        let parsed: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        let results = parsed
+            .get("r")
+            .and_then(|r| r.as_array())
+            .ok_or_else(|| "Unexpected suggestion response".to_string())?;
+        Ok(results
+            .iter()
+            .filter_map(|result| result.get("k").and_then(|k| k.as_str()).map(String::from))
+            .collect())
+    }
+}
+
+/// Brave Search's suggest API, OpenSearch format
+pub struct BraveSuggestions;
+
+impl SuggestionProvider for BraveSuggestions {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://search.brave.com"
+    }
+
+    fn request_path(&self, encoded_query: &str) -> String {
+        format!("/api/suggest?q={}", encoded_query)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+        parse_opensearch_json(body)
+    }
+}
+
+/// A custom engine's suggestion URL template, answered in OpenSearch format
+pub struct TemplateSuggestions {
+    template: String,
+    /// Scheme and host of the template
+    base_url: String,
+    /// Everything after the host, still containing `{searchTerms}`
+    path: String,
+}
+
+impl TemplateSuggestions {
+    fn new(template: &str) -> Option<Self> {
+        let host_start = template.find("://")? + 3;
+        let split = template[host_start..]
+            .find(['/', '?'])
+            .map_or(template.len(), |index| host_start + index);
+        let (base_url, path) = template.split_at(split);
+        Some(Self {
+            template: template.to_string(),
+            base_url: base_url.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl SuggestionProvider for TemplateSuggestions {
+    fn name(&self) -> &str {
+        &self.template
+    }
+
+    fn default_base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn request_path(&self, encoded_query: &str) -> String {
+        self.path.replace(SEARCH_TERMS, encoded_query)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+        parse_opensearch_json(body)
+    }
+}
+
+/// The provider for a search engine, if it offers suggestions
+pub fn provider_for(engine: &SearchEngine) -> Option<Box<dyn SuggestionProvider>> {
+    match engine.id.as_str() {
+        "google" => Some(Box::new(GoogleSuggestions)),
+        "duckduckgo" => Some(Box::new(DuckDuckGoSuggestions)),
+        "bing" => Some(Box::new(BingSuggestions)),
+        "yahoo" => Some(Box::new(YahooSuggestions)),
+        "brave" => Some(Box::new(BraveSuggestions)),
+        _ => engine
+            .suggest_url
+            .as_deref()
+            .and_then(TemplateSuggestions::new)
+            .map(|provider| Box::new(provider) as Box<dyn SuggestionProvider>),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::builtin_engines;
+
+    fn provider(id: &str) -> Box<dyn SuggestionProvider> {
+        let engine = builtin_engines().into_iter().find(|engine| engine.id == id).unwrap();
+        provider_for(&engine).unwrap()
+    }
+
+    fn custom_engine(suggest_url: Option<&str>) -> SearchEngine {
+        SearchEngine {
+            id: "custom".to_string(),
+            name: "Custom".to_string(),
+            keyword: None,
+            search_url: "https://search.example.com/?q={searchTerms}".to_string(),
+            suggest_url: suggest_url.map(str::to_string),
+            icon_url: None,
+        }
+    }
+
+    #[test]
+    fn every_builtin_engine_has_a_provider() {
+        for engine in builtin_engines() {
+            assert_eq!(provider_for(&engine).unwrap().name(), engine.id);
+        }
+        assert!(provider_for(&custom_engine(None)).is_none());
+    }
+
+    #[test]
+    fn request_urls_use_the_public_host_by_default() {
+        assert_eq!(
+            provider("google").request_url(None, "rust lang"),
+            "https://suggestqueries.google.com/complete/search?client=firefox&q=rust%20lang"
+        );
+        assert_eq!(provider("duckduckgo").request_url(None, "a&b"), "https://duckduckgo.com/ac/?q=a%26b");
+        assert_eq!(provider("bing").request_url(None, "tauri"), "https://api.bing.com/osjson.aspx?query=tauri");
+        assert_eq!(
+            provider("yahoo").request_url(None, "tauri"),
+            "https://search.yahoo.com/sugg/gossip/gossip-us-ura/?output=sd1&command=tauri"
+        );
+        assert_eq!(provider("brave").request_url(None, "tauri"), "https://search.brave.com/api/suggest?q=tauri");
+    }
+
+    #[test]
+    fn base_url_replaces_the_host() {
+        for (id, path) in [
+            ("google", "/complete/search?client=firefox&q=caf%C3%A9"),
+            ("duckduckgo", "/ac/?q=caf%C3%A9"),
+            ("bing", "/osjson.aspx?query=caf%C3%A9"),
+            ("yahoo", "/sugg/gossip/gossip-us-ura/?output=sd1&command=caf%C3%A9"),
+            ("brave", "/api/suggest?q=caf%C3%A9"),
+        ] {
+            let expected = format!("http://127.0.0.1:8080{}", path);
+            assert_eq!(provider(id).request_url(Some("http://127.0.0.1:8080"), "café"), expected);
+            // A trailing slash on the base URL doesn't double up
+            assert_eq!(provider(id).request_url(Some("http://127.0.0.1:8080/"), "café"), expected);
+        }
+    }
+
+    #[test]
+    fn template_providers_split_host_and_path() {
+        let engine = custom_engine(Some("https://search.example.com/suggest?q={searchTerms}&fmt=json"));
+        let provider = provider_for(&engine).unwrap();
+        assert_eq!(
+            provider.request_url(None, "a b"),
+            "https://search.example.com/suggest?q=a%20b&fmt=json"
+        );
+        assert_eq!(
+            provider.request_url(Some("http://localhost:9000"), "a b"),
+            "http://localhost:9000/suggest?q=a%20b&fmt=json"
+        );
+
+        let bare = TemplateSuggestions::new("https://example.com?q={searchTerms}").unwrap();
+        assert_eq!(bare.request_url(None, "x"), "https://example.com?q=x");
+        assert!(TemplateSuggestions::new("example.com/{searchTerms}").is_none());
+    }
+
+    #[test]
+    fn opensearch_responses() {
+        let body = r#"["rust", ["rust lang", "rust book", 42], [], {"google:suggesttype": []}]"#;
+        for id in ["google", "bing", "brave"] {
+            assert_eq!(provider(id).parse_response(body).unwrap(), ["rust lang", "rust book"]);
+        }
+        let custom = provider_for(&custom_engine(Some("https://example.com/s?q={searchTerms}"))).unwrap();
+        assert_eq!(custom.parse_response(body).unwrap(), ["rust lang", "rust book"]);
+
+        assert!(provider("google").parse_response(r#"["rust"]"#).is_err());
+        assert!(provider("bing").parse_response("<html>").is_err());
+    }
+
+    #[test]
+    fn duckduckgo_responses() {
+        let provider = provider("duckduckgo");
+        let body = r#"[{"phrase": "rust lang"}, {"other": 1}, {"phrase": "rust book"}]"#;
+        assert_eq!(provider.parse_response(body).unwrap(), ["rust lang", "rust book"]);
+        assert!(provider.parse_response(r#"{"phrase": "rust"}"#).is_err());
+    }
+
+    #[test]
+    fn yahoo_responses() {
+        let provider = provider("yahoo");
+        let body = r#"{"q": "rust", "r": [{"k": "rust lang", "m": 0}, {"m": 1}, {"k": "rust book"}]}"#;
+        assert_eq!(provider.parse_response(body).unwrap(), ["rust lang", "rust book"]);
+        assert!(provider.parse_response(r#"{"q": "rust"}"#).is_err());
+    }
+}
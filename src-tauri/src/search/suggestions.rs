@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+use super::providers::{SuggestionProvider, MAX_SUGGESTIONS};
+use super::SuggestionError;
+
+/// How long cached suggestions stay fresh
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum number of cached queries before the least recently used is evicted
+const CACHE_CAPACITY: usize = 256;
+
+/// Per-request timeout for suggestion endpoints
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Cache key: provider, endpoint and normalized query
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    provider: String,
+    base_url: Option<String>,
+    query: String,
+}
+
+struct CacheEntry {
+    suggestions: Vec<String>,
+    fetched_at: Instant,
+    /// Tick of the last read or write, for LRU eviction
+    last_used: u64,
+}
+
+/// In-memory TTL + LRU cache of suggestion responses
+#[derive(Default)]
+struct SuggestionCache {
+    entries: HashMap<CacheKey, CacheEntry>,
+    tick: u64,
+}
+
+impl SuggestionCache {
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<String>> {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(key) {
+            Some(entry) if entry.fetched_at.elapsed() < CACHE_TTL => {
+                entry.last_used = tick;
+                Some(entry.suggestions.clone())
+            }
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, suggestions: Vec<String>) {
+        self.tick += 1;
+        if !self.entries.contains_key(&key) && self.entries.len() >= CACHE_CAPACITY {
+            // Expired entries go first, then the least recently used
+            self.entries.retain(|_, entry| entry.fetched_at.elapsed() < CACHE_TTL);
+            if self.entries.len() >= CACHE_CAPACITY {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+        self.entries.insert(
+            key,
+            CacheEntry {
+                suggestions,
+                fetched_at: Instant::now(),
+                last_used: self.tick,
+            },
+        );
+    }
+}
+
+/// Shared HTTP client, response cache and the latest request ID per webview
+pub struct SuggestionService {
+    client: reqwest::Client,
+    cache: Mutex<SuggestionCache>,
+    latest: Mutex<HashMap<String, watch::Sender<u64>>>,
+}
+
+impl Default for SuggestionService {
+    fn default() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent("Mozilla/5.0")
+                .build()
+                .unwrap_or_default(),
+            cache: Mutex::default(),
+            latest: Mutex::default(),
+        }
+    }
+}
+
+impl SuggestionService {
+    /// Mark `request_id` as the newest request from a webview, cancelling older fetches.
+    /// Returns a receiver that changes once a newer request arrives, or `Superseded`
+    /// if this request is already older than one we've seen.
+    pub fn begin(&self, webview: &str, request_id: u64) -> Result<watch::Receiver<u64>, SuggestionError> {
+        let mut latest = self.latest.lock().unwrap();
+        let sender = latest
+            .entry(webview.to_string())
+            .or_insert_with(|| watch::channel(0).0);
+        if *sender.borrow() > request_id {
+            return Err(SuggestionError::Superseded);
+        }
+        sender.send_replace(request_id);
+        Ok(sender.subscribe())
+    }
+
+    /// Forget a webview's request state once it's gone
+    pub fn forget(&self, webview: &str) {
+        self.latest.lock().unwrap().remove(webview);
+    }
+
+    /// Fetch suggestions for a request `begin` returned `newer` for, from the cache when possible.
+    /// The HTTP request is abandoned as soon as the webview sends a newer request.
+    /// Queries typed for a private window are never cached.
+    pub async fn fetch(
+        &self,
+        request_id: u64,
+        mut newer: watch::Receiver<u64>,
+        provider: &dyn SuggestionProvider,
+        base_url: Option<&str>,
+        query: &str,
+        private: bool,
+    ) -> Result<Vec<String>, SuggestionError> {
+        if *newer.borrow() != request_id {
+            return Err(SuggestionError::Superseded);
+        }
+
+        let key = CacheKey {
+            provider: provider.name().to_string(),
+            base_url: base_url.map(String::from),
+            query: query.trim().to_lowercase(),
+        };
+        if let Some(suggestions) = self.cache.lock().unwrap().get(&key) {
+            return Ok(suggestions);
+        }
+
+        let url = provider.request_url(base_url, query);
+        let request = async {
+            let response = self.client.get(url).send().await?.error_for_status()?;
+            response.text().await
+        };
+
+        let body = tokio::select! {
+            body = request => body.map_err(|e| SuggestionError::Failed(e.to_string()))?,
+            _ = newer.wait_for(|latest| *latest != request_id) => return Err(SuggestionError::Superseded),
+        };
+
+        let mut suggestions = provider.parse_response(&body).map_err(SuggestionError::Failed)?;
+        suggestions.truncate(MAX_SUGGESTIONS);
+        if !private {
+            self.cache.lock().unwrap().insert(key, suggestions.clone());
+        }
+
+        // A newer request may have started while we were parsing
+        if *newer.borrow() != request_id {
+            return Err(SuggestionError::Superseded);
+        }
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+
+    struct MockProvider;
+
+    impl SuggestionProvider for MockProvider {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn default_base_url(&self) -> &'static str {
+            "http://127.0.0.1:9"
+        }
+
+        fn request_path(&self, encoded_query: &str) -> String {
+            format!("/suggest?q={}", encoded_query)
+        }
+
+        fn parse_response(&self, body: &str) -> Result<Vec<String>, String> {
+            Ok(body.lines().map(String::from).collect())
+        }
+    }
+
+    /// Answer a single request with `body`, then stop listening
+    fn serve_once(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 4096]);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        });
+        base_url
+    }
+
+    fn key(query: &str) -> CacheKey {
+        CacheKey { provider: "mock".to_string(), base_url: None, query: query.to_string() }
+    }
+
+    #[test]
+    fn cache_entries_expire() {
+        let mut cache = SuggestionCache::default();
+        cache.insert(key("rust"), vec!["rust lang".to_string()]);
+        assert_eq!(cache.get(&key("rust")).unwrap(), ["rust lang"]);
+
+        cache.entries.get_mut(&key("rust")).unwrap().fetched_at -= CACHE_TTL;
+        assert!(cache.get(&key("rust")).is_none());
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn full_cache_evicts_the_least_recently_used() {
+        let mut cache = SuggestionCache::default();
+        for index in 0..CACHE_CAPACITY {
+            cache.insert(key(&index.to_string()), Vec::new());
+        }
+        // Reading the oldest entry makes the second one the least recently used
+        assert!(cache.get(&key("0")).is_some());
+        cache.insert(key("new"), Vec::new());
+        assert_eq!(cache.entries.len(), CACHE_CAPACITY);
+        assert!(cache.entries.contains_key(&key("0")));
+        assert!(!cache.entries.contains_key(&key("1")));
+
+        // Replacing an entry that's already there evicts nothing
+        cache.insert(key("new"), vec!["again".to_string()]);
+        assert_eq!(cache.entries.len(), CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn full_cache_drops_expired_entries_first() {
+        let mut cache = SuggestionCache::default();
+        for index in 0..CACHE_CAPACITY {
+            cache.insert(key(&index.to_string()), Vec::new());
+        }
+        for index in 10..20 {
+            cache.entries.get_mut(&key(&index.to_string())).unwrap().fetched_at -= CACHE_TTL;
+        }
+        cache.insert(key("new"), Vec::new());
+        assert_eq!(cache.entries.len(), CACHE_CAPACITY - 9);
+        assert!(cache.entries.contains_key(&key("0")));
+    }
+
+    #[test]
+    fn newer_requests_supersede_older_ones() {
+        let service = SuggestionService::default();
+        let first = service.begin("content-1", 1).unwrap();
+        assert!(!first.has_changed().unwrap());
+
+        let second = service.begin("content-1", 2).unwrap();
+        assert!(first.has_changed().unwrap());
+        assert_eq!(*second.borrow(), 2);
+        // Late arrivals of older requests are rejected outright
+        assert!(matches!(service.begin("content-1", 1), Err(SuggestionError::Superseded)));
+        // Windows don't interfere with each other
+        assert!(service.begin("content-2", 1).is_ok());
+
+        service.forget("content-1");
+        assert!(service.begin("content-1", 1).is_ok());
+    }
+
+    #[test]
+    fn superseded_requests_are_not_fetched() {
+        let service = SuggestionService::default();
+        let newer = service.begin("content-1", 1).unwrap();
+        service.begin("content-1", 2).unwrap();
+
+        let fetch = service.fetch(1, newer, &MockProvider, None, "rust", false);
+        let result = tauri::async_runtime::block_on(fetch);
+        assert!(matches!(result, Err(SuggestionError::Superseded)));
+    }
+
+    #[test]
+    fn responses_are_cached_unless_private() {
+        let service = SuggestionService::default();
+        let fetch = |request_id, base_url: &str, query, private| {
+            let newer = service.begin("content-1", request_id).unwrap();
+            tauri::async_runtime::block_on(service.fetch(
+                request_id,
+                newer,
+                &MockProvider,
+                Some(base_url),
+                query,
+                private,
+            ))
+        };
+
+        let base_url = serve_once("rust lang\nrust book");
+        assert_eq!(fetch(1, &base_url, "Rust ", false).unwrap(), ["rust lang", "rust book"]);
+        // The mock server is gone, so this can only come from the cache
+        assert_eq!(fetch(2, &base_url, "rust", false).unwrap(), ["rust lang", "rust book"]);
+
+        let base_url = serve_once("secret");
+        assert_eq!(fetch(3, &base_url, "secret", true).unwrap(), ["secret"]);
+        assert!(matches!(fetch(4, &base_url, "secret", true), Err(SuggestionError::Failed(_))));
+    }
+}
@@ -0,0 +1,673 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, PhysicalPosition, PhysicalSize};
+
+use crate::clock::now_millis;
+use crate::containers::{self, ContainerRegistry};
+use crate::profiles::ProfileManager;
+use crate::storage;
+use crate::tabs::{self, TabRegistry};
+use crate::url_monitor::UrlMonitorRegistry;
+use crate::window::{self, WindowStorage};
+
+/// File name of the automatically saved session inside the app data directory
+pub const SESSION_FILE: &str = "session.json";
+
+/// File name of the sessions saved under a name
+pub const SAVED_SESSIONS_FILE: &str = "saved-sessions.json";
+
+/// Current on-disk schema version of both files
+const SCHEMA_VERSION: u32 = 1;
+
+/// How often open windows are written to `session.json`
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest allowed session name
+const MAX_NAME_LENGTH: usize = 100;
+
+/// Back and forward entries kept per tab; each is loaded again on restore
+const MAX_HISTORY_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTab {
+    pub url: String,
+    pub title: String,
+    /// Back/forward history around `url`, oldest first
+    #[serde(default)]
+    pub back: Vec<String>,
+    #[serde(default)]
+    pub forward: Vec<String>,
+}
+
+impl SessionTab {
+    /// A tab with at most `MAX_HISTORY_ENTRIES` entries kept on each side of `url`
+    fn new(url: String, title: String, mut back: Vec<String>, mut forward: Vec<String>) -> Self {
+        back.drain(..back.len().saturating_sub(MAX_HISTORY_ENTRIES));
+        forward.truncate(MAX_HISTORY_ENTRIES);
+        Self { url, title, back, forward }
+    }
+
+    /// Where the rebuilt tab starts: the oldest history entry, the rest is replayed from there
+    fn start_url(&self) -> &str {
+        self.back.first().unwrap_or(&self.url)
+    }
+
+    /// Pages to load after `start_url`, in order, and how many steps to go back afterwards
+    fn history_replay(&self) -> (Vec<String>, usize) {
+        let mut entries: Vec<String> = self.back.iter().skip(1).cloned().collect();
+        if !self.back.is_empty() {
+            entries.push(self.url.clone());
+        }
+        entries.extend(self.forward.iter().cloned());
+        (entries, self.forward.len())
+    }
+
+    /// Bring back the tab's back/forward history once its first page has loaded
+    fn replay_history(&self, app: &tauri::AppHandle, tab_label: &str) {
+        let (entries, back_steps) = self.history_replay();
+        app.state::<UrlMonitorRegistry>().replay_history(tab_label, self.start_url(), entries, back_steps);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowState {
+    Normal,
+    Maximized,
+    Minimized,
+    /// Hidden by the dock while another window was showing
+    Hidden,
+}
+
+/// One browser window: its tabs and where it was on screen (physical pixels)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionWindow {
+    pub tabs: Vec<SessionTab>,
+    pub active_tab: usize,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub state: WindowState,
+    /// Profile the window belonged to; windows saved before profiles open in the active one
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    /// Container whose cookies and storage the window's tabs used
+    #[serde(default)]
+    pub container_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub windows: Vec<SessionWindow>,
+    /// Index of the window the dock was showing
+    pub active_window: usize,
+    pub saved_at: i64,
+}
+
+impl Session {
+    fn summary(&self, name: Option<&str>) -> SessionSummary {
+        SessionSummary {
+            name: name.map(str::to_string),
+            saved_at: self.saved_at,
+            window_count: self.windows.len(),
+            tab_count: self.windows.iter().map(|window| window.tabs.len()).sum(),
+        }
+    }
+}
+
+/// What the frontend shows for a session without loading it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    /// `None` for the previous run's session
+    pub name: Option<String>,
+    pub saved_at: i64,
+    pub window_count: usize,
+    pub tab_count: usize,
+}
+
+/// A window brought back by a restore
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredWindow {
+    /// Content webview label of the window's active tab
+    pub window_label: String,
+    /// The OS window, `window-{uuid}`
+    pub parent_label: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredSession {
+    pub windows: Vec<RestoredWindow>,
+    /// Index into `windows` of the one to show
+    pub active_index: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    version: u32,
+    session: Session,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SavedSessionsFile {
+    version: u32,
+    sessions: BTreeMap<String, Session>,
+}
+
+fn parse_session(content: &str) -> Result<Session, String> {
+    let file: SessionFile = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    if file.version > SCHEMA_VERSION {
+        return Err(format!("Unsupported session schema version {}", file.version));
+    }
+    Ok(file.session)
+}
+
+fn parse_saved_sessions(content: &str) -> Result<BTreeMap<String, Session>, String> {
+    let file: SavedSessionsFile = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    if file.version > SCHEMA_VERSION {
+        return Err(format!("Unsupported session schema version {}", file.version));
+    }
+    Ok(file.sessions)
+}
+
+/// Owns `session.json` and `saved-sessions.json`. Unreadable files are set aside by
+/// `storage::load_with_backup` and treated as empty, so they never block startup.
+pub struct SessionManager {
+    session_path: PathBuf,
+    saved_path: PathBuf,
+    /// The previous run's session until it's restored or dismissed
+    pending: Mutex<Option<Session>>,
+    saved: Mutex<BTreeMap<String, Session>>,
+    /// Windows as last written, so unchanged snapshots aren't rewritten
+    last_written: Mutex<Option<Vec<SessionWindow>>>,
+    /// Set when the app quits; windows torn down after that weren't closed by the user
+    finished: Mutex<bool>,
+}
+
+impl SessionManager {
+    pub fn load(dir: &Path) -> Self {
+        let session_path = dir.join(SESSION_FILE);
+        let saved_path = dir.join(SAVED_SESSIONS_FILE);
+        let pending = storage::load_with_backup(&session_path, parse_session)
+            .filter(|session| !session.windows.is_empty());
+        let saved = storage::load_with_backup(&saved_path, parse_saved_sessions).unwrap_or_default();
+
+        Self {
+            session_path,
+            saved_path,
+            pending: Mutex::new(pending),
+            saved: Mutex::new(saved),
+            last_written: Mutex::new(None),
+            finished: Mutex::new(false),
+        }
+    }
+
+    fn write_session(&self, session: &Session) -> Result<(), String> {
+        let file = SessionFile {
+            version: SCHEMA_VERSION,
+            session: session.clone(),
+        };
+        let content = serde_json::to_vec_pretty(&file).map_err(|e| e.to_string())?;
+        storage::write_atomic(&self.session_path, &content).map_err(|e| e.to_string())
+    }
+
+    /// Apply a change to the saved sessions and persist it. Nothing is kept in memory if the write fails.
+    fn mutate_saved<T>(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, Session>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut saved = self.saved.lock().unwrap();
+        let mut updated = saved.clone();
+        let result = change(&mut updated)?;
+        let file = SavedSessionsFile {
+            version: SCHEMA_VERSION,
+            sessions: updated,
+        };
+        let content = serde_json::to_vec_pretty(&file).map_err(|e| e.to_string())?;
+        storage::write_atomic(&self.saved_path, &content).map_err(|e| e.to_string())?;
+        *saved = file.sessions;
+        Ok(result)
+    }
+
+    /// Write the open windows to `session.json` if they changed since the last write
+    pub fn save_current(&self, app: &tauri::AppHandle) {
+        if *self.finished.lock().unwrap() {
+            return;
+        }
+        let session = snapshot(app);
+        // Don't wipe the previous run's windows before the user has said whether they want them back
+        if session.windows.is_empty() && self.pending.lock().unwrap().is_some() {
+            return;
+        }
+        let mut last_written = self.last_written.lock().unwrap();
+        if last_written.as_ref() == Some(&session.windows) {
+            return;
+        }
+        if self.write_session(&session).is_ok() {
+            *last_written = Some(session.windows);
+        }
+    }
+}
+
+/// Where a window is and how it's shown
+fn window_geometry(window: &tauri::Window) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>, WindowState)> {
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    let state = if window.is_minimized().unwrap_or(false) {
+        WindowState::Minimized
+    } else if window.is_maximized().unwrap_or(false) {
+        WindowState::Maximized
+    } else if !window.is_visible().unwrap_or(true) {
+        WindowState::Hidden
+    } else {
+        WindowState::Normal
+    };
+    Some((position, size, state))
+}
+
+/// One browser window as it is now, or `None` if none of its tabs has loaded anything.
+/// Private windows are never saved.
+pub fn snapshot_window(app: &tauri::AppHandle, parent_label: &str) -> Option<SessionWindow> {
+    if tabs::is_private_window(app, parent_label) {
+        return None;
+    }
+    let registry = app.state::<TabRegistry>();
+    let monitor = app.state::<UrlMonitorRegistry>();
+    let (position, size, state) = window_geometry(&app.get_window(parent_label)?)?;
+
+    let active = registry.active_tab(parent_label);
+    let mut active_tab = 0;
+    let mut session_tabs = Vec::new();
+    for tab in registry.tabs(parent_label) {
+        // Tabs that never loaded anything have nothing to restore
+        let Some(page) = monitor.page_state(&tab).filter(|page| !page.url.is_empty()) else {
+            continue;
+        };
+        if active.as_ref() == Some(&tab) {
+            active_tab = session_tabs.len();
+        }
+        let (back, forward) = monitor.history(&tab);
+        session_tabs.push(SessionTab::new(page.url, page.title, back, forward));
+    }
+    if session_tabs.is_empty() {
+        return None;
+    }
+    Some(SessionWindow {
+        tabs: session_tabs,
+        active_tab,
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        state,
+        profile_id: Some(app.state::<ProfileManager>().window_profile(parent_label)),
+        container_id: app
+            .state::<ContainerRegistry>()
+            .window_container(parent_label)
+            .map(|container| container.id),
+    })
+}
+
+/// The open browser windows, oldest first
+pub fn snapshot(app: &tauri::AppHandle) -> Session {
+    let windows: Vec<SessionWindow> = app
+        .state::<TabRegistry>()
+        .windows()
+        .iter()
+        .filter_map(|parent_label| snapshot_window(app, parent_label))
+        .collect();
+
+    // The dock keeps every window but the one it's showing hidden
+    let active_window = windows
+        .iter()
+        .position(|window| matches!(window.state, WindowState::Normal | WindowState::Maximized))
+        .unwrap_or_default();
+    Session {
+        windows,
+        active_window,
+        saved_at: now_millis(),
+    }
+}
+
+/// Whether a saved position is still on one of the connected monitors
+fn on_screen(app: &tauri::AppHandle, x: i32, y: i32) -> bool {
+    app.available_monitors().unwrap_or_default().iter().any(|monitor| {
+        let origin = monitor.position();
+        let size = monitor.size();
+        x >= origin.x
+            && y >= origin.y
+            && i64::from(x) < i64::from(origin.x) + i64::from(size.width)
+            && i64::from(y) < i64::from(origin.y) + i64::from(size.height)
+    })
+}
+
+/// Open one saved window with all its tabs and their history. Returns its active tab's label and URL.
+pub fn restore_window(app: &tauri::AppHandle, saved: &SessionWindow) -> Result<RestoredWindow, String> {
+    let first = saved.tabs.first().ok_or_else(|| "Saved window has no tabs".to_string())?;
+    let window_id = uuid::Uuid::new_v4().to_string();
+    let window_label = format!("window-{}", window_id);
+    let content_label = format!("content-{}", window_id);
+    // Windows of a deleted profile or container come back in the active profile or without a container
+    let profiles = app.state::<ProfileManager>();
+    let profile_id = saved
+        .profile_id
+        .clone()
+        .filter(|id| profiles.exists(id))
+        .unwrap_or_else(|| profiles.active_id());
+    let container_id = saved
+        .container_id
+        .as_deref()
+        .filter(|id| containers::find(app, &profile_id, id).is_ok());
+    window::create_multi_webview_window(
+        app,
+        &window_label,
+        &format!("titlebar-{}", window_id),
+        &content_label,
+        first.start_url(),
+        WindowStorage {
+            private: false,
+            profile_id: Some(&profile_id),
+            container_id,
+        },
+    )?;
+    first.replay_history(app, &content_label);
+
+    let mut labels = vec![content_label];
+    for tab in &saved.tabs[1..] {
+        let label = tabs::open_tab(app, &window_label, tab.start_url(), None, false)?;
+        tab.replay_history(app, &label);
+        labels.push(label);
+    }
+    let active_tab = saved.active_tab.min(labels.len() - 1);
+    app.state::<TabRegistry>().activate(&labels[active_tab]);
+
+    if let Some(window) = app.get_window(&window_label) {
+        if on_screen(app, saved.x, saved.y) {
+            let _ = window.set_position(PhysicalPosition::new(saved.x, saved.y));
+        }
+        if saved.width > 0 && saved.height > 0 {
+            let _ = window.set_size(PhysicalSize::new(saved.width, saved.height));
+        }
+        let _ = match saved.state {
+            WindowState::Normal => Ok(()),
+            WindowState::Maximized => window.maximize(),
+            WindowState::Minimized => window.minimize(),
+            WindowState::Hidden => window.hide(),
+        };
+    }
+    tabs::tabs_changed(app, &window_label);
+
+    Ok(RestoredWindow {
+        window_label: labels.swap_remove(active_tab),
+        parent_label: window_label,
+        url: saved.tabs[active_tab].url.clone(),
+    })
+}
+
+/// Open every window of a session next to the ones already open
+fn restore(app: &tauri::AppHandle, session: &Session) -> Result<RestoredSession, String> {
+    let mut windows = Vec::new();
+    let mut active_index = 0;
+    for (index, saved) in session.windows.iter().enumerate() {
+        // One broken window shouldn't stop the rest
+        if let Ok(restored) = restore_window(app, saved) {
+            if index == session.active_window {
+                active_index = windows.len();
+            }
+            windows.push(restored);
+        }
+    }
+    if windows.is_empty() {
+        return Err("None of the session's windows could be opened".to_string());
+    }
+    Ok(RestoredSession { windows, active_index })
+}
+
+/// Keep `session.json` up to date while the app runs
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(AUTOSAVE_INTERVAL).await;
+            app.state::<SessionManager>().save_current(&app);
+        }
+    });
+}
+
+/// Write the session one last time before the app's windows are torn down
+pub fn save_on_exit(app: &tauri::AppHandle) {
+    let Some(manager) = app.try_state::<SessionManager>() else {
+        return;
+    };
+    manager.save_current(app);
+    *manager.finished.lock().unwrap() = true;
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Session names must be 1 to {} characters", MAX_NAME_LENGTH));
+    }
+    Ok(name.to_string())
+}
+
+/// The previous run's windows, if the user hasn't restored or dismissed them yet
+#[tauri::command]
+pub async fn get_session_restore_offer(
+    manager: tauri::State<'_, SessionManager>,
+) -> Result<Option<SessionSummary>, String> {
+    Ok(manager.pending.lock().unwrap().as_ref().map(|session| session.summary(None)))
+}
+
+#[tauri::command]
+pub async fn restore_last_session(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, SessionManager>,
+) -> Result<RestoredSession, String> {
+    let session = manager
+        .pending
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "There is no previous session to restore".to_string())?;
+    restore(&app, &session)
+}
+
+/// Start fresh; the previous run's windows are overwritten by the next autosave
+#[tauri::command]
+pub async fn dismiss_session_restore(manager: tauri::State<'_, SessionManager>) -> Result<(), String> {
+    manager.pending.lock().unwrap().take();
+    Ok(())
+}
+
+/// Save the open windows under a name, replacing any session with the same name
+#[tauri::command]
+pub async fn save_session(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, SessionManager>,
+    name: String,
+) -> Result<SessionSummary, String> {
+    let name = validate_name(&name)?;
+    let session = snapshot(&app);
+    if session.windows.is_empty() {
+        return Err("There are no open windows to save".to_string());
+    }
+    manager.mutate_saved(|sessions| {
+        let summary = session.summary(Some(&name));
+        sessions.insert(name, session);
+        Ok(summary)
+    })
+}
+
+/// Saved sessions, by name
+#[tauri::command]
+pub async fn list_sessions(manager: tauri::State<'_, SessionManager>) -> Result<Vec<SessionSummary>, String> {
+    Ok(manager
+        .saved
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, session)| session.summary(Some(name)))
+        .collect())
+}
+
+/// Open a saved session's windows next to the ones already open
+#[tauri::command]
+pub async fn restore_session(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, SessionManager>,
+    name: String,
+) -> Result<RestoredSession, String> {
+    let session = manager
+        .saved
+        .lock()
+        .unwrap()
+        .get(name.trim())
+        .cloned()
+        .ok_or_else(|| format!("No saved session named {}", name))?;
+    restore(&app, &session)
+}
+
+#[tauri::command]
+pub async fn delete_session(manager: tauri::State<'_, SessionManager>, name: String) -> Result<(), String> {
+    manager.mutate_saved(|sessions| {
+        sessions
+            .remove(name.trim())
+            .map(|_| ())
+            .ok_or_else(|| format!("No saved session named {}", name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("meikai-session-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn urls(prefix: &str, count: usize) -> Vec<String> {
+        (0..count).map(|index| format!("https://{}.example/{}", prefix, index)).collect()
+    }
+
+    fn tab(url: &str, back: &[&str], forward: &[&str]) -> SessionTab {
+        SessionTab::new(
+            url.to_string(),
+            String::new(),
+            back.iter().map(|url| url.to_string()).collect(),
+            forward.iter().map(|url| url.to_string()).collect(),
+        )
+    }
+
+    fn session_file(version: u32) -> String {
+        format!(
+            r#"{{"version": {}, "session": {{"windows": [{{"tabs": [{{"url": "https://a.example/", "title": "A"}}],
+                "activeTab": 0, "x": 10, "y": 20, "width": 800, "height": 600, "state": "normal"}}],
+                "activeWindow": 0, "savedAt": 1700000000000}}}}"#,
+            version
+        )
+    }
+
+    #[test]
+    fn old_session_files_load_with_defaults() {
+        let session = parse_session(&session_file(SCHEMA_VERSION)).unwrap();
+        let window = &session.windows[0];
+        assert_eq!(window.tabs[0].title, "A");
+        assert!(window.tabs[0].back.is_empty() && window.tabs[0].forward.is_empty());
+        assert_eq!((window.profile_id.as_ref(), window.container_id.as_ref()), (None, None));
+    }
+
+    #[test]
+    fn future_and_corrupt_session_files_are_rejected() {
+        let error = parse_session(&session_file(SCHEMA_VERSION + 1)).unwrap_err();
+        assert!(error.contains("Unsupported"), "{}", error);
+        assert!(parse_session("{\"version\": 1, \"session\": {\"windows\": [").is_err());
+        assert!(parse_session("null").is_err());
+        assert!(parse_saved_sessions(r#"{"version": 99, "sessions": {}}"#).is_err());
+    }
+
+    #[test]
+    fn unreadable_files_never_block_startup() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(SESSION_FILE), session_file(SCHEMA_VERSION + 1)).unwrap();
+        fs::write(dir.join(SAVED_SESSIONS_FILE), "\u{0}garbage").unwrap();
+
+        let manager = SessionManager::load(&dir);
+        assert!(manager.pending.lock().unwrap().is_none());
+        assert!(manager.saved.lock().unwrap().is_empty());
+        // Both were set aside rather than overwritten
+        let quarantined = fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(quarantined, 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn previous_session_is_offered_unless_empty() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(SESSION_FILE), session_file(SCHEMA_VERSION)).unwrap();
+        let summary = SessionManager::load(&dir).pending.lock().unwrap().as_ref().unwrap().summary(None);
+        assert_eq!((summary.window_count, summary.tab_count), (1, 1));
+
+        let empty = r#"{"version": 1, "session": {"windows": [], "activeWindow": 0, "savedAt": 0}}"#;
+        fs::write(dir.join(SESSION_FILE), empty).unwrap();
+        assert!(SessionManager::load(&dir).pending.lock().unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn history_is_trimmed_around_the_current_page() {
+        let url = "https://now.example/".to_string();
+        let tab = SessionTab::new(url, String::new(), urls("back", 15), urls("forward", 15));
+        assert_eq!(tab.back, urls("back", 15)[5..]);
+        assert_eq!(tab.forward, urls("forward", 10));
+    }
+
+    #[test]
+    fn tabs_without_history_just_load_their_page() {
+        let tab = tab("https://c.example/", &[], &[]);
+        assert_eq!(tab.start_url(), "https://c.example/");
+        assert_eq!(tab.history_replay(), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn history_is_replayed_oldest_first_then_walked_back() {
+        let tab = tab(
+            "https://c.example/",
+            &["https://a.example/", "https://b.example/"],
+            &["https://d.example/", "https://e.example/"],
+        );
+        assert_eq!(tab.start_url(), "https://a.example/");
+        let (entries, back_steps) = tab.history_replay();
+        assert_eq!(
+            entries,
+            ["https://b.example/", "https://c.example/", "https://d.example/", "https://e.example/"]
+        );
+        // From e back to c
+        assert_eq!(back_steps, 2);
+    }
+
+    #[test]
+    fn forward_only_history_starts_at_the_current_page() {
+        let tab = tab("https://a.example/", &[], &["https://b.example/"]);
+        assert_eq!(tab.start_url(), "https://a.example/");
+        assert_eq!(tab.history_replay(), (vec!["https://b.example/".to_string()], 1));
+    }
+}
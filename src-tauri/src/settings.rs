@@ -0,0 +1,612 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{Emitter, Manager, Url};
+
+use crate::containers::{self, Container, ContainerRule};
+use crate::hibernation;
+use crate::popup_blocker;
+use crate::popup_rules::{self, CompiledRules, PopupRule};
+use crate::search::{self, SearchEngine};
+use crate::storage::{self, FileState};
+
+/// File name of the settings file inside the app data directory
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Current on-disk schema version
+const SCHEMA_VERSION: u32 = 7;
+
+/// Bounds for the number of quick links on the home panel
+const QUICK_LINKS_RANGE: std::ops::RangeInclusive<u8> = 3..=6;
+
+/// Bounds for hibernation thresholds in minutes; 0 turns hibernation off
+const HIBERNATE_MINUTES_RANGE: std::ops::RangeInclusive<u32> = 0..=1440;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+/// User preferences. Also the payload of `settings-changed`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    pub theme: Theme,
+    /// Id of a built-in or custom engine
+    pub search_engine: String,
+    pub custom_search_engines: Vec<SearchEngine>,
+    /// Description URLs of discovered engines the user said no to
+    pub ignored_search_engine_offers: Vec<String>,
+    pub quick_links_limit: u8,
+    pub animations_enabled: bool,
+    pub has_seen_disclaimer: bool,
+    /// Replaces the suggestion provider's public host, e.g. a local mock server
+    pub suggest_base_url: Option<String>,
+    /// Checked before the built-in popup rules
+    pub popup_rules: Vec<PopupRule>,
+    /// Sites that may open popups without a click, e.g. `example.com` (subdomains included)
+    pub popup_allowlist: Vec<String>,
+    /// Tabs of a hidden or minimized window lose their webview after this many minutes (0 = never)
+    pub hibernate_hidden_windows_after_minutes: u32,
+    /// Tabs in the background of a shown window lose their webview after this many minutes (0 = never)
+    pub hibernate_background_tabs_after_minutes: u32,
+    /// Sites whose tabs are never hibernated, e.g. chat apps (subdomains included)
+    pub never_hibernate: Vec<String>,
+    /// Separate cookie and storage partitions windows can open in
+    pub containers: Vec<Container>,
+    /// Sites that always open in a container (subdomains included)
+    pub container_rules: Vec<ContainerRule>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Light,
+            search_engine: search::DEFAULT_ENGINE_ID.to_string(),
+            custom_search_engines: Vec::new(),
+            ignored_search_engine_offers: Vec::new(),
+            quick_links_limit: 6,
+            animations_enabled: true,
+            has_seen_disclaimer: false,
+            suggest_base_url: None,
+            popup_rules: Vec::new(),
+            popup_allowlist: Vec::new(),
+            hibernate_hidden_windows_after_minutes: 30,
+            hibernate_background_tabs_after_minutes: 60,
+            never_hibernate: Vec::new(),
+            containers: Vec::new(),
+            container_rules: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Checks serde can't express
+    fn validate(&self) -> Result<(), SettingsError> {
+        if !QUICK_LINKS_RANGE.contains(&self.quick_links_limit) {
+            return Err(invalid_value("quickLinksLimit", &Value::from(self.quick_links_limit)));
+        }
+        search::validate_custom(&self.custom_search_engines).map_err(SettingsError::InvalidValue)?;
+        if !self.engines().iter().any(|engine| engine.id == self.search_engine) {
+            return Err(invalid_value("searchEngine", &Value::from(self.search_engine.as_str())));
+        }
+        popup_rules::validate_rules(&self.popup_rules).map_err(SettingsError::InvalidValue)?;
+        popup_blocker::validate_allowlist(&self.popup_allowlist).map_err(SettingsError::InvalidValue)?;
+        for (key, minutes) in [
+            ("hibernateHiddenWindowsAfterMinutes", self.hibernate_hidden_windows_after_minutes),
+            ("hibernateBackgroundTabsAfterMinutes", self.hibernate_background_tabs_after_minutes),
+        ] {
+            if !HIBERNATE_MINUTES_RANGE.contains(&minutes) {
+                return Err(invalid_value(key, &Value::from(minutes)));
+            }
+        }
+        hibernation::validate_never_hibernate(&self.never_hibernate).map_err(SettingsError::InvalidValue)?;
+        containers::validate(&self.containers, &self.container_rules).map_err(SettingsError::InvalidValue)?;
+        if let Some(base_url) = &self.suggest_base_url {
+            let valid = Url::parse(base_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !valid {
+                return Err(invalid_value("suggestBaseUrl", &Value::from(base_url.as_str())));
+            }
+        }
+        Ok(())
+    }
+
+    /// Built-in engines followed by the user's own
+    pub fn engines(&self) -> Vec<SearchEngine> {
+        let mut engines = search::builtin_engines();
+        engines.extend(self.custom_search_engines.iter().cloned());
+        engines
+    }
+
+    /// The engine used for plain searches
+    pub fn default_engine(&self) -> SearchEngine {
+        let mut engines = self.engines();
+        let index = engines
+            .iter()
+            .position(|engine| engine.id == self.search_engine)
+            .unwrap_or_default();
+        engines.swap_remove(index)
+    }
+
+    /// Keys and values as the frontend sees them
+    fn to_map(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
+
+    /// Copy with one key replaced, rejecting unknown keys and invalid values
+    fn with_value(&self, key: &str, value: Value) -> Result<Settings, SettingsError> {
+        let mut map = self.to_map();
+        if !map.contains_key(key) {
+            return Err(SettingsError::UnknownKey(key.to_string()));
+        }
+        map.insert(key.to_string(), value.clone());
+
+        let updated: Settings =
+            serde_json::from_value(Value::Object(map)).map_err(|_| invalid_value(key, &value))?;
+        updated.validate()?;
+        Ok(updated)
+    }
+}
+
+/// Structured settings failure returned to the frontend
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum SettingsError {
+    /// No setting with this key exists
+    UnknownKey(String),
+    /// The value has the wrong type or is out of range
+    InvalidValue(String),
+    /// No custom search engine with this id
+    UnknownEngine(String),
+    /// No container with this id
+    UnknownContainer(String),
+    /// The settings file couldn't be written
+    Storage(String),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::UnknownKey(key) => write!(f, "Unknown setting: {}", key),
+            SettingsError::InvalidValue(message) => write!(f, "{}", message),
+            SettingsError::UnknownEngine(id) => write!(f, "No custom search engine with id {}", id),
+            SettingsError::UnknownContainer(id) => write!(f, "No container with id {}", id),
+            SettingsError::Storage(message) => write!(f, "Failed to save settings: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// Human-readable description of what a key accepts
+fn expected(key: &str) -> String {
+    match key {
+        "theme" => "\"light\" or \"dark\"".to_string(),
+        "searchEngine" => "the id of a built-in or custom search engine".to_string(),
+        "customSearchEngines" => "a list of search engines".to_string(),
+        "ignoredSearchEngineOffers" => "a list of URLs".to_string(),
+        "quickLinksLimit" => format!(
+            "a whole number from {} to {}",
+            QUICK_LINKS_RANGE.start(),
+            QUICK_LINKS_RANGE.end()
+        ),
+        "suggestBaseUrl" => "null or an http(s) URL".to_string(),
+        "popupRules" => "a list of popup rules".to_string(),
+        "containers" => "a list of containers".to_string(),
+        "containerRules" => "a list of container rules for known containers".to_string(),
+        "popupAllowlist" | "neverHibernate" => "a list of host names".to_string(),
+        "hibernateHiddenWindowsAfterMinutes" | "hibernateBackgroundTabsAfterMinutes" => format!(
+            "a whole number of minutes from {} (never) to {}",
+            HIBERNATE_MINUTES_RANGE.start(),
+            HIBERNATE_MINUTES_RANGE.end()
+        ),
+        _ => "true or false".to_string(),
+    }
+}
+
+/// e.g. `quickLinksLimit must be a whole number from 3 to 6 (got "six")`
+fn invalid_value(key: &str, value: &Value) -> SettingsError {
+    SettingsError::InvalidValue(format!("{} must be {} (got {})", key, expected(key), value))
+}
+
+/// Upgrade a raw settings object from `version` to the current schema, one step at a time
+fn migrate(map: &mut Map<String, Value>, version: u32) {
+    for from in version..SCHEMA_VERSION {
+        match from {
+            // Version 0: the unversioned file written by useSettings.ts. Same keys,
+            // but nothing was validated - bad values are dropped when the file is read.
+            0 => {}
+            // Version 1: `searchEngine` could only name a built-in engine
+            1 => {
+                map.entry("customSearchEngines").or_insert_with(|| Value::Array(Vec::new()));
+            }
+            // Version 2: engines were never discovered from sites
+            2 => {
+                map.entry("ignoredSearchEngineOffers").or_insert_with(|| Value::Array(Vec::new()));
+            }
+            // Version 3: popups were routed by built-in rules only
+            3 => {
+                map.entry("popupRules").or_insert_with(|| Value::Array(Vec::new()));
+            }
+            // Version 4: every popup was allowed
+            4 => {
+                map.entry("popupAllowlist").or_insert_with(|| Value::Array(Vec::new()));
+            }
+            // Version 5: webviews lived as long as their tab
+            5 => {
+                let defaults = Settings::default();
+                map.entry("hibernateHiddenWindowsAfterMinutes")
+                    .or_insert_with(|| Value::from(defaults.hibernate_hidden_windows_after_minutes));
+                map.entry("hibernateBackgroundTabsAfterMinutes")
+                    .or_insert_with(|| Value::from(defaults.hibernate_background_tabs_after_minutes));
+                map.entry("neverHibernate").or_insert_with(|| Value::Array(Vec::new()));
+            }
+            // Version 6: every window shared the profile's cookies and storage
+            6 => {
+                map.entry("containers").or_insert_with(|| Value::Array(Vec::new()));
+                map.entry("containerRules").or_insert_with(|| Value::Array(Vec::new()));
+            }
+            _ => unreachable!("no migration from settings version {}", from),
+        }
+    }
+    map.insert("version".to_string(), Value::from(SCHEMA_VERSION));
+}
+
+/// Build settings from a (migrated) object key by key, so one bad value only resets that key.
+/// The flag is true when anything had to be dropped or defaulted.
+fn from_map_lenient(mut map: Map<String, Value>) -> (Settings, bool) {
+    map.remove("version");
+    let mut settings = Settings::default();
+    let mut repaired = false;
+
+    // Custom engines and containers go first: `searchEngine` and `containerRules` refer to them
+    let mut known: Vec<String> = settings.to_map().keys().cloned().collect();
+    known.sort_by_key(|key| !matches!(key.as_str(), "customSearchEngines" | "containers"));
+    for key in &known {
+        match map.remove(key) {
+            Some(value) => match settings.with_value(key, value) {
+                Ok(updated) => settings = updated,
+                Err(_) => repaired = true,
+            },
+            None => repaired = true,
+        }
+    }
+    // Whatever is left isn't a setting we know about
+    (settings, repaired || !map.is_empty())
+}
+
+/// Parse any version of the settings file. A newer file keeps the keys this build knows.
+fn parse_and_migrate(content: &str) -> Result<(Settings, FileState), String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let Value::Object(mut map) = value else {
+        return Err("Settings file is not an object".to_string());
+    };
+
+    let version = map.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    if version > SCHEMA_VERSION {
+        return Ok((from_map_lenient(map).0, FileState::Newer));
+    }
+    migrate(&mut map, version);
+
+    let (settings, repaired) = from_map_lenient(map);
+    let state = if repaired || version < SCHEMA_VERSION { FileState::Outdated } else { FileState::Current };
+    Ok((settings, state))
+}
+
+/// Owns `settings.json`; every change is persisted before it's reported back
+pub struct SettingsStore {
+    path: Mutex<PathBuf>,
+    settings: Mutex<Settings>,
+    /// `settings.popup_rules`, compiled whenever they're loaded or changed
+    popup_rules: Mutex<Arc<CompiledRules>>,
+    /// The file is from a newer build: changes only last until the app quits
+    read_only: Mutex<bool>,
+}
+
+impl SettingsStore {
+    /// Load settings from `dir`, recovering from the backup if the file is corrupt
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(SETTINGS_FILE);
+        let (settings, state) = storage::load_with_backup(&path, parse_and_migrate)
+            .unwrap_or_else(|| (Settings::default(), FileState::Outdated));
+
+        let store = Self {
+            path: Mutex::new(path),
+            popup_rules: Mutex::new(Arc::new(CompiledRules::new(&settings.popup_rules))),
+            settings: Mutex::new(settings),
+            read_only: Mutex::new(state == FileState::Newer),
+        };
+
+        // Persist defaults, migrations and repairs in the current schema
+        if state == FileState::Outdated {
+            let _ = store.save(&store.snapshot());
+        }
+        store
+    }
+
+    fn save(&self, settings: &Settings) -> Result<(), SettingsError> {
+        if *self.read_only.lock().unwrap() {
+            return Ok(());
+        }
+        let mut map = settings.to_map();
+        map.insert("version".to_string(), Value::from(SCHEMA_VERSION));
+        let content = serde_json::to_vec_pretty(&map)
+            .map_err(|e| SettingsError::Storage(e.to_string()))?;
+        storage::write_atomic(&self.path.lock().unwrap(), &content).map_err(|e| SettingsError::Storage(e.to_string()))
+    }
+
+    /// Switch to the settings in `dir`, e.g. another profile's, and tell every window
+    pub fn reload(&self, app: &tauri::AppHandle, dir: &Path) {
+        let loaded = Self::load(dir);
+        let mut settings = self.settings.lock().unwrap();
+        *self.path.lock().unwrap() = loaded.path.into_inner().unwrap();
+        *settings = loaded.settings.into_inner().unwrap();
+        *self.popup_rules.lock().unwrap() = loaded.popup_rules.into_inner().unwrap();
+        *self.read_only.lock().unwrap() = loaded.read_only.into_inner().unwrap();
+        emit_changed(app, &settings);
+    }
+
+    pub fn snapshot(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// The user's popup rules, ready to route popups
+    pub fn popup_rules(&self) -> Arc<CompiledRules> {
+        self.popup_rules.lock().unwrap().clone()
+    }
+
+    /// Replace the settings and persist them. Nothing is kept in memory if the
+    /// result is invalid or the write fails.
+    fn replace(
+        &self,
+        change: impl FnOnce(&Settings) -> Result<Settings, SettingsError>,
+    ) -> Result<Settings, SettingsError> {
+        let mut settings = self.settings.lock().unwrap();
+        let updated = change(&settings)?;
+        updated.validate()?;
+        self.save(&updated)?;
+        if updated.popup_rules != settings.popup_rules {
+            *self.popup_rules.lock().unwrap() = Arc::new(CompiledRules::new(&updated.popup_rules));
+        }
+        *settings = updated.clone();
+        Ok(updated)
+    }
+
+    /// Apply a change and tell every window if anything actually changed
+    pub fn update(
+        &self,
+        app: &tauri::AppHandle,
+        change: impl FnOnce(&Settings) -> Result<Settings, SettingsError>,
+    ) -> Result<Settings, SettingsError> {
+        let before = self.snapshot();
+        let settings = self.replace(change)?;
+        if settings != before {
+            emit_changed(app, &settings);
+        }
+        Ok(settings)
+    }
+}
+
+/// Current settings, or the defaults before the store is set up
+pub fn current(app: &tauri::AppHandle) -> Settings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.snapshot())
+        .unwrap_or_default()
+}
+
+/// The user's compiled popup rules, or none before the store is set up
+pub fn popup_rules(app: &tauri::AppHandle) -> Arc<CompiledRules> {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.popup_rules())
+        .unwrap_or_default()
+}
+
+/// Tell every window (panel, dock, content title bars) about the new settings
+fn emit_changed(app: &tauri::AppHandle, settings: &Settings) {
+    let _ = app.emit("settings-changed", settings);
+}
+
+#[tauri::command]
+pub async fn get_settings(store: tauri::State<'_, SettingsStore>) -> Result<Settings, SettingsError> {
+    Ok(store.snapshot())
+}
+
+/// Change a single setting by its camelCase key, e.g. `set_setting("theme", "dark")`
+#[tauri::command]
+pub async fn set_setting(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    key: String,
+    value: Value,
+) -> Result<Settings, SettingsError> {
+    store.update(&app, |settings| settings.with_value(&key, value))
+}
+
+#[tauri::command]
+pub async fn reset_settings(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+) -> Result<Settings, SettingsError> {
+    let settings = store.replace(|_| Ok(Settings::default()))?;
+    emit_changed(&app, &settings);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(json: &str) -> Map<String, Value> {
+        match serde_json::from_str(json).unwrap() {
+            Value::Object(map) => map,
+            other => panic!("not an object: {}", other),
+        }
+    }
+
+    fn current_file(settings: &Settings) -> String {
+        let mut map = settings.to_map();
+        map.insert("version".to_string(), Value::from(SCHEMA_VERSION));
+        Value::Object(map).to_string()
+    }
+
+    #[test]
+    fn bad_value_only_resets_its_key() {
+        let mut map = Settings::default().to_map();
+        map.insert("quickLinksLimit".to_string(), Value::from("six"));
+        map.insert("theme".to_string(), Value::from("dark"));
+
+        let (settings, repaired) = from_map_lenient(map);
+        assert!(repaired);
+        assert_eq!(settings.quick_links_limit, Settings::default().quick_links_limit);
+        assert_eq!(settings.theme, Theme::Dark);
+    }
+
+    #[test]
+    fn clean_file_needs_no_rewrite() {
+        let settings = Settings { theme: Theme::Dark, quick_links_limit: 4, ..Settings::default() };
+        let (loaded, state) = parse_and_migrate(&current_file(&settings)).unwrap();
+        assert_eq!(loaded, settings);
+        assert_eq!(state, FileState::Current);
+    }
+
+    #[test]
+    fn unknown_keys_are_dropped() {
+        let mut map = Settings::default().to_map();
+        map.insert("legacyFlag".to_string(), Value::Bool(true));
+        let (settings, repaired) = from_map_lenient(map);
+        assert!(repaired);
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn custom_engine_can_be_the_default() {
+        let mut map = Settings::default().to_map();
+        // `searchEngine` comes before `customSearchEngines` in the file, but depends on it
+        map.insert("searchEngine".to_string(), Value::from("mine"));
+        map.insert(
+            "customSearchEngines".to_string(),
+            serde_json::json!([{
+                "id": "mine",
+                "name": "Mine",
+                "keyword": "m",
+                "searchUrl": "https://search.example.com/?q={searchTerms}",
+                "suggestUrl": null,
+                "iconUrl": null
+            }]),
+        );
+        let (settings, repaired) = from_map_lenient(map);
+        assert!(!repaired);
+        assert_eq!(settings.default_engine().name, "Mine");
+    }
+
+    #[test]
+    fn with_value_rejects_unknown_keys_and_bad_values() {
+        let settings = Settings::default();
+        assert!(matches!(
+            settings.with_value("fontSize", Value::from(12)),
+            Err(SettingsError::UnknownKey(key)) if key == "fontSize"
+        ));
+
+        let error = settings.with_value("quickLinksLimit", Value::from("six")).unwrap_err();
+        assert_eq!(error.to_string(), "quickLinksLimit must be a whole number from 3 to 6 (got \"six\")");
+        assert!(settings.with_value("quickLinksLimit", Value::from(7)).is_err());
+        assert!(settings.with_value("theme", Value::from("blue")).is_err());
+        assert!(settings.with_value("searchEngine", Value::from("nope")).is_err());
+        assert!(settings.with_value("suggestBaseUrl", Value::from("ftp://mock")).is_err());
+        assert!(settings.with_value("hibernateHiddenWindowsAfterMinutes", Value::from(1441)).is_err());
+        assert!(settings.with_value("popupAllowlist", serde_json::json!(["https://example.com"])).is_err());
+
+        let updated = settings.with_value("suggestBaseUrl", Value::from("http://127.0.0.1:8080")).unwrap();
+        assert_eq!(updated.suggest_base_url.as_deref(), Some("http://127.0.0.1:8080"));
+        assert_eq!(settings.with_value("quickLinksLimit", Value::from(3)).unwrap().quick_links_limit, 3);
+    }
+
+    #[test]
+    fn migrate_fills_every_later_key() {
+        let mut map = object(r#"{"theme": "dark"}"#);
+        migrate(&mut map, 0);
+        assert_eq!(map["version"], Value::from(SCHEMA_VERSION));
+        for key in [
+            "customSearchEngines",
+            "ignoredSearchEngineOffers",
+            "popupRules",
+            "popupAllowlist",
+            "hibernateHiddenWindowsAfterMinutes",
+            "hibernateBackgroundTabsAfterMinutes",
+            "neverHibernate",
+            "containers",
+            "containerRules",
+        ] {
+            assert!(map.contains_key(key), "{} missing after migration", key);
+        }
+
+        // Later steps leave values that are already there alone
+        let mut map = object(r#"{"version": 5, "hibernateHiddenWindowsAfterMinutes": 5}"#);
+        migrate(&mut map, 5);
+        assert_eq!(map["hibernateHiddenWindowsAfterMinutes"], Value::from(5));
+        assert!(!map.contains_key("popupRules"));
+    }
+
+    #[test]
+    fn version_0_file_from_use_settings_is_upgraded() {
+        let content = r#"{
+            "theme": "dark",
+            "searchEngine": "duckduckgo",
+            "quickLinksLimit": "six",
+            "animationsEnabled": false,
+            "hasSeenDisclaimer": true
+        }"#;
+        let (settings, state) = parse_and_migrate(content).unwrap();
+        assert_eq!(state, FileState::Outdated);
+        assert_eq!(
+            settings,
+            Settings {
+                theme: Theme::Dark,
+                search_engine: "duckduckgo".to_string(),
+                animations_enabled: false,
+                has_seen_disclaimer: true,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn future_files_load_what_this_build_understands() {
+        let future = format!(r#"{{"version": {}, "theme": "dark", "newKey": [1, 2]}}"#, SCHEMA_VERSION + 1);
+        let (settings, state) = parse_and_migrate(&future).unwrap();
+        assert_eq!(state, FileState::Newer);
+        assert_eq!(settings, Settings { theme: Theme::Dark, ..Settings::default() });
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(parse_and_migrate("[]").is_err());
+        assert!(parse_and_migrate("{").is_err());
+    }
+
+    #[test]
+    fn future_files_are_never_overwritten() {
+        let dir = std::env::temp_dir().join(format!("meikai-settings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SETTINGS_FILE);
+        let future = format!(r#"{{"version": {}, "theme": "dark", "newKey": true}}"#, SCHEMA_VERSION + 1);
+        std::fs::write(&path, &future).unwrap();
+
+        let store = SettingsStore::load(&dir);
+        assert_eq!(store.snapshot().theme, Theme::Dark);
+        store
+            .replace(|settings| Ok(Settings { theme: Theme::Light, ..settings.clone() }))
+            .unwrap();
+        assert_eq!(store.snapshot().theme, Theme::Light);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), future);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::tabs::{self, TabRegistry};
+
+/// Gap between panes, in logical pixels
+const DIVIDER_SIZE: f64 = 4.0;
+
+/// No pane can be squeezed below this share of the window
+const MIN_PANE_SHARE: f64 = 0.1;
+
+/// Most tabs a split can show, so each pane can get at least `MIN_PANE_SHARE`
+const MAX_PANES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SplitOrientation {
+    /// Side by side
+    Columns,
+    /// Top and bottom
+    Rows,
+}
+
+/// Position and size of a pane in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaneBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Two or more tabs of one window shown at once. Part of `tabs-changed`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitLayout {
+    pub orientation: SplitOrientation,
+    /// Tab labels, left to right or top to bottom
+    pub panes: Vec<String>,
+    /// Share of the window each pane gets; adds up to 1, so layouts keep their
+    /// proportions when the window is resized or maximized
+    pub shares: Vec<f64>,
+}
+
+impl SplitLayout {
+    /// Panes of equal size
+    pub fn new(orientation: SplitOrientation, panes: Vec<String>) -> Self {
+        let share = 1.0 / panes.len() as f64;
+        Self {
+            orientation,
+            shares: vec![share; panes.len()],
+            panes,
+        }
+    }
+
+    pub fn contains(&self, tab_label: &str) -> bool {
+        self.panes.iter().any(|pane| pane == tab_label)
+    }
+
+    /// Where each pane goes in a window of this size, dividers left between them
+    pub fn pane_bounds(&self, width: f64, height: f64) -> Vec<(String, PaneBounds)> {
+        let length = match self.orientation {
+            SplitOrientation::Columns => width,
+            SplitOrientation::Rows => height,
+        };
+        let dividers = DIVIDER_SIZE * (self.panes.len() - 1) as f64;
+        let available = (length - dividers).max(0.0);
+
+        let mut offset = 0.0;
+        self.panes
+            .iter()
+            .zip(&self.shares)
+            .map(|(pane, share)| {
+                let size = (available * share).round();
+                let bounds = match self.orientation {
+                    SplitOrientation::Columns => PaneBounds { x: offset, y: 0.0, width: size, height },
+                    SplitOrientation::Rows => PaneBounds { x: 0.0, y: offset, width, height: size },
+                };
+                offset += size + DIVIDER_SIZE;
+                (pane.clone(), bounds)
+            })
+            .collect()
+    }
+
+    /// Put divider `divider` (between pane `divider` and the next one) at `position`,
+    /// a share of the window from its left or top edge
+    pub fn move_divider(&mut self, divider: usize, position: f64) -> Result<(), String> {
+        if divider + 1 >= self.panes.len() {
+            return Err(format!("Split view has no divider {}", divider));
+        }
+        if !position.is_finite() {
+            return Err("Divider position must be a number between 0 and 1".to_string());
+        }
+        let start: f64 = self.shares[..divider].iter().sum();
+        let end = start + self.shares[divider] + self.shares[divider + 1];
+        let (min, max) = (start + MIN_PANE_SHARE, end - MIN_PANE_SHARE);
+        if min > max {
+            return Err("These panes are already as small as they can be".to_string());
+        }
+        let position = position.clamp(min, max);
+
+        self.shares[divider] = position - start;
+        self.shares[divider + 1] = end - position;
+        Ok(())
+    }
+
+    /// Exchange two panes; each keeps the size of the slot it moves into
+    pub fn swap(&mut self, first: usize, second: usize) -> Result<(), String> {
+        if first >= self.panes.len() || second >= self.panes.len() {
+            return Err(format!("Split view has {} panes", self.panes.len()));
+        }
+        self.panes.swap(first, second);
+        Ok(())
+    }
+
+    /// Drop a closed tab, giving its space to a neighbour.
+    /// Returns false when fewer than two panes are left and the split should end.
+    pub fn remove_pane(&mut self, tab_label: &str) -> bool {
+        if let Some(index) = self.panes.iter().position(|pane| pane == tab_label) {
+            self.panes.remove(index);
+            let share = self.shares.remove(index);
+            let neighbour = index.min(self.shares.len().saturating_sub(1));
+            if let Some(next) = self.shares.get_mut(neighbour) {
+                *next += share;
+            }
+        }
+        self.panes.len() >= 2
+    }
+}
+
+/// Show two or more tabs of a window at once: the given ones, or the active tab and the one after it.
+/// Calling it again while split only changes the orientation.
+#[tauri::command]
+pub async fn enter_split_view(
+    app: tauri::AppHandle,
+    window_label: String,
+    orientation: SplitOrientation,
+    tab_labels: Option<Vec<String>>,
+) -> Result<(), String> {
+    let parent_label = tabs::parent_window_label(&app, &window_label);
+    let registry = app.state::<TabRegistry>();
+    let window_tabs = registry.tabs(&parent_label);
+    let active = registry
+        .active_tab(&parent_label)
+        .ok_or_else(|| format!("Browser window not found: {}", parent_label))?;
+
+    let split = match (tab_labels, registry.split(&parent_label)) {
+        (None, Some(mut split)) => {
+            split.orientation = orientation;
+            split
+        }
+        (Some(panes), _) => {
+            if let Some(stranger) = panes.iter().find(|pane| !window_tabs.contains(pane)) {
+                return Err(format!("Tab {} isn't in this window", stranger));
+            }
+            let unique: HashSet<&String> = panes.iter().collect();
+            if panes.len() < 2 || unique.len() != panes.len() {
+                return Err("Split view needs at least two different tabs".to_string());
+            }
+            if panes.len() > MAX_PANES {
+                return Err(format!("Split view can show at most {} tabs", MAX_PANES));
+            }
+            SplitLayout::new(orientation, panes)
+        }
+        (None, None) => {
+            let index = window_tabs.iter().position(|tab| *tab == active).unwrap_or_default();
+            let partner = window_tabs
+                .get(index + 1)
+                .or_else(|| index.checked_sub(1).and_then(|previous| window_tabs.get(previous)))
+                .ok_or_else(|| "Split view needs at least two tabs".to_string())?;
+            SplitLayout::new(orientation, vec![active.clone(), partner.clone()])
+        }
+    };
+
+    if !split.contains(&active) {
+        registry.activate(&split.panes[0]);
+    }
+    registry.set_split(&parent_label, Some(split));
+    tabs::tabs_changed(&app, &parent_label);
+    Ok(())
+}
+
+/// Back to showing only the active tab
+#[tauri::command]
+pub async fn leave_split_view(app: tauri::AppHandle, window_label: String) -> Result<(), String> {
+    let parent_label = tabs::parent_window_label(&app, &window_label);
+    app.state::<TabRegistry>().set_split(&parent_label, None);
+    tabs::tabs_changed(&app, &parent_label);
+    Ok(())
+}
+
+/// Apply a change to a window's split layout and re-layout it
+fn update_split(
+    app: &tauri::AppHandle,
+    window_label: &str,
+    change: impl FnOnce(&mut SplitLayout) -> Result<(), String>,
+) -> Result<(), String> {
+    let parent_label = tabs::parent_window_label(app, window_label);
+    let registry = app.state::<TabRegistry>();
+    let mut split = registry
+        .split(&parent_label)
+        .ok_or_else(|| "This window isn't in split view".to_string())?;
+    change(&mut split)?;
+    registry.set_split(&parent_label, Some(split));
+    tabs::tabs_changed(app, &parent_label);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn swap_split_panes(
+    app: tauri::AppHandle,
+    window_label: String,
+    first: usize,
+    second: usize,
+) -> Result<(), String> {
+    update_split(&app, &window_label, |split| split.swap(first, second))
+}
+
+/// Move a divider; `position` is a share of the window (0 to 1) from its left or top edge
+#[tauri::command]
+pub async fn resize_split_divider(
+    app: tauri::AppHandle,
+    window_label: String,
+    divider: usize,
+    position: f64,
+) -> Result<(), String> {
+    update_split(&app, &window_label, |split| split.move_divider(divider, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(orientation: SplitOrientation, count: usize) -> SplitLayout {
+        SplitLayout::new(orientation, (0..count).map(|index| format!("content-{}", index)).collect())
+    }
+
+    fn assert_shares(split: &SplitLayout, expected: &[f64]) {
+        assert_eq!(split.shares.len(), expected.len());
+        for (share, expected) in split.shares.iter().zip(expected) {
+            assert!((share - expected).abs() < 1e-9, "{:?} != {:?}", split.shares, expected);
+        }
+    }
+
+    #[test]
+    fn columns_leave_room_for_dividers() {
+        let bounds = layout(SplitOrientation::Columns, 2).pane_bounds(1004.0, 700.0);
+        assert_eq!(bounds[0].0, "content-0");
+        assert_eq!(bounds[0].1, PaneBounds { x: 0.0, y: 0.0, width: 500.0, height: 700.0 });
+        assert_eq!(bounds[1].1, PaneBounds { x: 504.0, y: 0.0, width: 500.0, height: 700.0 });
+    }
+
+    #[test]
+    fn rows_follow_their_shares() {
+        let mut split = layout(SplitOrientation::Rows, 3);
+        split.shares = vec![0.5, 0.25, 0.25];
+        let bounds: Vec<PaneBounds> = split.pane_bounds(800.0, 408.0).into_iter().map(|(_, b)| b).collect();
+        assert_eq!(
+            bounds,
+            [
+                PaneBounds { x: 0.0, y: 0.0, width: 800.0, height: 200.0 },
+                PaneBounds { x: 0.0, y: 204.0, width: 800.0, height: 100.0 },
+                PaneBounds { x: 0.0, y: 308.0, width: 800.0, height: 100.0 },
+            ]
+        );
+        // A window smaller than its dividers doesn't produce negative sizes
+        assert!(split.pane_bounds(800.0, 4.0).iter().all(|(_, b)| b.height == 0.0));
+    }
+
+    #[test]
+    fn dividers_move_between_their_neighbours_only() {
+        let mut split = layout(SplitOrientation::Columns, 3);
+        split.move_divider(0, 0.5).unwrap();
+        assert_shares(&split, &[0.5, 1.0 / 6.0, 1.0 / 3.0]);
+
+        // Clamped so neither neighbour drops below the minimum
+        split.move_divider(0, 0.9).unwrap();
+        assert_shares(&split, &[2.0 / 3.0 - MIN_PANE_SHARE, MIN_PANE_SHARE, 1.0 / 3.0]);
+        split.move_divider(1, 0.0).unwrap();
+        assert_shares(&split, &[2.0 / 3.0 - MIN_PANE_SHARE, MIN_PANE_SHARE, 1.0 / 3.0]);
+
+        assert!(split.move_divider(2, 0.5).is_err());
+        assert!(split.move_divider(0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn panes_at_the_minimum_refuse_to_move() {
+        let mut split = layout(SplitOrientation::Columns, 3);
+        split.shares = vec![0.05, 0.05, 0.9];
+        assert!(split.move_divider(0, 0.5).is_err());
+        assert_shares(&split, &[0.05, 0.05, 0.9]);
+
+        // The largest split still leaves every divider usable
+        let mut split = layout(SplitOrientation::Rows, MAX_PANES);
+        for divider in 0..MAX_PANES - 1 {
+            let _ = split.move_divider(divider, 0.0);
+        }
+        assert!((split.shares.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn swapped_panes_take_over_the_slot_size() {
+        let mut split = layout(SplitOrientation::Columns, 3);
+        split.shares = vec![0.5, 0.2, 0.3];
+        split.swap(0, 2).unwrap();
+        assert_eq!(split.panes, ["content-2", "content-1", "content-0"]);
+        assert_shares(&split, &[0.5, 0.2, 0.3]);
+        assert!(split.swap(0, 3).is_err());
+    }
+
+    #[test]
+    fn removed_panes_give_their_space_to_a_neighbour() {
+        let mut split = layout(SplitOrientation::Columns, 4);
+        split.shares = vec![0.1, 0.2, 0.3, 0.4];
+        assert!(split.remove_pane("content-1"));
+        assert_eq!(split.panes, ["content-0", "content-2", "content-3"]);
+        assert_shares(&split, &[0.1, 0.5, 0.4]);
+
+        // The last pane gives its space to the one before it
+        assert!(split.remove_pane("content-3"));
+        assert_shares(&split, &[0.1, 0.9]);
+
+        assert!(split.remove_pane("content-9"));
+        assert!(!split.remove_pane("content-0"));
+        assert_eq!(split.panes, ["content-2"]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::clock::now_millis;
+
+/// `<path><suffix>`, e.g. `bookmarks.json` -> `bookmarks.json.bak`
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.file_name().unwrap_or_default().to_owned();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Location of the previous good copy of a data file
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+/// How a parsed data file relates to the schema this build writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    /// Already in the current schema
+    Current,
+    /// Migrated or repaired while loading; rewrite it in the current schema
+    Outdated,
+    /// Written by a newer build. Use what this build understands, but never save over it:
+    /// going back to an older build mustn't wipe the user's data.
+    Newer,
+}
+
+/// Write a data file atomically: write a temp file, flush it to disk, keep the
+/// current file as `.bak`, then rename the temp file into place.
+/// A crash at any point leaves either the old or the new file intact.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = with_suffix(path, ".tmp");
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+
+    if path.exists() {
+        fs::copy(path, backup_path(path))?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/// Move an unreadable file aside so the next save can't overwrite the user's data
+fn quarantine(path: &Path) -> io::Result<PathBuf> {
+    let target = with_suffix(path, &format!(".corrupt-{}", now_millis()));
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Load a data file, falling back to its backup when the primary can't be parsed.
+/// Returns `None` if neither exists or both are unreadable; corrupt files are
+/// renamed to `.corrupt-<timestamp>` rather than being left to be overwritten.
+pub fn load_with_backup<T>(path: &Path, parse: impl Fn(&str) -> Result<T, String>) -> Option<T> {
+    for candidate in [path.to_path_buf(), backup_path(path)] {
+        let Ok(content) = fs::read_to_string(&candidate) else {
+            continue;
+        };
+        match parse(&content) {
+            Ok(value) => return Some(value),
+            Err(_) => {
+                let _ = quarantine(&candidate);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory under the system temp dir
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("meikai-storage-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn parse_number(content: &str) -> Result<u32, String> {
+        content.trim().parse().map_err(|_| format!("not a number: {}", content))
+    }
+
+    fn corrupt_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains(".corrupt-"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn write_atomic_creates_parents_and_keeps_a_backup() {
+        let dir = temp_dir();
+        let path = dir.join("nested").join("data.json");
+
+        write_atomic(&path, b"1").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1");
+        assert!(!backup_path(&path).exists());
+
+        write_atomic(&path, b"2").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "1");
+        assert!(!with_suffix(&path, ".tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_reads_the_primary_file() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        write_atomic(&path, b"1").unwrap();
+        write_atomic(&path, b"2").unwrap();
+        assert_eq!(load_with_backup(&path, parse_number), Some(2));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_file_is_quarantined_and_the_backup_used() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        write_atomic(&path, b"1").unwrap();
+        write_atomic(&path, b"garbage").unwrap();
+
+        assert_eq!(load_with_backup(&path, parse_number), Some(1));
+        assert!(!path.exists());
+        let quarantined = corrupt_files(&dir);
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0].starts_with("data.json.corrupt-"));
+        assert_eq!(fs::read_to_string(dir.join(&quarantined[0])).unwrap(), "garbage");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn nothing_loads_when_both_copies_are_bad_or_missing() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        assert_eq!(load_with_backup(&path, parse_number), None);
+
+        fs::write(&path, "bad").unwrap();
+        fs::write(backup_path(&path), "worse").unwrap();
+        assert_eq!(load_with_backup(&path, parse_number), None);
+        assert_eq!(corrupt_files(&dir).len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,573 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::{Emitter, LogicalPosition, LogicalSize, Manager};
+
+use crate::containers::{Container, ContainerRegistry};
+use crate::hibernation::{self, HibernationManager};
+use crate::profiles::ProfileManager;
+use crate::search::SuggestionService;
+use crate::split_view::{PaneBounds, SplitLayout};
+use crate::url_monitor::{PageState, UrlMonitorRegistry};
+use crate::window;
+
+/// Payload of `tabs-changed`: the tabs of one browser window, in order
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabStrip {
+    /// The OS window, `window-{uuid}`
+    pub parent_label: String,
+    /// Page state of each tab; `windowLabel` is the tab's content webview label
+    pub tabs: Vec<PageState>,
+    pub active_label: String,
+    /// Tabs shown side by side or stacked, if any
+    pub split: Option<SplitLayout>,
+    /// Tabs whose webview was destroyed to save memory until they're shown again
+    pub hibernated_labels: Vec<String>,
+    /// A private window, whose tabs are kept out of history and sessions
+    pub private: bool,
+    /// Profile the window belongs to
+    pub profile_id: String,
+    /// Container the window's tabs keep cookies and storage in, if any
+    pub container: Option<Container>,
+}
+
+/// Tabs of one OS window
+struct WindowTabs {
+    tabs: Vec<String>,
+    active: String,
+    split: Option<SplitLayout>,
+    opened_at: Instant,
+}
+
+/// A tab taken out of its window
+pub struct RemovedTab {
+    pub parent_label: String,
+    /// False once the window has no tabs
+    pub window_has_tabs: bool,
+}
+
+/// Which content webviews (tabs) each browser window hosts, and which one is showing
+#[derive(Default)]
+pub struct TabRegistry {
+    windows: Mutex<HashMap<String, WindowTabs>>,
+}
+
+impl TabRegistry {
+    /// Add a tab at `index` (the end if `None`), creating the window's entry on first use
+    pub fn add(&self, parent_label: &str, tab_label: &str, index: Option<usize>, activate: bool) {
+        let mut windows = self.windows.lock().unwrap();
+        let entry = windows.entry(parent_label.to_string()).or_insert_with(|| WindowTabs {
+            tabs: Vec::new(),
+            active: tab_label.to_string(),
+            split: None,
+            opened_at: Instant::now(),
+        });
+        let index = index.unwrap_or(entry.tabs.len()).min(entry.tabs.len());
+        entry.tabs.insert(index, tab_label.to_string());
+        if activate {
+            entry.active = tab_label.to_string();
+        }
+    }
+
+    /// The browser window a tab lives in
+    pub fn parent_of(&self, tab_label: &str) -> Option<String> {
+        self.windows
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, window)| window.tabs.iter().any(|tab| tab == tab_label))
+            .map(|(parent, _)| parent.clone())
+    }
+
+    /// The browser window for a tab or window label, see `parent_window_label`
+    pub fn window_of(&self, label: &str) -> String {
+        if let Some(parent) = self.parent_of(label) {
+            return parent;
+        }
+        match label.strip_prefix("content-") {
+            Some(id) => format!("window-{}", id),
+            None => label.to_string(),
+        }
+    }
+
+    pub fn active_tab(&self, parent_label: &str) -> Option<String> {
+        self.windows.lock().unwrap().get(parent_label).map(|window| window.active.clone())
+    }
+
+    /// A window's tabs, in order
+    pub fn tabs(&self, parent_label: &str) -> Vec<String> {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(parent_label)
+            .map(|window| window.tabs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Position of a tab within its window
+    pub fn index_of(&self, tab_label: &str) -> Option<usize> {
+        self.windows
+            .lock()
+            .unwrap()
+            .values()
+            .find_map(|window| window.tabs.iter().position(|tab| tab == tab_label))
+    }
+
+    /// Take a tab out of its window. An active tab hands over to its right neighbour, or its left one at the end.
+    pub fn remove(&self, tab_label: &str) -> Option<RemovedTab> {
+        let mut windows = self.windows.lock().unwrap();
+        let (parent_label, window) = windows
+            .iter_mut()
+            .find(|(_, window)| window.tabs.iter().any(|tab| tab == tab_label))?;
+        let parent_label = parent_label.clone();
+
+        let index = window.tabs.iter().position(|tab| tab == tab_label)?;
+        window.tabs.remove(index);
+        if window.split.as_mut().is_some_and(|split| !split.remove_pane(tab_label)) {
+            window.split = None;
+        }
+        if window.active == tab_label {
+            if let Some(next) = window.tabs.get(index).or_else(|| window.tabs.last()) {
+                window.active = next.clone();
+            }
+        }
+
+        let window_has_tabs = !window.tabs.is_empty();
+        if !window_has_tabs {
+            windows.remove(&parent_label);
+        }
+        Some(RemovedTab {
+            parent_label,
+            window_has_tabs,
+        })
+    }
+
+    /// Make a tab the visible one. Switching to a tab outside a split view ends it. Returns its window.
+    pub fn activate(&self, tab_label: &str) -> Option<String> {
+        let mut windows = self.windows.lock().unwrap();
+        let (parent_label, window) = windows
+            .iter_mut()
+            .find(|(_, window)| window.tabs.iter().any(|tab| tab == tab_label))?;
+        window.active = tab_label.to_string();
+        if window.split.as_ref().is_some_and(|split| !split.contains(tab_label)) {
+            window.split = None;
+        }
+        Some(parent_label.clone())
+    }
+
+    /// Labels of every OS window with tabs, oldest first
+    pub fn windows(&self) -> Vec<String> {
+        let windows = self.windows.lock().unwrap();
+        let mut labels: Vec<(&String, Instant)> =
+            windows.iter().map(|(label, window)| (label, window.opened_at)).collect();
+        labels.sort_by_key(|(_, opened_at)| *opened_at);
+        labels.into_iter().map(|(label, _)| label.clone()).collect()
+    }
+
+    pub fn split(&self, parent_label: &str) -> Option<SplitLayout> {
+        self.windows.lock().unwrap().get(parent_label).and_then(|window| window.split.clone())
+    }
+
+    pub fn set_split(&self, parent_label: &str, split: Option<SplitLayout>) {
+        if let Some(window) = self.windows.lock().unwrap().get_mut(parent_label) {
+            window.split = split;
+        }
+    }
+
+    /// Move a tab to another position in its window. Returns its window.
+    pub fn reorder(&self, tab_label: &str, index: usize) -> Option<String> {
+        let mut windows = self.windows.lock().unwrap();
+        let (parent_label, window) = windows
+            .iter_mut()
+            .find(|(_, window)| window.tabs.iter().any(|tab| tab == tab_label))?;
+        let from = window.tabs.iter().position(|tab| tab == tab_label)?;
+        let tab = window.tabs.remove(from);
+        let index = index.min(window.tabs.len());
+        window.tabs.insert(index, tab);
+        Some(parent_label.clone())
+    }
+
+    /// Forget a closed window, returning the tabs it had
+    pub fn remove_window(&self, parent_label: &str) -> Vec<String> {
+        self.windows
+            .lock()
+            .unwrap()
+            .remove(parent_label)
+            .map(|window| window.tabs)
+            .unwrap_or_default()
+    }
+}
+
+/// The browser window hosting a tab. Accepts a window label too, and falls back to the
+/// `content-{uuid}` / `window-{uuid}` naming for a window's first tab.
+pub fn parent_window_label(app: &tauri::AppHandle, label: &str) -> String {
+    app.state::<TabRegistry>().window_of(label)
+}
+
+/// Show a window's active tab filling the window, or the panes of its split view, and hide the rest
+pub fn layout_window(app: &tauri::AppHandle, parent_label: &str) {
+    let Some(window) = app.get_window(parent_label) else {
+        return;
+    };
+    let (Ok(size), Ok(scale)) = (window.inner_size(), window.scale_factor()) else {
+        return;
+    };
+    let size = size.to_logical::<f64>(scale);
+
+    let registry = app.state::<TabRegistry>();
+    let active = registry.active_tab(parent_label);
+    let shown = window.is_visible().unwrap_or(true) && !window.is_minimized().unwrap_or(false);
+    let visible: Vec<(String, PaneBounds)> = match registry.split(parent_label) {
+        Some(split) => split.pane_bounds(size.width, size.height),
+        None => active
+            .iter()
+            .map(|tab| (tab.clone(), PaneBounds { x: 0.0, y: 0.0, width: size.width, height: size.height }))
+            .collect(),
+    };
+
+    for tab in registry.tabs(parent_label) {
+        let bounds = visible.iter().find(|(pane, _)| *pane == tab).map(|(_, bounds)| bounds);
+        // Hibernated tabs come back as soon as they're on screen again
+        if bounds.is_some() && shown && app.get_webview(&tab).is_none() {
+            hibernation::wake(app, parent_label, &tab);
+        }
+        let Some(webview) = app.get_webview(&tab) else {
+            continue;
+        };
+        match bounds {
+            Some(bounds) => {
+                let _ = webview.set_bounds(tauri::Rect {
+                    position: LogicalPosition::new(bounds.x, bounds.y).into(),
+                    size: LogicalSize::new(bounds.width, bounds.height).into(),
+                });
+                let _ = webview.show();
+            }
+            None => {
+                let _ = webview.hide();
+            }
+        }
+    }
+    if let Some(webview) = active.and_then(|tab| app.get_webview(&tab)) {
+        let _ = webview.set_focus();
+    }
+}
+
+/// Whether a browser window was opened as a private window
+pub fn is_private_window(app: &tauri::AppHandle, parent_label: &str) -> bool {
+    let monitor = app.state::<UrlMonitorRegistry>();
+    app.state::<TabRegistry>()
+        .tabs(parent_label)
+        .iter()
+        .any(|tab| monitor.is_private(tab))
+}
+
+/// Tabs a window has on screen: its split view panes, or else its active tab
+pub fn visible_tabs(app: &tauri::AppHandle, parent_label: &str) -> Vec<String> {
+    let registry = app.state::<TabRegistry>();
+    match registry.split(parent_label) {
+        Some(split) => split.panes,
+        None => registry.active_tab(parent_label).into_iter().collect(),
+    }
+}
+
+fn tab_strip(app: &tauri::AppHandle, parent_label: &str) -> Option<TabStrip> {
+    let registry = app.state::<TabRegistry>();
+    let active_label = registry.active_tab(parent_label)?;
+    let monitor = app.state::<UrlMonitorRegistry>();
+    let labels = registry.tabs(parent_label);
+    let tabs = labels
+        .iter()
+        .map(|tab| monitor.page_state(tab).unwrap_or_else(|| PageState::new(tab)))
+        .collect();
+    Some(TabStrip {
+        parent_label: parent_label.to_string(),
+        tabs,
+        active_label,
+        split: registry.split(parent_label),
+        hibernated_labels: app.state::<HibernationManager>().hibernated_of(&labels),
+        private: is_private_window(app, parent_label),
+        profile_id: app.state::<ProfileManager>().window_profile(parent_label),
+        container: app.state::<ContainerRegistry>().window_container(parent_label),
+    })
+}
+
+/// Re-layout a window after its tabs changed and tell the frontend
+pub fn tabs_changed(app: &tauri::AppHandle, parent_label: &str) {
+    layout_window(app, parent_label);
+    if let Some(strip) = tab_strip(app, parent_label) {
+        let _ = app.emit("tabs-changed", strip);
+    }
+}
+
+/// Open a tab in an existing browser window, after `after` if given, and return its label
+pub fn open_tab(
+    app: &tauri::AppHandle,
+    parent_label: &str,
+    url: &str,
+    after: Option<&str>,
+    activate: bool,
+) -> Result<String, String> {
+    let window = app
+        .get_window(parent_label)
+        .ok_or_else(|| format!("Browser window not found: {}", parent_label))?;
+    let tab_label = format!("content-{}", uuid::Uuid::new_v4());
+
+    let size = window.inner_size().map_err(|e| e.to_string())?;
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let size = size.to_logical::<f64>(scale);
+    // Tabs opened in a private window are private too
+    let private = is_private_window(app, parent_label);
+    let builder = window::content_webview_builder(app, &tab_label, url, private)?;
+    window
+        .add_child(builder, LogicalPosition::new(0.0, 0.0), LogicalSize::new(size.width, size.height))
+        .map_err(|e| e.to_string())?;
+
+    let registry = app.state::<TabRegistry>();
+    let index = after.and_then(|tab| registry.index_of(tab)).map(|index| index + 1);
+    registry.add(parent_label, &tab_label, index, activate);
+    tabs_changed(app, parent_label);
+    Ok(tab_label)
+}
+
+/// Close one tab, and its window with it if it was the last one
+pub fn close(app: &tauri::AppHandle, tab_label: &str) -> Result<(), String> {
+    let registry = app.state::<TabRegistry>();
+    let parent_label = registry
+        .parent_of(tab_label)
+        .ok_or_else(|| format!("Tab not found: {}", tab_label))?;
+
+    if registry.tabs(&parent_label).len() <= 1 {
+        // The window's Destroyed handler cleans up its last tab
+        if let Some(window) = app.get_window(&parent_label) {
+            window.close().map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+
+    registry.remove(tab_label);
+    if let Some(webview) = app.get_webview(tab_label) {
+        webview.close().map_err(|e| e.to_string())?;
+    }
+    app.state::<UrlMonitorRegistry>().unsubscribe(tab_label);
+    app.state::<HibernationManager>().forget(tab_label);
+    app.state::<SuggestionService>().forget(tab_label);
+    tabs_changed(app, &parent_label);
+    Ok(())
+}
+
+/// Tabs of the window a tab (or window) label belongs to
+#[tauri::command]
+pub async fn get_tabs(app: tauri::AppHandle, window_label: String) -> Result<TabStrip, String> {
+    let parent_label = parent_window_label(&app, &window_label);
+    tab_strip(&app, &parent_label).ok_or_else(|| format!("Browser window not found: {}", parent_label))
+}
+
+/// Open a new tab in the same window as `window_label`
+#[tauri::command]
+pub async fn create_tab(
+    app: tauri::AppHandle,
+    window_label: String,
+    url: String,
+    activate: Option<bool>,
+) -> Result<String, String> {
+    let parent_label = parent_window_label(&app, &window_label);
+    open_tab(&app, &parent_label, &url, None, activate.unwrap_or(true))
+}
+
+#[tauri::command]
+pub async fn close_tab(app: tauri::AppHandle, tab_label: String) -> Result<(), String> {
+    close(&app, &tab_label)
+}
+
+#[tauri::command]
+pub async fn activate_tab(app: tauri::AppHandle, tab_label: String) -> Result<(), String> {
+    let parent_label = app
+        .state::<TabRegistry>()
+        .activate(&tab_label)
+        .ok_or_else(|| format!("Tab not found: {}", tab_label))?;
+    tabs_changed(&app, &parent_label);
+    Ok(())
+}
+
+/// Move a tab to `index` in its window, or into the window of `target_window_label`
+#[tauri::command]
+pub async fn move_tab(
+    app: tauri::AppHandle,
+    tab_label: String,
+    index: usize,
+    target_window_label: Option<String>,
+) -> Result<(), String> {
+    let registry = app.state::<TabRegistry>();
+    let source = registry
+        .parent_of(&tab_label)
+        .ok_or_else(|| format!("Tab not found: {}", tab_label))?;
+    let target = target_window_label
+        .map(|label| parent_window_label(&app, &label))
+        .unwrap_or_else(|| source.clone());
+
+    if target == source {
+        registry.reorder(&tab_label, index);
+        tabs_changed(&app, &source);
+        return Ok(());
+    }
+
+    let window = app
+        .get_window(&target)
+        .ok_or_else(|| format!("Browser window not found: {}", target))?;
+    // A webview's data store is fixed when it's created
+    if is_private_window(&app, &source) != is_private_window(&app, &target) {
+        return Err("Tabs can't move between private and regular windows".to_string());
+    }
+    let profiles = app.state::<ProfileManager>();
+    if profiles.window_profile(&source) != profiles.window_profile(&target) {
+        return Err("Tabs can't move between windows of different profiles".to_string());
+    }
+    let containers = app.state::<ContainerRegistry>();
+    if containers.window_container(&source) != containers.window_container(&target) {
+        return Err("Tabs can't move between windows of different containers".to_string());
+    }
+    // A hibernated tab has no webview to move; it's rebuilt in the target window when shown
+    match app.get_webview(&tab_label) {
+        Some(webview) => webview.reparent(&window).map_err(|e| e.to_string())?,
+        None if app.state::<HibernationManager>().is_hibernated(&tab_label) => {}
+        None => return Err(format!("Tab not found: {}", tab_label)),
+    }
+
+    let removed = registry.remove(&tab_label);
+    registry.add(&target, &tab_label, Some(index), true);
+    tabs_changed(&app, &target);
+
+    if let Some(removed) = removed {
+        if removed.window_has_tabs {
+            tabs_changed(&app, &removed.parent_label);
+        } else if let Some(source_window) = app.get_window(&removed.parent_label) {
+            source_window.close().map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_view::SplitOrientation;
+
+    /// A window with tabs `a`, `b`, `c`, ... in order, the first one active
+    fn registry(tabs: &[&str]) -> TabRegistry {
+        let registry = TabRegistry::default();
+        for (index, tab) in tabs.iter().enumerate() {
+            registry.add("window-1", tab, None, index == 0);
+        }
+        registry
+    }
+
+    #[test]
+    fn tabs_are_added_in_place() {
+        let registry = registry(&["a", "b"]);
+        registry.add("window-1", "c", Some(1), false);
+        registry.add("window-1", "d", Some(99), true);
+        assert_eq!(registry.tabs("window-1"), ["a", "c", "b", "d"]);
+        assert_eq!(registry.active_tab("window-1").as_deref(), Some("d"));
+        assert_eq!(registry.parent_of("c").as_deref(), Some("window-1"));
+        assert_eq!(registry.index_of("b"), Some(2));
+        assert_eq!(registry.parent_of("missing"), None);
+        assert_eq!(registry.window_of("c"), "window-1");
+        assert_eq!(registry.window_of("content-2"), "window-2");
+        assert_eq!(registry.window_of("window-3"), "window-3");
+    }
+
+    #[test]
+    fn first_tab_of_a_window_is_active() {
+        let registry = TabRegistry::default();
+        registry.add("window-1", "a", None, false);
+        assert_eq!(registry.active_tab("window-1").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn closing_the_active_tab_hands_over_to_the_right() {
+        let registry = registry(&["a", "b", "c"]);
+        registry.activate("b");
+        let removed = registry.remove("b").unwrap();
+        assert_eq!(removed.parent_label, "window-1");
+        assert!(removed.window_has_tabs);
+        assert_eq!(registry.active_tab("window-1").as_deref(), Some("c"));
+
+        // The last tab hands over to its left neighbour
+        registry.remove("c");
+        assert_eq!(registry.active_tab("window-1").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn closing_a_background_tab_keeps_the_active_one() {
+        let registry = registry(&["a", "b", "c"]);
+        registry.activate("c");
+        registry.remove("a");
+        assert_eq!(registry.tabs("window-1"), ["b", "c"]);
+        assert_eq!(registry.active_tab("window-1").as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn closing_the_last_tab_forgets_the_window() {
+        let registry = registry(&["a"]);
+        let removed = registry.remove("a").unwrap();
+        assert!(!removed.window_has_tabs);
+        assert!(registry.windows().is_empty());
+        assert!(registry.remove("a").is_none());
+    }
+
+    #[test]
+    fn closing_a_pane_shrinks_or_ends_the_split() {
+        let registry = registry(&["a", "b", "c", "d"]);
+        let panes = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        registry.set_split("window-1", Some(SplitLayout::new(SplitOrientation::Columns, panes)));
+
+        registry.remove("d");
+        assert_eq!(registry.split("window-1").unwrap().panes, ["a", "b", "c"]);
+        registry.remove("b");
+        assert_eq!(registry.split("window-1").unwrap().panes, ["a", "c"]);
+        registry.remove("c");
+        assert_eq!(registry.split("window-1"), None);
+    }
+
+    #[test]
+    fn activating_a_tab_outside_the_split_ends_it() {
+        let registry = registry(&["a", "b", "c"]);
+        let panes = vec!["a".to_string(), "b".to_string()];
+        registry.set_split("window-1", Some(SplitLayout::new(SplitOrientation::Rows, panes)));
+
+        assert_eq!(registry.activate("b").as_deref(), Some("window-1"));
+        assert!(registry.split("window-1").is_some());
+        registry.activate("c");
+        assert_eq!(registry.split("window-1"), None);
+        assert_eq!(registry.activate("missing"), None);
+    }
+
+    #[test]
+    fn reorder_moves_a_tab_and_clamps_the_index() {
+        let registry = registry(&["a", "b", "c", "d"]);
+        assert_eq!(registry.reorder("a", 2).as_deref(), Some("window-1"));
+        assert_eq!(registry.tabs("window-1"), ["b", "c", "a", "d"]);
+        registry.reorder("d", 0);
+        assert_eq!(registry.tabs("window-1"), ["d", "b", "c", "a"]);
+        registry.reorder("b", 99);
+        assert_eq!(registry.tabs("window-1"), ["d", "c", "a", "b"]);
+        assert_eq!(registry.reorder("missing", 0), None);
+    }
+
+    #[test]
+    fn windows_are_listed_oldest_first() {
+        let registry = TabRegistry::default();
+        registry.add("window-2", "a", None, true);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        registry.add("window-1", "b", None, true);
+        registry.add("window-2", "c", None, true);
+        assert_eq!(registry.windows(), ["window-2", "window-1"]);
+
+        assert_eq!(registry.remove_window("window-2"), ["a", "c"]);
+        assert_eq!(registry.windows(), ["window-1"]);
+        assert!(registry.remove_window("window-2").is_empty());
+    }
+}
@@ -1,57 +1,643 @@
-use tauri::{Emitter, Manager};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::webview::{PageLoadEvent, WebviewBuilder};
+use tauri::{Emitter, Manager, Url};
+
+use crate::{bookmarks, history, profiles, search};
+
+/// Loading state of a content webview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoadingState {
+    Started,
+    Finished,
+}
+
+/// Snapshot of a content webview, emitted as `page-state`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageState {
+    pub window_label: String,
+    pub url: String,
+    pub title: String,
+    pub favicon_url: Option<String>,
+    pub loading: LoadingState,
+    pub is_secure: bool,
+    pub can_go_back: bool,
+    pub can_go_forward: bool,
+    /// In a private window: kept out of history and sessions
+    pub private: bool,
+}
+
+impl PageState {
+    /// Nothing loaded yet
+    pub fn new(window_label: &str) -> Self {
+        Self {
+            window_label: window_label.to_string(),
+            url: String::new(),
+            title: String::new(),
+            favicon_url: None,
+            loading: LoadingState::Started,
+            is_secure: false,
+            can_go_back: false,
+            can_go_forward: false,
+            private: false,
+        }
+    }
+}
+
+/// Per-webview monitoring state
+struct MonitoredPage {
+    state: PageState,
+    /// Committed URLs in visit order, used to answer back/forward availability
+    history: Vec<String>,
+    history_index: usize,
+    /// History as it was before the last commit, in case the page reports that it was a step back or forward
+    before_commit: Option<(Vec<String>, usize)>,
+    /// The webview was destroyed but will be rebuilt under the same label
+    suspended: bool,
+    /// History being rebuilt in a reopened tab
+    replay: Option<HistoryReplay>,
+}
+
+/// Back/forward history of a closed tab being loaded again, one page at a time
+struct HistoryReplay {
+    /// Pages still to visit after the current one
+    pending: VecDeque<String>,
+    /// Pages expected after each step back to the one the tab was on
+    back_to: VecDeque<String>,
+    /// Page the last step should have loaded; anything else means the user took over
+    awaiting: String,
+}
+
+impl MonitoredPage {
+    fn new(label: &str, private: bool) -> Self {
+        Self {
+            state: PageState { private, ..PageState::new(label) },
+            history: Vec::new(),
+            history_index: 0,
+            before_commit: None,
+            suspended: false,
+            replay: None,
+        }
+    }
+
+    /// Move to a new URL. Only a `traversal` (a step back or forward the page reported) moves
+    /// within the history; anything else is a new entry, even a link to the previous page.
+    /// Returns false if the URL didn't change.
+    fn commit_url(&mut self, url: &str, traversal: bool) -> bool {
+        if self.state.url == url {
+            return false;
+        }
+
+        self.before_commit = Some((self.history.clone(), self.history_index));
+        self.move_in_history(url, traversal);
+
+        let parsed = Url::parse(url).ok();
+        self.state.url = url.to_string();
+        // The new document hasn't reported a title or icon yet
+        self.state.title = String::new();
+        self.state.is_secure = parsed.as_ref().is_some_and(|u| u.scheme() == "https");
+        self.state.favicon_url = parsed.as_ref().and_then(default_favicon_url);
+        self.update_history_state();
+        true
+    }
+
+    fn move_in_history(&mut self, url: &str, traversal: bool) {
+        if traversal && self.history_index > 0 && self.history[self.history_index - 1] == url {
+            self.history_index -= 1;
+        } else if traversal && self.history.get(self.history_index + 1).is_some_and(|next| next == url) {
+            self.history_index += 1;
+        } else {
+            if !self.history.is_empty() {
+                self.history.truncate(self.history_index + 1);
+                self.history_index += 1;
+            }
+            self.history.push(url.to_string());
+        }
+    }
+
+    /// Back/forward availability from our own record of the history
+    fn update_history_state(&mut self) {
+        self.state.can_go_back = self.history_index > 0;
+        self.state.can_go_forward = self.history_index + 1 < self.history.len();
+    }
+
+    /// Apply what the page script found in the document
+    fn apply_info(&mut self, info: PageInfo) -> PageChange {
+        let mut change = PageChange::default();
+        if info.url != self.state.url {
+            // A late report from the previous document, unless it's a step back or forward within the page
+            if !info.traversal {
+                return change;
+            }
+            change.url = self.commit_url(&info.url, true);
+        } else if info.traversal {
+            // Load events can't tell a step back or forward from a new visit, so the
+            // page was committed as a new entry; redo it as a move within the history
+            if let Some((history, index)) = self.before_commit.take() {
+                self.history = history;
+                self.history_index = index;
+                self.move_in_history(&info.url, true);
+                self.update_history_state();
+            }
+        }
+
+        let favicon_url = info
+            .favicon_url
+            .and_then(|icon| Url::parse(&icon).ok())
+            .filter(|icon| matches!(icon.scheme(), "http" | "https" | "data"))
+            .map(|icon| icon.to_string())
+            .or_else(|| Url::parse(&self.state.url).ok().as_ref().and_then(default_favicon_url));
+        let (can_go_back, can_go_forward) = (
+            info.can_go_back.unwrap_or(self.state.can_go_back),
+            info.can_go_forward.unwrap_or(self.state.can_go_forward),
+        );
+        change.details = self.state.favicon_url != favicon_url
+            || self.state.can_go_back != can_go_back
+            || self.state.can_go_forward != can_go_forward;
+        self.state.favicon_url = favicon_url;
+        self.state.can_go_back = can_go_back;
+        self.state.can_go_forward = can_go_forward;
+        change
+    }
+}
+
+/// What the page script reports about the document in a content webview
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    url: String,
+    /// Largest `<link rel="icon">`, resolved by the page
+    favicon_url: Option<String>,
+    /// The document was reached by going back or forward
+    #[serde(default)]
+    traversal: bool,
+    /// The webview's own history, where the engine has the Navigation API
+    can_go_back: Option<bool>,
+    can_go_forward: Option<bool>,
+    /// OpenSearch descriptions the `<head>` links to, resolved by the page
+    #[serde(default)]
+    search_descriptions: Vec<String>,
+}
+
+/// What changed in a page update
+#[derive(Default)]
+struct PageChange {
+    url: bool,
+    title: bool,
+    loading: bool,
+    /// Icon or back/forward availability
+    details: bool,
+}
+
+impl PageChange {
+    fn any(&self) -> bool {
+        self.url || self.title || self.loading || self.details
+    }
+}
+
+/// Injected into the main frame of content webviews. Load events only carry the URL, so the
+/// page reports its icon, its OpenSearch links, whether it was reached by going back or
+/// forward and, where the engine has the Navigation API, the webview's real back/forward
+/// availability. `capabilities/content.json` limits pages to this report and the scroll one.
+fn page_info_script() -> &'static str {
+    r#"(function () {
+  if (window !== window.top) return;
+  function favicon() {
+    var best = null, bestSize = -1;
+    document.querySelectorAll("link[rel][href]").forEach(function (link) {
+      var rel = link.rel.toLowerCase().split(/\s+/);
+      if (rel.indexOf("icon") < 0) return;
+      var size = 0;
+      String(link.getAttribute("sizes") || "").split(/\s+/).forEach(function (s) {
+        var n = s === "any" ? 1024 : parseInt(s, 10);
+        if (n > size) size = n;
+      });
+      if (size > bestSize) { best = link.href; bestSize = size; }
+    });
+    return best;
+  }
+  function searchDescriptions() {
+    var links = [];
+    if (!document.head) return links;
+    document.head.querySelectorAll("link[rel][type][href]").forEach(function (link) {
+      var rel = link.rel.toLowerCase().split(/\s+/);
+      if (rel.indexOf("search") >= 0 && link.type.toLowerCase() === "application/opensearchdescription+xml") {
+        links.push(link.href);
+      }
+    });
+    return links;
+  }
+  function report(traversal) {
+    var internals = window.__TAURI_INTERNALS__;
+    if (!internals) return;
+    var nav = window.navigation;
+    internals.invoke("report_page_info", { info: {
+      url: location.href,
+      faviconUrl: favicon(),
+      traversal: traversal,
+      canGoBack: nav ? nav.canGoBack : null,
+      canGoForward: nav ? nav.canGoForward : null,
+      searchDescriptions: searchDescriptions()
+    } }).catch(function () {});
+  }
+  function loaded() {
+    var entry = performance.getEntriesByType("navigation")[0];
+    report(!!entry && entry.type === "back_forward");
+  }
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", loaded, { once: true });
+  } else {
+    loaded();
+  }
+  window.addEventListener("pageshow", function (event) { if (event.persisted) report(true); });
+  window.addEventListener("popstate", function () { report(true); });
+  window.addEventListener("load", function () { report(false); }, { once: true });
+})();"#
+}
+
+/// Sites without an explicit icon link serve `/favicon.ico` by convention
+fn default_favicon_url(url: &Url) -> Option<String> {
+    match url.scheme() {
+        "http" | "https" => url.join("/favicon.ico").ok().map(|u| u.to_string()),
+        _ => None,
+    }
+}
+
+/// Tracks which content webviews have URL monitoring attached.
+/// Guarantees a single subscription per webview label.
+#[derive(Default)]
+pub struct UrlMonitorRegistry {
+    pages: Mutex<HashMap<String, MonitoredPage>>,
+}
+
+impl UrlMonitorRegistry {
+    /// Claim the subscription for a label. Returns false if it's already monitored.
+    /// A suspended page is picked up again with its state and history.
+    pub(crate) fn subscribe(&self, label: &str, private: bool) -> bool {
+        let mut pages = self.pages.lock().unwrap();
+        if let Some(page) = pages.get_mut(label) {
+            return std::mem::replace(&mut page.suspended, false);
+        }
+        pages.insert(label.to_string(), MonitoredPage::new(label, private));
+        true
+    }
+
+    /// Whether a webview belongs to a private window
+    pub fn is_private(&self, label: &str) -> bool {
+        self.pages.lock().unwrap().get(label).is_some_and(|page| page.state.private)
+    }
+
+    /// Keep a page's state while its webview is gone, for the webview that replaces it
+    pub fn suspend(&self, label: &str) {
+        if let Some(page) = self.pages.lock().unwrap().get_mut(label) {
+            page.suspended = true;
+        }
+    }
+
+    /// Back and forward history of a page, oldest first, without the current entry
+    pub fn history(&self, label: &str) -> (Vec<String>, Vec<String>) {
+        let pages = self.pages.lock().unwrap();
+        let Some(page) = pages.get(label).filter(|page| !page.history.is_empty()) else {
+            return (Vec::new(), Vec::new());
+        };
+        (
+            page.history[..page.history_index].to_vec(),
+            page.history[page.history_index + 1..].to_vec(),
+        )
+    }
+
+    /// Once `start` (the page the tab was opened with) has loaded, load `entries` one after another
+    /// and then go `back_steps` back, so a reopened tab gets its history back.
+    /// Replayed pages aren't recorded as visits.
+    pub fn replay_history(&self, label: &str, start: &str, entries: Vec<String>, back_steps: usize) {
+        if entries.is_empty() {
+            return;
+        }
+        let mut visited = vec![start.to_string()];
+        visited.extend(entries.iter().cloned());
+        let back_to = visited.iter().rev().skip(1).take(back_steps).cloned().collect();
+        if let Some(page) = self.pages.lock().unwrap().get_mut(label) {
+            page.replay = Some(HistoryReplay {
+                pending: entries.into(),
+                back_to,
+                awaiting: start.to_string(),
+            });
+        }
+    }
+
+    /// Next step of a history replay after `url` finished loading
+    fn next_replay_step(&self, label: &str, url: &str) -> Option<ReplayStep> {
+        let mut pages = self.pages.lock().unwrap();
+        let page = pages.get_mut(label)?;
+        let replay = page.replay.as_mut()?;
+        if replay.awaiting != url {
+            page.replay = None;
+            return None;
+        }
+        if let Some(next) = replay.pending.pop_front() {
+            replay.awaiting = next.clone();
+            return Some(ReplayStep::Visit(next));
+        }
+        if let Some(previous) = replay.back_to.pop_front() {
+            replay.awaiting = previous;
+            return Some(ReplayStep::Back);
+        }
+        page.replay = None;
+        None
+    }
+
+    fn is_replaying(&self, label: &str) -> bool {
+        self.pages.lock().unwrap().get(label).is_some_and(|page| page.replay.is_some())
+    }
+
+    /// Drop the subscription once the webview is gone
+    pub fn unsubscribe(&self, label: &str) {
+        self.pages.lock().unwrap().remove(label);
+    }
+
+    /// Current state for a monitored webview
+    pub fn page_state(&self, label: &str) -> Option<PageState> {
+        self.pages.lock().unwrap().get(label).map(|page| page.state.clone())
+    }
+
+    /// States of every monitored webview
+    pub fn pages(&self) -> Vec<PageState> {
+        self.pages.lock().unwrap().values().map(|page| page.state.clone()).collect()
+    }
+
+    /// Apply a change to a monitored page, returning the new state and what changed
+    fn update(
+        &self,
+        label: &str,
+        change: impl FnOnce(&mut MonitoredPage) -> PageChange,
+    ) -> Option<(PageState, PageChange)> {
+        let mut pages = self.pages.lock().unwrap();
+        let page = pages.get_mut(label)?;
+        let page_change = change(page);
+        page_change.any().then(|| (page.state.clone(), page_change))
+    }
+}
+
+enum ReplayStep {
+    Visit(String),
+    Back,
+}
+
+/// Attach page monitoring hooks to a content webview builder.
+/// Page loads catch regular navigations; title changes catch most in-page (SPA) route changes.
+pub fn monitor_webview(
+    app: &tauri::AppHandle,
+    builder: WebviewBuilder<tauri::Wry>,
+    label: &str,
+    private: bool,
+) -> WebviewBuilder<tauri::Wry> {
+    if !app.state::<UrlMonitorRegistry>().subscribe(label, private) {
+        return builder;
+    }
+
+    builder
+        .initialization_script(page_info_script())
+        .on_page_load(|webview, payload| {
+            let url = payload.url().to_string();
+            let loading = match payload.event() {
+                PageLoadEvent::Started => LoadingState::Started,
+                PageLoadEvent::Finished => LoadingState::Finished,
+            };
+            report(webview.app_handle(), webview.label(), |page| {
+                let url_changed = page.commit_url(&url, false);
+                let loading_changed = page.state.loading != loading;
+                page.state.loading = loading;
+                PageChange { url: url_changed, loading: loading_changed, ..Default::default() }
+            });
+            if loading == LoadingState::Finished {
+                let monitor = webview.app_handle().state::<UrlMonitorRegistry>();
+                let step = monitor.next_replay_step(webview.label(), &url);
+                match step {
+                    Some(ReplayStep::Visit(next)) => {
+                        if let Ok(next) = Url::parse(&next) {
+                            let _ = webview.navigate(next);
+                        }
+                    }
+                    Some(ReplayStep::Back) => {
+                        let _ = webview.eval("history.back()");
+                    }
+                    None => {}
+                }
+            }
+        })
+        .on_document_title_changed(|webview, title| {
+            let url = webview.url().map(|u| u.to_string()).ok();
+            report(webview.app_handle(), webview.label(), |page| {
+                let url_changed = url.is_some_and(|url| page.commit_url(&url, false));
+                let title_changed = page.state.title != title;
+                page.state.title = title;
+                PageChange { url: url_changed, title: title_changed, ..Default::default() }
+            });
+        })
+}
+
+/// Apply a change, record it in history and emit `page-state` (plus `url-changed` when the URL moved)
+fn report(
+    app: &tauri::AppHandle,
+    label: &str,
+    change: impl FnOnce(&mut MonitoredPage) -> PageChange,
+) {
+    let monitor = app.state::<UrlMonitorRegistry>();
+    let Some((state, page_change)) = monitor.update(label, change) else {
+        return;
+    };
+
+    // Private windows leave no trace, and pages loaded again to rebuild
+    // a reopened tab's history were already visited
+    let recorded = !state.private && !monitor.is_replaying(label);
+    if page_change.url && recorded {
+        history::record_navigation(app, &state.url, &state.title, label);
+        // Bookmarks of other profiles aren't loaded
+        if profiles::in_active_profile(app, label) {
+            bookmarks::record_visit(app, &state.url);
+        }
+    } else if page_change.title && recorded {
+        history::record_title(app, &state.url, &state.title, label);
+    }
+
+    if page_change.url {
+        // Emit event to all windows (the main window will receive it)
+        let _ = app.emit("url-changed", serde_json::json!({
+            "url": state.url,
+            "windowLabel": label,
+            "private": state.private
+        }));
+    }
+    let _ = app.emit("page-state", state);
+}
 
 #[tauri::command]
-pub async fn get_current_url(
+pub async fn get_page_state(
     app: tauri::AppHandle,
     window_label: String,
-) -> Result<String, String> {
-    // Use get_webview for child webviews in multi-webview architecture
-    if let Some(webview) = app.get_webview(&window_label) {
-        let url = webview.url().map_err(|e| e.to_string())?;
-        Ok(url.to_string())
-    } else {
-        Err("Webview not found".to_string())
+) -> Result<PageState, String> {
+    // Hibernated tabs have no webview but keep their state
+    if let Some(state) = app.state::<UrlMonitorRegistry>().page_state(&window_label) {
+        if !state.url.is_empty() {
+            return Ok(state);
+        }
     }
+
+    // Use get_webview for child webviews in multi-webview architecture
+    let webview = app.get_webview(&window_label)
+        .ok_or_else(|| "Webview not found".to_string())?;
+
+    // Not monitored or nothing loaded yet - build what we can from the webview itself
+    let url = webview.url().map_err(|e| e.to_string())?;
+    let mut page = MonitoredPage::new(&window_label, false);
+    page.commit_url(url.as_str(), false);
+    Ok(page.state)
 }
 
+/// Called by the page script in a content webview; a page can only report on itself
 #[tauri::command]
-pub async fn setup_url_monitor(
-    app: tauri::AppHandle,
-    window_label: String,
+pub async fn report_page_info(
+    webview: tauri::Webview,
+    mut info: PageInfo,
 ) -> Result<(), String> {
-    let app_handle = app.clone();
-    let label = window_label.clone();
-    
-    // Spawn a background task to monitor URL changes
-    std::thread::spawn(move || {
-        let mut last_url = String::new();
-        
-        loop {
-            // Check if webview still exists (use get_webview for child webviews)
-            if let Some(webview) = app_handle.get_webview(&label) {
-                if let Ok(current_url) = webview.url() {
-                    let url_string = current_url.to_string();
-                    
-                    // Only emit if URL has changed
-                    if url_string != last_url {
-                        last_url = url_string.clone();
-                        
-                        // Emit event to all windows (the main window will receive it)
-                        let _ = app_handle.emit("url-changed", serde_json::json!({
-                            "url": url_string,
-                            "windowLabel": label
-                        }));
-                    }
-                }
-            } else {
-                // Window was closed, stop monitoring
-                break;
-            }
-            
-            // Poll every 300ms (more efficient than frontend polling)
-            std::thread::sleep(std::time::Duration::from_millis(300));
-        }
-    });
-    
+    let app = webview.app_handle();
+    let page_url = info.url.clone();
+    let search_descriptions = std::mem::take(&mut info.search_descriptions);
+    report(app, webview.label(), |page| page.apply_info(info));
+
+    // Sites visited privately aren't remembered as search engine offers either
+    if !search_descriptions.is_empty() && !app.state::<UrlMonitorRegistry>().is_private(webview.label()) {
+        search::opensearch::discover(app, webview.label(), &page_url, &search_descriptions);
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(urls: &[&str]) -> MonitoredPage {
+        let mut page = MonitoredPage::new("content-1", false);
+        for url in urls {
+            page.commit_url(url, false);
+        }
+        page
+    }
+
+    fn info(url: &str) -> PageInfo {
+        PageInfo { url: url.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn new_pages_are_appended() {
+        let page = visit(&["https://a.com/", "https://b.com/", "https://c.com/"]);
+        assert_eq!(page.history, ["https://a.com/", "https://b.com/", "https://c.com/"]);
+        assert_eq!(page.history_index, 2);
+        assert!(page.state.can_go_back);
+        assert!(!page.state.can_go_forward);
+        assert!(page.state.is_secure);
+        assert_eq!(page.state.favicon_url.as_deref(), Some("https://c.com/favicon.ico"));
+    }
+
+    #[test]
+    fn same_url_is_not_a_change() {
+        let mut page = visit(&["https://a.com/"]);
+        page.state.title = "A".to_string();
+        assert!(!page.commit_url("https://a.com/", false));
+        assert_eq!(page.history.len(), 1);
+        assert_eq!(page.state.title, "A");
+    }
+
+    #[test]
+    fn link_to_the_previous_page_is_a_new_entry() {
+        let mut page = visit(&["https://a.com/", "https://b.com/"]);
+        assert!(page.commit_url("https://a.com/", false));
+        assert_eq!(page.history, ["https://a.com/", "https://b.com/", "https://a.com/"]);
+        assert_eq!(page.history_index, 2);
+    }
+
+    #[test]
+    fn traversals_move_within_history() {
+        let mut page = visit(&["https://a.com/", "https://b.com/", "https://c.com/"]);
+        page.commit_url("https://b.com/", true);
+        assert_eq!(page.history_index, 1);
+        assert!(page.state.can_go_back && page.state.can_go_forward);
+
+        page.commit_url("https://c.com/", true);
+        assert_eq!(page.history_index, 2);
+        assert_eq!(page.history.len(), 3);
+    }
+
+    #[test]
+    fn new_entry_drops_forward_history() {
+        let mut page = visit(&["https://a.com/", "https://b.com/", "https://c.com/"]);
+        page.commit_url("https://b.com/", true);
+        page.commit_url("https://d.com/", false);
+        assert_eq!(page.history, ["https://a.com/", "https://b.com/", "https://d.com/"]);
+        assert!(!page.state.can_go_forward);
+    }
+
+    #[test]
+    fn reported_traversal_corrects_a_new_entry() {
+        let mut page = visit(&["https://a.com/", "https://b.com/"]);
+        // Load events see the step back as a visit
+        page.commit_url("https://a.com/", false);
+        page.apply_info(PageInfo { traversal: true, ..info("https://a.com/") });
+        assert_eq!(page.history, ["https://a.com/", "https://b.com/"]);
+        assert_eq!(page.history_index, 0);
+        assert!(!page.state.can_go_back);
+        assert!(page.state.can_go_forward);
+    }
+
+    #[test]
+    fn reported_traversal_within_the_page_commits_the_url() {
+        let mut page = visit(&["https://a.com/#one", "https://a.com/#two"]);
+        let change = page.apply_info(PageInfo { traversal: true, ..info("https://a.com/#one") });
+        assert!(change.url);
+        assert_eq!(page.state.url, "https://a.com/#one");
+        assert_eq!(page.history_index, 0);
+    }
+
+    #[test]
+    fn late_report_from_another_document_is_ignored() {
+        let mut page = visit(&["https://a.com/", "https://b.com/"]);
+        let change = page.apply_info(PageInfo {
+            favicon_url: Some("https://a.com/icon.png".to_string()),
+            ..info("https://a.com/")
+        });
+        assert!(!change.any());
+        assert_eq!(page.state.favicon_url.as_deref(), Some("https://b.com/favicon.ico"));
+    }
+
+    #[test]
+    fn document_icon_and_history_state_win() {
+        let mut page = visit(&["https://a.com/"]);
+        let change = page.apply_info(PageInfo {
+            favicon_url: Some("https://cdn.a.com/icon-192.png".to_string()),
+            can_go_back: Some(true),
+            can_go_forward: Some(false),
+            ..info("https://a.com/")
+        });
+        assert!(change.details);
+        assert_eq!(page.state.favicon_url.as_deref(), Some("https://cdn.a.com/icon-192.png"));
+        assert!(page.state.can_go_back);
+
+        // Scripts can't point the icon anywhere else
+        page.apply_info(PageInfo {
+            favicon_url: Some("javascript:alert(1)".to_string()),
+            ..info("https://a.com/")
+        });
+        assert_eq!(page.state.favicon_url.as_deref(), Some("https://a.com/favicon.ico"));
+    }
+
+    #[test]
+    fn non_web_pages_have_no_default_icon() {
+        let page = visit(&["about:blank"]);
+        assert_eq!(page.state.favicon_url, None);
+        assert!(!page.state.is_secure);
+    }
+}
@@ -1,7 +1,14 @@
-use tauri::{Emitter, WebviewUrl, LogicalPosition, LogicalSize};
+use tauri::{Emitter, Manager, Url, WebviewUrl, LogicalPosition, LogicalSize};
 use tauri::window::WindowBuilder;
 use tauri::webview::WebviewBuilder;
 
+use crate::navigation::normalize_url;
+use crate::popup_rules::{self, PopupAction};
+use crate::tabs::{self, TabRegistry};
+use crate::url_monitor::{self, UrlMonitorRegistry};
+use crate::containers::{self, ContainerRegistry};
+use crate::profiles::{self, ProfileManager};
+use crate::{popup_blocker, settings};
 use crate::constants::{
     TITLE_BAR_HEIGHT, 
     WINDOW_WIDTH_PERCENT, 
@@ -10,6 +17,17 @@ use crate::constants::{
     FALLBACK_WINDOW_HEIGHT
 };
 
+/// Where a browser window keeps its cookies and storage
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WindowStorage<'a> {
+    /// In memory only; the window also stays out of history and sessions
+    pub private: bool,
+    /// The active profile if `None`
+    pub profile_id: Option<&'a str>,
+    /// A container of the profile. If `None`, the container the URL's container rule names, if any.
+    pub container_id: Option<&'a str>,
+}
+
 /// Helper function to create a content window with multi-webview (title bar + content)
 pub fn create_multi_webview_window(
     app: &tauri::AppHandle,
@@ -17,7 +35,30 @@ pub fn create_multi_webview_window(
     titlebar_label: &str,
     content_webview_label: &str,
     url: &str,
+    storage: WindowStorage,
 ) -> Result<(), String> {
+    let private = storage.private;
+    let profiles = app.state::<ProfileManager>();
+    let profile_id = match storage.profile_id {
+        Some(id) if profiles.exists(id) => id.to_string(),
+        Some(id) => return Err(format!("Profile not found: {}", id)),
+        None => profiles.active_id(),
+    };
+    // Private windows have nothing to partition
+    let container_id = match storage.container_id {
+        _ if private => None,
+        Some(id) => Some(id.to_string()),
+        None => containers::rule_for(&profiles::settings_of(app, &profile_id), url),
+    };
+    let container = container_id
+        .map(|id| containers::find(app, &profile_id, &id))
+        .transpose()?;
+    let title = match &container {
+        _ if private => "Meikai Browser (Private)".to_string(),
+        Some(container) => format!("Meikai Browser ({})", container.name),
+        None => "Meikai Browser".to_string(),
+    };
+
     // Calculate window size as percentage of screen width, height from 7:4 aspect ratio
     let (window_width, window_height) = match app.primary_monitor() {
         Ok(Some(monitor)) => {
@@ -32,7 +73,7 @@ pub fn create_multi_webview_window(
     // Create the window WITH native decorations (saves ~80-100MB per window)
     // Previously used custom title bar webview which required decorations(false) + transparent(true)
     let window = WindowBuilder::new(app, window_label)
-        .title("Meikai Browser")
+        .title(title)
         .inner_size(window_width, window_height)
         .center()
         .resizable(true)
@@ -40,6 +81,11 @@ pub fn create_multi_webview_window(
         .transparent(false)  // No transparency needed with native decorations
         .build()
         .map_err(|e| e.to_string())?;
+    // Before the first tab is built, which takes its data directory from the window's profile and container
+    profiles.assign_window(window_label, &profile_id);
+    if let Some(container) = container {
+        app.state::<ContainerRegistry>().assign_window(window_label, container);
+    }
 
     // Get window size for positioning webviews
     let window_size = window.inner_size().map_err(|e| e.to_string())?;
@@ -73,63 +119,8 @@ pub fn create_multi_webview_window(
     // ).map_err(|e| e.to_string())?;
     // ============================================================================
 
-    // Clone app handle for on_new_window handler
-    let app_for_handler = app.clone();
-    
-    // Create content webview (loads the external URL)
-    let content_webview = WebviewBuilder::new(
-        content_webview_label,
-        WebviewUrl::External(url.parse().map_err(|e| format!("Invalid URL: {:?}", e))?)
-    ).on_new_window(move |new_url, _features| {
-        // Check if this is an OAuth-related URL that needs native popup handling
-        // OAuth flows require window.opener to communicate back to the parent
-        let url_string = new_url.to_string();
-        let is_oauth_url = url_string.contains("accounts.google.com") ||
-                          url_string.contains("login.microsoftonline.com") ||
-                          url_string.contains("facebook.com/login") ||
-                          url_string.contains("facebook.com/v") ||
-                          url_string.contains("appleid.apple.com") ||
-                          url_string.contains("github.com/login/oauth") ||
-                          url_string.contains("twitter.com/oauth") ||
-                          url_string.contains("api.twitter.com") ||
-                          url_string.contains("oauth") ||
-                          url_string.contains("authorize") ||
-                          url_string.contains("signin") ||
-                          url_string.contains("auth/");
-        
-        if is_oauth_url {
-            // Let the WebView handle OAuth popups natively to maintain window.opener
-            // This is required for OAuth flows to work properly
-            tauri::webview::NewWindowResponse::Allow
-        } else {
-            // Handle regular window.open() and target="_blank" requests with our custom window
-            let new_window_id = uuid::Uuid::new_v4().to_string();
-            let new_window_label = format!("window-{}", new_window_id);
-            let new_titlebar_label = format!("titlebar-{}", new_window_id);
-            let new_content_label = format!("content-{}", new_window_id);
-            let app_clone = app_for_handler.clone();
-            let label_for_event = new_content_label.clone();
-            let url_for_window = url_string.clone();
-            
-            match create_multi_webview_window(
-                &app_clone,
-                &new_window_label,
-                &new_titlebar_label,
-                &new_content_label,
-                &url_for_window,
-            ) {
-                Ok(_) => {
-                    // Emit event to frontend to track this new window
-                    let _ = app_clone.emit("new-window-created", serde_json::json!({
-                        "windowLabel": label_for_event,
-                        "url": url_for_window
-                    }));
-                    tauri::webview::NewWindowResponse::Deny
-                }
-                Err(_) => tauri::webview::NewWindowResponse::Deny
-            }
-        }
-    });
+    // Create content webview (loads the external URL) as the window's first tab
+    let content_webview = content_webview_builder(app, content_webview_label, url, private)?;
     
     // Content webview now fills the entire window (no custom title bar offset)
     // Previously used: LogicalPosition::new(0.0, TITLE_BAR_HEIGHT) and height - TITLE_BAR_HEIGHT
@@ -138,14 +129,148 @@ pub fn create_multi_webview_window(
         LogicalPosition::new(0.0, 0.0),  // Start at top since native decorations handle title bar
         LogicalSize::new(width, height), // Full height
     ).map_err(|e| e.to_string())?;
+    app.state::<TabRegistry>().add(window_label, content_webview_label, None, true);
 
     Ok(())
 }
 
+/// Builder for a content webview (a tab): popup routing, the popup blocker and page monitoring.
+/// A private tab gets an ephemeral data store instead of its container's or profile's data directory.
+pub fn content_webview_builder(
+    app: &tauri::AppHandle,
+    content_webview_label: &str,
+    url: &str,
+    private: bool,
+) -> Result<WebviewBuilder<tauri::Wry>, String> {
+    // Clone app handle and label for the on_new_window handler
+    let app_for_handler = app.clone();
+    let opener_label = content_webview_label.to_string();
+
+    let mut content_webview = WebviewBuilder::new(
+        content_webview_label,
+        WebviewUrl::External(normalize_url(url).map_err(|e| e.to_string())?)
+    )
+    .incognito(private);
+
+    // Cookies, cache and storage of the window's container, or else its profile.
+    // Private tabs keep theirs in memory only, so they never touch either.
+    let profile_id = profiles::tab_profile(app, content_webview_label);
+    let data_store = match containers::tab_container(app, content_webview_label) {
+        _ if private => None,
+        Some(container) => Some((containers::data_dir(app, &profile_id, &container.id), container.id)),
+        None => app
+            .state::<ProfileManager>()
+            .webview_data_dir(&profile_id)
+            .map(|dir| (dir, profile_id)),
+    };
+    if let Some((data_directory, store_id)) = data_store {
+        content_webview = content_webview.data_directory(data_directory);
+        // WKWebView has no data directories, only data stores identified by a UUID
+        if let Ok(id) = uuid::Uuid::parse_str(&store_id) {
+            content_webview = content_webview.data_store_identifier(*id.as_bytes());
+        }
+    }
+
+    let content_webview = content_webview
+    .initialization_script_for_all_frames(popup_blocker::blocker_script())
+    .on_new_window(move |new_url, _features| {
+        let settings = settings::current(&app_for_handler);
+
+        // Popups a page opened without a user gesture arrive wrapped by the blocker script
+        if let Some(target) = popup_blocker::blocked_target(&new_url) {
+            if !popup_blocker::opener_allowed(&app_for_handler, &opener_label, &settings.popup_allowlist) {
+                popup_blocker::report_blocked(&app_for_handler, &opener_label, &target);
+                return tauri::webview::NewWindowResponse::Deny;
+            }
+            let action = popup_blocker::released_action(&target, &settings::popup_rules(&app_for_handler));
+            return open_popup(&app_for_handler, &opener_label, target, action);
+        }
+
+        // Built-in and user popup rules decide where window.open() and target="_blank" go
+        let action = popup_rules::route(&new_url, &settings::popup_rules(&app_for_handler));
+        open_popup(&app_for_handler, &opener_label, new_url, action)
+    });
+
+    // Report URL changes to the frontend from the webview's own load events
+    Ok(url_monitor::monitor_webview(app, content_webview, content_webview_label, private))
+}
+
+/// Carry out a popup routing decision for the page in `opener_label`
+pub fn open_popup(
+    app: &tauri::AppHandle,
+    opener_label: &str,
+    url: Url,
+    action: PopupAction,
+) -> tauri::webview::NewWindowResponse<tauri::Wry> {
+    match action {
+        // OAuth flows need window.opener to report back to the page that opened them
+        PopupAction::NativePopup => return tauri::webview::NewWindowResponse::Allow,
+        PopupAction::NewTab => {
+            let opener_container = containers::tab_container(app, opener_label).map(|container| container.id);
+            // Sites that always open in another container get a window of their own
+            if containers::link_container(app, opener_label, url.as_str()) != opener_container {
+                let _ = open_popup_window(app, opener_label, url.as_str());
+            } else {
+                let parent_label = tabs::parent_window_label(app, opener_label);
+                let _ = tabs::open_tab(app, &parent_label, url.as_str(), Some(opener_label), true);
+            }
+        }
+        PopupAction::NewWindow => {
+            let _ = open_popup_window(app, opener_label, url.as_str());
+        }
+        PopupAction::SameWindow => {
+            if let Some(opener) = app.get_webview(opener_label) {
+                let _ = opener.navigate(url);
+            }
+        }
+        PopupAction::Block => {}
+    }
+    tauri::webview::NewWindowResponse::Deny
+}
+
+/// Open a popup's URL in a new Meikai window and tell the frontend to track it.
+/// Popups stay private and in the opener's profile; they keep its container unless a rule names another.
+fn open_popup_window(app: &tauri::AppHandle, opener_label: &str, url: &str) -> Result<(), String> {
+    let private = app.state::<UrlMonitorRegistry>().is_private(opener_label);
+    let profile_id = profiles::tab_profile(app, opener_label);
+    let container_id = containers::link_container(app, opener_label, url);
+    let new_window_id = uuid::Uuid::new_v4().to_string();
+    let new_window_label = format!("window-{}", new_window_id);
+    let new_titlebar_label = format!("titlebar-{}", new_window_id);
+    let new_content_label = format!("content-{}", new_window_id);
+
+    create_multi_webview_window(
+        app,
+        &new_window_label,
+        &new_titlebar_label,
+        &new_content_label,
+        url,
+        WindowStorage {
+            private,
+            profile_id: Some(&profile_id),
+            container_id: container_id.as_deref(),
+        },
+    )?;
+
+    // Emit event to frontend to track this new window
+    let _ = app.emit("new-window-created", serde_json::json!({
+        "windowLabel": new_content_label,
+        "url": url,
+        "private": private,
+        "profileId": profile_id,
+        "containerId": container_id
+    }));
+    Ok(())
+}
+
+/// Open a browser window in `profile_id`, or the active profile if not given. Its tabs keep
+/// cookies and storage in `container_id`, or the container a rule names for the URL.
 #[tauri::command]
 pub async fn create_content_window(
     app: tauri::AppHandle,
     url: String,
+    profile_id: Option<String>,
+    container_id: Option<String>,
 ) -> Result<String, String> {
     let window_id = uuid::Uuid::new_v4().to_string();
     let window_label = format!("window-{}", window_id);
@@ -158,8 +283,40 @@ pub async fn create_content_window(
         &titlebar_label,
         &content_label,
         &url,
+        WindowStorage {
+            private: false,
+            profile_id: profile_id.as_deref(),
+            container_id: container_id.as_deref(),
+        },
     )?;
 
     // Return the content webview label so frontend can track it
     Ok(content_label)
 }
+
+/// Like `create_content_window`, but nothing the window does outlives it: storage is
+/// ephemeral and its pages stay out of history, sessions and search engine offers
+#[tauri::command]
+pub async fn create_private_window(
+    app: tauri::AppHandle,
+    url: String,
+) -> Result<String, String> {
+    let window_id = uuid::Uuid::new_v4().to_string();
+    let window_label = format!("window-{}", window_id);
+    let titlebar_label = format!("titlebar-{}", window_id);
+    let content_label = format!("content-{}", window_id);
+
+    create_multi_webview_window(
+        &app,
+        &window_label,
+        &titlebar_label,
+        &content_label,
+        &url,
+        WindowStorage {
+            private: true,
+            ..Default::default()
+        },
+    )?;
+
+    Ok(content_label)
+}
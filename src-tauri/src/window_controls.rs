@@ -1,23 +1,18 @@
 use tauri::Manager;
 
-/// Derive the parent window label from the content webview label
-fn get_parent_window_label(window_label: &str) -> String {
-    if window_label.starts_with("content-") {
-        format!("window-{}", window_label.trim_start_matches("content-"))
-    } else {
-        window_label.to_string()
-    }
-}
+use crate::tabs;
 
 #[tauri::command]
 pub async fn show_browser_window(
     app: tauri::AppHandle,
     window_label: String,
 ) -> Result<(), String> {
-    let parent_label = get_parent_window_label(&window_label);
+    let parent_label = tabs::parent_window_label(&app, &window_label);
     
     if let Some(window) = app.get_window(&parent_label) {
         window.show().map_err(|e| e.to_string())?;
+        // Rebuilds the visible tabs if they were hibernated while the window was hidden
+        tabs::layout_window(&app, &parent_label);
     }
     Ok(())
 }
@@ -27,7 +22,7 @@ pub async fn hide_browser_window(
     app: tauri::AppHandle,
     window_label: String,
 ) -> Result<(), String> {
-    let parent_label = get_parent_window_label(&window_label);
+    let parent_label = tabs::parent_window_label(&app, &window_label);
     
     if let Some(window) = app.get_window(&parent_label) {
         window.hide().map_err(|e| e.to_string())?;
@@ -40,7 +35,7 @@ pub async fn close_browser_window(
     app: tauri::AppHandle,
     window_label: String,
 ) -> Result<(), String> {
-    let parent_label = get_parent_window_label(&window_label);
+    let parent_label = tabs::parent_window_label(&app, &window_label);
     
     if let Some(window) = app.get_window(&parent_label) {
         window.close().map_err(|e| e.to_string())?;
@@ -53,7 +48,7 @@ pub async fn minimize_browser_window(
     app: tauri::AppHandle,
     window_label: String,
 ) -> Result<(), String> {
-    let parent_label = get_parent_window_label(&window_label);
+    let parent_label = tabs::parent_window_label(&app, &window_label);
     
     if let Some(window) = app.get_window(&parent_label) {
         // Check if window is minimized
@@ -75,7 +70,7 @@ pub async fn toggle_maximize_browser_window(
     app: tauri::AppHandle,
     window_label: String,
 ) -> Result<(), String> {
-    let parent_label = get_parent_window_label(&window_label);
+    let parent_label = tabs::parent_window_label(&app, &window_label);
     
     if let Some(window) = app.get_window(&parent_label) {
         // Check if window is maximized
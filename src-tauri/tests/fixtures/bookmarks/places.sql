@@ -0,0 +1,42 @@
+-- The parts of Firefox's places.sqlite that bookmark import reads
+CREATE TABLE moz_places (
+    id INTEGER PRIMARY KEY,
+    url LONGVARCHAR,
+    title LONGVARCHAR,
+    guid TEXT UNIQUE
+);
+CREATE TABLE moz_bookmarks (
+    id INTEGER PRIMARY KEY,
+    type INTEGER,
+    fk INTEGER DEFAULT NULL,
+    parent INTEGER,
+    position INTEGER,
+    title LONGVARCHAR,
+    dateAdded INTEGER,
+    lastModified INTEGER,
+    guid TEXT UNIQUE
+);
+
+INSERT INTO moz_places (id, url, title, guid) VALUES
+    (1, 'https://www.mozilla.org/', 'Mozilla', 'place1______'),
+    (2, 'https://developer.mozilla.org/en-US/', 'MDN', 'place2______'),
+    (3, 'place:sort=8&maxResults=10', NULL, 'place3______'),
+    (4, 'https://example.com/unfiled', 'Unfiled', 'place4______');
+
+INSERT INTO moz_bookmarks (id, type, fk, parent, position, title, dateAdded, lastModified, guid) VALUES
+    (1, 2, NULL, 0, 0, '', 1700000000000000, 1700000000000000, 'root________'),
+    (2, 2, NULL, 1, 0, 'menu', 1700000000000000, 1700000000000000, 'menu________'),
+    (3, 2, NULL, 1, 1, 'toolbar', 1700000000000000, 1700000000000000, 'toolbar_____'),
+    (4, 2, NULL, 1, 2, 'tags', 1700000000000000, 1700000000000000, 'tags________'),
+    (5, 2, NULL, 1, 3, 'unfiled', 1700000000000000, 1700000000000000, 'unfiled_____'),
+    (6, 2, NULL, 1, 4, 'mobile', 1700000000000000, 1700000000000000, 'mobile______'),
+    -- Toolbar: MDN, then a folder with Mozilla, in that order despite the IDs
+    (10, 2, NULL, 3, 1, 'Reading', 1700000100000000, 1700000100000000, 'folder1_____'),
+    (11, 1, 1, 10, 0, 'Mozilla', 1700000200000000, 1700000200000000, 'bookmark1___'),
+    (12, 1, 2, 3, 0, 'MDN', 1700000300000000, 1700000300000000, 'bookmark2___'),
+    (13, 3, NULL, 3, 2, NULL, 1700000300000000, 1700000300000000, 'separator1__'),
+    (14, 1, 3, 3, 3, 'Most Visited', 1700000300000000, 1700000300000000, 'smartbmk1___'),
+    -- Tags: "docs" on MDN
+    (20, 2, NULL, 4, 0, 'docs', 1700000400000000, 1700000400000000, 'tag1________'),
+    (21, 1, 2, 20, 0, NULL, 1700000400000000, 1700000400000000, 'tagentry1___'),
+    (30, 1, 4, 5, 0, 'Unfiled', 1700000500000000, 1700000500000000, 'bookmark3___');
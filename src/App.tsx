@@ -5,8 +5,11 @@ import { listen } from "@tauri-apps/api/event";
 import { PhysicalPosition, PhysicalSize } from '@tauri-apps/api/dpi';
 import { Panel, Dock, MiniPanel } from './components';
 import { BetaDisclaimer } from './components/BetaDisclaimer';
+import { SessionRestorePrompt } from './components/SessionRestorePrompt';
 import { useSettings } from './hooks/useSettings';
 import { useBookmarks } from './hooks/useBookmarks';
+import { TabStrip } from './hooks/useTabs';
+import { useSessionRestore, RestoredWindow } from './hooks/useSession';
 
 // =============================================================================
 // LAYOUT CONFIGURATION (Percentage-based sizing)
@@ -51,16 +54,26 @@ const calculateSizes = (screenWidth: number, screenHeight: number) => ({
 export const PANEL_SIZE = calculateSizes(FALLBACK_SCREEN.width, FALLBACK_SCREEN.height).panel;
 
 interface ContentWindow {
+  // Content webview label of the window's active tab
   windowLabel: string;
+  // The OS window hosting the tabs
+  parentLabel: string;
   url: string;
+  // Opened with create_private_window: ephemeral storage, no history
+  private?: boolean;
 }
 
+// A window's first tab shares its ID: content-{uuid} lives in window-{uuid}
+const parentLabelOf = (windowLabel: string) => windowLabel.replace(/^content-/, "window-");
+
 function App() {
   const [url, setUrl] = useState("");
   const [isNotchMode, setIsNotchMode] = useState(false);
   const [contentWindows, setContentWindows] = useState<ContentWindow[]>([]);
   const [activeWindowIndex, setActiveWindowIndex] = useState(0);
   const [showMiniPanel, setShowMiniPanel] = useState(false);
+  // Whether the MiniPanel opens a new (private) window or a tab in the active one
+  const [miniPanelTarget, setMiniPanelTarget] = useState<"window" | "private" | "tab">("window");
 
   // Helper to get layout sizes directly from monitor
   const getLayoutSizes = async () => {
@@ -83,7 +96,6 @@ function App() {
     setAnimationsEnabled,
     setHasSeenDisclaimer,
     resetSettings,
-    getSearchUrl,
   } = useSettings();
 
   // Bookmarks management - also at App level for MiniPanel access
@@ -93,6 +105,9 @@ function App() {
     addBookmark,
   } = useBookmarks();
 
+  // Windows left open when Meikai last quit
+  const { offer: sessionOffer, restore: restoreLastSession, dismiss: dismissSessionOffer } = useSessionRestore();
+
   // Get the actively selected window
   const activeContentWindow = contentWindows.length > 0 
     ? contentWindows[activeWindowIndex]?.windowLabel 
@@ -114,15 +129,15 @@ function App() {
 
   // Listen for new windows created from redirect links (target="_blank", window.open)
   useEffect(() => {
-    const unlisten = listen<{ windowLabel: string; url: string }>("new-window-created", async (event) => {
-      const { windowLabel, url: newUrl } = event.payload;
+    const unlisten = listen<{ windowLabel: string; url: string; private: boolean }>("new-window-created", async (event) => {
+      const { windowLabel, url: newUrl, private: isPrivate } = event.payload;
       
       // Hide current active window before showing new one
       if (contentWindows.length > 0 && contentWindows[activeWindowIndex]) {
         await invoke("hide_browser_window", { windowLabel: contentWindows[activeWindowIndex].windowLabel });
       }
       
-      setContentWindows(prev => [...prev, { windowLabel, url: newUrl }]);
+      setContentWindows(prev => [...prev, { windowLabel, parentLabel: parentLabelOf(windowLabel), url: newUrl, private: isPrivate }]);
       setActiveWindowIndex(contentWindows.length); // New window becomes active (will be at the end)
       setUrl(newUrl);
     });
@@ -132,13 +147,32 @@ function App() {
     };
   }, [contentWindows, activeWindowIndex]);
 
+  // Follow tab switches, so commands and the URL bar target each window's active tab
+  useEffect(() => {
+    const unlisten = listen<TabStrip>("tabs-changed", (event) => {
+      const { parentLabel, activeLabel, tabs } = event.payload;
+      const activeUrl = tabs.find(tab => tab.windowLabel === activeLabel)?.url ?? "";
+
+      setContentWindows(prev => prev.map(w =>
+        w.parentLabel === parentLabel ? { ...w, windowLabel: activeLabel, url: activeUrl || w.url } : w
+      ));
+      if (contentWindows[activeWindowIndex]?.parentLabel === parentLabel && activeUrl) {
+        setUrl(activeUrl);
+      }
+    });
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [contentWindows, activeWindowIndex]);
+
   // Listen for windows being closed (user clicks X on window title bar)
   useEffect(() => {
-    const unlisten = listen<{ windowLabel: string }>("window-closed", async (event) => {
-      const closedLabel = event.payload.windowLabel;
+    const unlisten = listen<{ windowLabel: string; parentLabel: string }>("window-closed", async (event) => {
+      const { parentLabel } = event.payload;
       
       // Check if this is one of our content windows
-      const closedIndex = contentWindows.findIndex(w => w.windowLabel === closedLabel);
+      const closedIndex = contentWindows.findIndex(w => w.parentLabel === parentLabel);
       if (closedIndex === -1) return; // Not a content window we're tracking
       
       // Remove the closed window from our list
@@ -220,7 +254,7 @@ function App() {
   const handleNavigate = async (fullUrl: string) => {
     // Create new content window
     const windowLabel = await invoke<string>("create_content_window", { url: fullUrl });
-    setContentWindows([{ windowLabel, url: fullUrl }]);
+    setContentWindows([{ windowLabel, parentLabel: parentLabelOf(windowLabel), url: fullUrl }]);
     setActiveWindowIndex(0);
     setUrl(fullUrl);
 
@@ -230,7 +264,7 @@ function App() {
 
   const handleQuickLink = async (siteUrl: string) => {
     const windowLabel = await invoke<string>("create_content_window", { url: siteUrl });
-    setContentWindows([{ windowLabel, url: siteUrl }]);
+    setContentWindows([{ windowLabel, parentLabel: parentLabelOf(windowLabel), url: siteUrl }]);
     setActiveWindowIndex(0);
     setUrl(siteUrl);
 
@@ -238,17 +272,51 @@ function App() {
     await transformToNotch();
   };
 
-  // Handler for opening new window from MiniPanel
-  const handleNewWindow = async () => {
+  // Reopen the previous run's windows, showing the one that was active
+  const handleRestoreSession = async () => {
+    try {
+      const { windows, activeIndex } = await restoreLastSession();
+      const active = windows[activeIndex];
+      setContentWindows(windows);
+      setActiveWindowIndex(activeIndex);
+      setUrl(active.url);
+      await invoke("show_browser_window", { windowLabel: active.windowLabel });
+
+      await transformToNotch();
+    } catch (error) {
+      console.error("Failed to restore the last session:", error);
+    }
+  };
+
+  // Expand the dock to show the MiniPanel, which then opens a window or a tab
+  const openMiniPanel = async (target: "window" | "private" | "tab") => {
     const window = getCurrentWindow();
     const { miniPanel } = await getLayoutSizes();
-    // Expand dock to show MiniPanel
     await window.setSize(new PhysicalSize(miniPanel.width, miniPanel.height));
+    setMiniPanelTarget(target);
     setShowMiniPanel(true);
   };
 
+  // Handler for opening new window from MiniPanel
+  const handleNewWindow = () => openMiniPanel("window");
+
+  const handleNewPrivateWindow = () => openMiniPanel("private");
+
+  const handleNewTab = () => openMiniPanel("tab");
+
+  // Open the MiniPanel's URL as a tab of the active window; tabs-changed makes it active
+  const handleCreateNewTab = async (fullUrl: string) => {
+    const appWindow = getCurrentWindow();
+    if (activeContentWindow) {
+      await invoke<string>("create_tab", { windowLabel: activeContentWindow, url: fullUrl });
+    }
+    setShowMiniPanel(false);
+    const { dock } = await getLayoutSizes();
+    await appWindow.setSize(new PhysicalSize(dock.width, dock.height));
+  };
+
   // Handler for creating additional windows
-  const handleCreateNewWindow = async (fullUrl: string) => {
+  const handleCreateNewWindow = async (fullUrl: string, isPrivate = false) => {
     const appWindow = getCurrentWindow();
     
     // Hide current active window before creating new one
@@ -256,10 +324,10 @@ function App() {
       await invoke("hide_browser_window", { windowLabel: contentWindows[activeWindowIndex].windowLabel });
     }
     
-    const windowLabel = await invoke<string>("create_content_window", { url: fullUrl });
+    const windowLabel = await invoke<string>(isPrivate ? "create_private_window" : "create_content_window", { url: fullUrl });
     setContentWindows(prev => {
       setActiveWindowIndex(prev.length); // Set to new window's index
-      return [...prev, { windowLabel, url: fullUrl }];
+      return [...prev, { windowLabel, parentLabel: parentLabelOf(windowLabel), url: fullUrl, private: isPrivate }];
     });
     setUrl(fullUrl);
     setShowMiniPanel(false);
@@ -268,6 +336,23 @@ function App() {
     await appWindow.setSize(new PhysicalSize(dock.width, dock.height));
   };
 
+  // Bring back the most recently closed window where it was, next to the open ones
+  const handleReopenClosedWindow = async () => {
+    try {
+      const reopened = await invoke<RestoredWindow>("reopen_closed_window");
+      if (contentWindows[activeWindowIndex]) {
+        await invoke("hide_browser_window", { windowLabel: contentWindows[activeWindowIndex].windowLabel });
+      }
+      setContentWindows(prev => {
+        setActiveWindowIndex(prev.length);
+        return [...prev, reopened];
+      });
+      setUrl(reopened.url);
+    } catch (error) {
+      console.error("Failed to reopen window:", error);
+    }
+  };
+
   // Handler for switching between windows (tab-like behavior)
   const handleSwitchWindow = async (index: number) => {
     if (index === activeWindowIndex || index < 0 || index >= contentWindows.length) return;
@@ -318,6 +403,13 @@ function App() {
         {!settings.hasSeenDisclaimer && (
           <BetaDisclaimer onDismiss={() => setHasSeenDisclaimer(true)} />
         )}
+        {settings.hasSeenDisclaimer && sessionOffer && (
+          <SessionRestorePrompt
+            session={sessionOffer}
+            onRestore={handleRestoreSession}
+            onDismiss={dismissSessionOffer}
+          />
+        )}
         <Panel 
           onNavigate={handleNavigate} 
           onQuickLink={handleQuickLink}
@@ -327,7 +419,6 @@ function App() {
           onQuickLinksLimitChange={setQuickLinksLimit}
           onAnimationsChange={setAnimationsEnabled}
           onResetSettings={resetSettings}
-          getSearchUrl={getSearchUrl}
         />
       </>
     );
@@ -340,6 +431,9 @@ function App() {
         initialUrl={url} 
         onClose={handleClose}
         onNewWindow={handleNewWindow}
+        onNewTab={handleNewTab}
+        onNewPrivateWindow={handleNewPrivateWindow}
+        onReopenClosedWindow={handleReopenClosedWindow}
         isMiniPanelOpen={showMiniPanel}
         contentWindows={contentWindows}
         activeWindowIndex={activeWindowIndex}
@@ -349,11 +443,14 @@ function App() {
       />
       <MiniPanel 
         isVisible={showMiniPanel}
-        onNavigate={handleCreateNewWindow}
+        onNavigate={
+          miniPanelTarget === "tab" ? handleCreateNewTab
+            : miniPanelTarget === "private" ? (fullUrl: string) => handleCreateNewWindow(fullUrl, true)
+            : handleCreateNewWindow
+        }
         onClose={handleCloseMiniPanel}
         bookmarks={bookmarks}
         starredBookmarks={starredBookmarks}
-        getSearchUrl={getSearchUrl}
       />
     </>
   );
@@ -1,11 +1,15 @@
 import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { ChevronLeft, ChevronRight, RotateCw, X, Minus, Square, Plus, Search, Bookmark } from 'lucide-react';
+import { ChevronLeft, ChevronRight, RotateCw, X, Minus, Square, Plus, Search, Bookmark, Check, Columns2, Rows2, ArrowLeftRight, Undo2, EyeOff } from 'lucide-react';
+import { useSearchEngineOffers } from "../hooks/useSearchEngines";
+import { useTabs } from "../hooks/useTabs";
+import { useClosedWindows } from "../hooks/useClosedWindows";
 
 interface ContentWindow {
   windowLabel: string;
   url: string;
+  private?: boolean;
 }
 
 interface DockProps {
@@ -13,6 +17,9 @@ interface DockProps {
   initialUrl: string;
   onClose: () => void;
   onNewWindow: () => void;
+  onNewTab: () => void;
+  onNewPrivateWindow: () => void;
+  onReopenClosedWindow: () => void;
   isMiniPanelOpen?: boolean;
   contentWindows: ContentWindow[];
   activeWindowIndex: number;
@@ -26,23 +33,41 @@ interface UrlChangedPayload {
   windowLabel: string;
 }
 
-export function Dock({ activeContentWindow, initialUrl, onClose, onNewWindow, isMiniPanelOpen = false, contentWindows, activeWindowIndex, onSwitchWindow, onAddBookmark, isBookmarked = false }: DockProps) {
+// A window.open() without a click, held back by the Rust `popup_blocker` module
+interface BlockedPopup {
+  windowLabel: string;
+  url: string;
+  // Allowlist entry for the page that asked, null if it has no host
+  site: string | null;
+}
+
+export function Dock({ activeContentWindow, initialUrl, onClose, onNewWindow, onNewTab, onNewPrivateWindow, onReopenClosedWindow, isMiniPanelOpen = false, contentWindows, activeWindowIndex, onSwitchWindow, onAddBookmark, isBookmarked = false }: DockProps) {
   const [url, setUrl] = useState(initialUrl);
   const [isEditing, setIsEditing] = useState(false);
   const isEditingRef = useRef(isEditing);
+  const { offers, acceptOffer, ignoreOffer } = useSearchEngineOffers();
+  const closedWindows = useClosedWindows();
+  const { tabs, activeLabel, split, hibernatedLabels, activateTab, closeTab, moveTab, enterSplitView, leaveSplitView, swapSplitPanes, resizeSplitDivider } = useTabs(activeContentWindow);
+  const [draggedTab, setDraggedTab] = useState<string | null>(null);
+  const engineOffer = offers.find((offer) => offer.windowLabel === activeContentWindow);
+  const [blockedPopups, setBlockedPopups] = useState<BlockedPopup[]>([]);
+  const blockedPopup = blockedPopups.find((popup) => popup.windowLabel === activeContentWindow);
 
   // Keep ref in sync with state
   useEffect(() => {
     isEditingRef.current = isEditing;
   }, [isEditing]);
 
-  // Set up URL monitor and listen for URL change events
+  // Sync with the active content window and listen for URL change events
   useEffect(() => {
     if (!activeContentWindow) return;
 
-    // Set up the URL monitor in the content window
-    invoke("setup_url_monitor", { windowLabel: activeContentWindow })
-      .catch(err => console.error("Failed to setup URL monitor:", err));
+    // The backend monitors every content webview; fetch the current URL when switching windows
+    invoke<{ url: string }>("get_page_state", { windowLabel: activeContentWindow })
+      .then(state => {
+        if (!isEditingRef.current) setUrl(state.url);
+      })
+      .catch(err => console.error("Failed to get page state:", err));
 
     // Listen for URL change events
     const unlistenPromise = listen<UrlChangedPayload>("url-changed", (event) => {
@@ -57,23 +82,50 @@ export function Dock({ activeContentWindow, initialUrl, onClose, onNewWindow, is
     };
   }, [activeContentWindow]); // Only re-run when content window changes
 
+  // Keep the latest blocked popup per window until it's answered or the page moves on
+  useEffect(() => {
+    const unlistenBlocked = listen<BlockedPopup>("popup-blocked", (event) => {
+      setBlockedPopups((current) => [
+        ...current.filter((popup) => popup.windowLabel !== event.payload.windowLabel),
+        event.payload,
+      ]);
+    });
+    const unlistenUrl = listen<UrlChangedPayload>("url-changed", (event) => {
+      setBlockedPopups((current) => current.filter((popup) => popup.windowLabel !== event.payload.windowLabel));
+    });
+
+    return () => {
+      unlistenBlocked.then(unlisten => unlisten());
+      unlistenUrl.then(unlisten => unlisten());
+    };
+  }, []);
+
+  const dismissBlockedPopup = (popup: BlockedPopup) => {
+    setBlockedPopups((current) => current.filter((other) => other !== popup));
+  };
+
+  const handleOpenBlockedPopup = (popup: BlockedPopup) => {
+    dismissBlockedPopup(popup);
+    invoke("open_blocked_popup", { windowLabel: popup.windowLabel, url: popup.url })
+      .catch(err => console.error("Failed to open popup:", err));
+  };
+
+  const handleAllowPopups = (popup: BlockedPopup) => {
+    if (!popup.site) return;
+    dismissBlockedPopup(popup);
+    invoke("allow_popups_for_site", { windowLabel: popup.windowLabel, site: popup.site, url: popup.url })
+      .catch(err => console.error("Failed to allow popups:", err));
+  };
+
   const handleNavigate = async (e: React.FormEvent) => {
     e.preventDefault();
     if (!activeContentWindow) return;
 
-    let fullUrl = url.trim();
-
-    const isUrl = fullUrl.includes('.') && !fullUrl.includes(' ') ||
-                   fullUrl.startsWith("http://") ||
-                   fullUrl.startsWith("https://");
+    const trimmedUrl = url.trim();
+    if (!trimmedUrl) return;
 
-    if (isUrl) {
-      if (!fullUrl.startsWith("http://") && !fullUrl.startsWith("https://")) {
-        fullUrl = "https://" + fullUrl;
-      }
-    } else {
-      fullUrl = `https://www.google.com/search?q=${encodeURIComponent(fullUrl)}`;
-    }
+    // URL vs search classification and the search engine are resolved in Rust
+    const { url: fullUrl } = await invoke<{ kind: string; url: string }>("resolve_omnibox_input", { input: trimmedUrl });
 
     await invoke("navigate_to_url", { windowLabel: activeContentWindow, url: fullUrl });
     setIsEditing(false);
@@ -137,6 +189,14 @@ export function Dock({ activeContentWindow, initialUrl, onClose, onNewWindow, is
           >
             <Plus className="w-[clamp(0.75rem,3vw,1rem)] h-[clamp(0.75rem,3vw,1rem)]" />
           </button>
+          <button
+            onClick={onNewPrivateWindow}
+            disabled={isMiniPanelOpen}
+            className={`p-[clamp(0.25rem,0.75vw,0.5rem)] rounded transition-colors text-[var(--color-text-primary)] ${isMiniPanelOpen ? 'opacity-40' : 'hover:bg-[var(--color-bg-secondary)]'}`}
+            title="New Private Window"
+          >
+            <EyeOff className="w-[clamp(0.75rem,3vw,1rem)] h-[clamp(0.75rem,3vw,1rem)]" />
+          </button>
           <button
             onClick={handleReload}
             className="p-[clamp(0.25rem,0.75vw,0.5rem)] rounded hover:bg-[var(--color-bg-secondary)] text-[var(--color-text-primary)] transition-colors"
@@ -205,22 +265,180 @@ export function Dock({ activeContentWindow, initialUrl, onClose, onNewWindow, is
               }
             }}
           >
-            {contentWindows.map((_, index) => (
+            {contentWindows.map((contentWindow, index) => (
               <button
                 key={index}
                 onClick={() => onSwitchWindow(index)}
                 className={`w-[clamp(0.5rem,2vw,0.75rem)] h-[clamp(0.5rem,2vw,0.75rem)] rounded-full transition-colors hover:scale-110 ${
                   index === activeWindowIndex ? 'bg-[var(--color-accent)]' : 'bg-[var(--color-accent)]/30 hover:bg-[var(--color-accent)]/50'
-                }`}
-                title={`Window ${index + 1}`}
+                } ${contentWindow.private ? 'ring-2 ring-offset-1 ring-[var(--color-text-primary)]' : ''}`}
+                title={contentWindow.private ? `Private window ${index + 1}` : `Window ${index + 1}`}
               />
             ))}
           </div>
+          {/* Tabs of the active window */}
+          <div className="flex items-center gap-[clamp(0.125rem,0.5vw,0.25rem)] min-w-0 overflow-hidden">
+            {tabs.map((tab, index) => (
+              <div
+                key={tab.windowLabel}
+                draggable
+                onDragStart={() => setDraggedTab(tab.windowLabel)}
+                onDragOver={(e) => e.preventDefault()}
+                onDrop={() => {
+                  if (draggedTab && draggedTab !== tab.windowLabel) moveTab(draggedTab, index);
+                  setDraggedTab(null);
+                }}
+                onClick={() => activateTab(tab.windowLabel)}
+                onAuxClick={(e) => {
+                  if (e.button === 1) closeTab(tab.windowLabel);
+                }}
+                className={`group/tab flex items-center gap-1 min-w-0 max-w-[10rem] px-1.5 py-0.5 rounded cursor-pointer text-[clamp(0.65rem,3vw,0.75rem)] transition-colors ${
+                  tab.windowLabel === activeLabel
+                    ? 'bg-[var(--color-bg-secondary)] text-[var(--color-text-primary)]'
+                    : 'text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)]/60'
+                } ${hibernatedLabels.includes(tab.windowLabel) ? 'opacity-60' : ''}`}
+                title={tab.title || tab.url}
+              >
+                {tab.faviconUrl && (
+                  <img src={tab.faviconUrl} alt="" className="w-3 h-3 flex-shrink-0" onError={(e) => { e.currentTarget.style.display = 'none'; }} />
+                )}
+                <span className="truncate">{tab.title || tab.url || "New tab"}</span>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    closeTab(tab.windowLabel);
+                  }}
+                  className="flex-shrink-0 opacity-0 group-hover/tab:opacity-100 rounded hover:bg-[var(--color-text-secondary)]/20"
+                  title="Close tab"
+                >
+                  <X className="w-3 h-3" />
+                </button>
+              </div>
+            ))}
+            {activeContentWindow && (
+              <button
+                onClick={onNewTab}
+                disabled={isMiniPanelOpen}
+                className={`flex-shrink-0 p-0.5 rounded text-[var(--color-text-secondary)] transition-colors ${isMiniPanelOpen ? 'opacity-40' : 'hover:bg-[var(--color-bg-secondary)]'}`}
+                title="New Tab"
+              >
+                <Plus className="w-3 h-3" />
+              </button>
+            )}
+            {/* Split view of the active window */}
+            {split ? (
+              <div className="flex items-center gap-0.5 flex-shrink-0">
+                <button
+                  onClick={() => enterSplitView(split.orientation === "columns" ? "rows" : "columns")}
+                  className="p-0.5 rounded text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)]"
+                  title={split.orientation === "columns" ? "Stack panes" : "Panes side by side"}
+                >
+                  {split.orientation === "columns" ? <Rows2 className="w-3 h-3" /> : <Columns2 className="w-3 h-3" />}
+                </button>
+                <button
+                  onClick={() => swapSplitPanes(0, 1)}
+                  className="p-0.5 rounded text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)]"
+                  title="Swap panes"
+                >
+                  <ArrowLeftRight className="w-3 h-3" />
+                </button>
+                <input
+                  type="range"
+                  min={10}
+                  max={90}
+                  value={Math.round(split.shares[0] * 100)}
+                  onChange={(e) => resizeSplitDivider(0, Number(e.target.value) / 100)}
+                  className="w-16 accent-[var(--color-accent)]"
+                  title="Divider"
+                />
+                <button
+                  onClick={leaveSplitView}
+                  className="p-0.5 rounded text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)]"
+                  title="Leave split view"
+                >
+                  <X className="w-3 h-3" />
+                </button>
+              </div>
+            ) : tabs.length > 1 && (
+              <button
+                onClick={() => enterSplitView("columns")}
+                className="flex-shrink-0 p-0.5 rounded text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)]"
+                title="Split view"
+              >
+                <Columns2 className="w-3 h-3" />
+              </button>
+            )}
+          </div>
         </div>
 
         {/* Right Group: Window Controls & Extras */}
         <div className="flex items-center gap-[clamp(0.25rem,0.75vw,0.5rem)]">
+          {blockedPopup && (
+            <div
+              className="flex items-center gap-1 px-2 py-0.5 rounded-[clamp(0.25rem,0.75vw,0.5rem)] bg-[var(--color-bg-secondary)] text-[clamp(0.65rem,3vw,0.75rem)] text-[var(--color-text-primary)]"
+              title={blockedPopup.url}
+            >
+              <span className="whitespace-nowrap">Popup blocked</span>
+              <button
+                onClick={() => handleOpenBlockedPopup(blockedPopup)}
+                className="px-1 rounded hover:bg-[var(--color-text-secondary)]/20 text-[var(--color-accent)]"
+              >
+                Open
+              </button>
+              {blockedPopup.site && (
+                <button
+                  onClick={() => handleAllowPopups(blockedPopup)}
+                  className="px-1 rounded hover:bg-[var(--color-text-secondary)]/20 text-[var(--color-accent)] whitespace-nowrap"
+                  title={`Always allow popups from ${blockedPopup.site}`}
+                >
+                  Always allow
+                </button>
+              )}
+              <button
+                onClick={() => dismissBlockedPopup(blockedPopup)}
+                className="p-0.5 rounded hover:bg-[var(--color-text-secondary)]/20 text-[var(--color-text-secondary)]"
+                title="Dismiss"
+              >
+                <X className="w-3.5 h-3.5" />
+              </button>
+            </div>
+          )}
+          {engineOffer && (
+            <div
+              className="flex items-center gap-1 px-2 py-0.5 rounded-[clamp(0.25rem,0.75vw,0.5rem)] bg-[var(--color-bg-secondary)] text-[clamp(0.65rem,3vw,0.75rem)] text-[var(--color-text-primary)]"
+              title={engineOffer.engine.description ?? engineOffer.engine.searchUrl}
+            >
+              {engineOffer.engine.iconUrl && (
+                <img src={engineOffer.engine.iconUrl} alt="" className="w-3.5 h-3.5 object-contain" />
+              )}
+              <span className="whitespace-nowrap">Add {engineOffer.engine.name} search?</span>
+              <button
+                onClick={() => acceptOffer(engineOffer.descriptionUrl)}
+                className="p-0.5 rounded hover:bg-[var(--color-text-secondary)]/20 text-[var(--color-accent)]"
+                title="Add search engine"
+              >
+                <Check className="w-3.5 h-3.5" />
+              </button>
+              <button
+                onClick={() => ignoreOffer(engineOffer.descriptionUrl)}
+                className="p-0.5 rounded hover:bg-[var(--color-text-secondary)]/20 text-[var(--color-text-secondary)]"
+                title="Don't ask again for this site"
+              >
+                <X className="w-3.5 h-3.5" />
+              </button>
+            </div>
+          )}
           <div className="flex justify-center items-center gap-[clamp(0.125rem,0.5vw,0.25rem)]">
+            {closedWindows.length > 0 && (
+              <button
+                onClick={onReopenClosedWindow}
+                disabled={isMiniPanelOpen}
+                className="w-[clamp(1.25rem,2vw,1.75rem)] h-[clamp(1rem,1.75vw,1.5rem)] hover:bg-[var(--color-text-secondary)]/20 hover:backdrop-blur-md rounded-[clamp(0.75rem,1.5vw,1rem)] flex items-center justify-center transition-colors text-[var(--color-text-primary)]"
+                title={`Reopen ${closedWindows[0].title || closedWindows[0].url}`}
+              >
+                <Undo2 className="w-[clamp(0.75rem,3vw,1rem)] h-[clamp(0.75rem,3vw,1rem)]" />
+              </button>
+            )}
             <button
               onClick={handleMinimize}
               className="w-[clamp(1.25rem,2vw,1.75rem)] h-[clamp(1rem,1.75vw,1.5rem)] hover:bg-[var(--color-text-secondary)]/20 hover:backdrop-blur-md rounded-[clamp(0.75rem,1.5vw,1rem)] flex items-center justify-center transition-colors text-[var(--color-text-primary)]"
@@ -1,6 +1,7 @@
 import { useState, useRef, useEffect, useMemo } from "react";
 import { motion, AnimatePresence } from "framer-motion";
 import { X, Search, Globe, Star, ExternalLink, Bookmark } from 'lucide-react';
+import { invoke } from "@tauri-apps/api/core";
 import { Bookmark as BookmarkType } from "../hooks/useBookmarks";
 
 interface MiniPanelProps {
@@ -9,7 +10,6 @@ interface MiniPanelProps {
   onClose: () => void;
   bookmarks: BookmarkType[];
   starredBookmarks: BookmarkType[];
-  getSearchUrl: (query: string) => string;
 }
 
 export function MiniPanel({ 
@@ -17,8 +17,7 @@ export function MiniPanel({
   onNavigate, 
   onClose, 
   bookmarks,
-  starredBookmarks,
-  getSearchUrl
+  starredBookmarks
 }: MiniPanelProps) {
   const [searchQuery, setSearchQuery] = useState("");
   const inputRef = useRef<HTMLInputElement>(null);
@@ -43,24 +42,13 @@ export function MiniPanel({
     }
   };
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     const trimmedQuery = searchQuery.trim();
     if (!trimmedQuery) return;
 
-    let fullUrl = trimmedQuery;
-
-    const isUrl = fullUrl.includes('.') && !fullUrl.includes(' ') ||
-                   fullUrl.startsWith("http://") ||
-                   fullUrl.startsWith("https://");
-
-    if (isUrl) {
-      if (!fullUrl.startsWith("http://") && !fullUrl.startsWith("https://")) {
-        fullUrl = "https://" + fullUrl;
-      }
-    } else {
-      fullUrl = getSearchUrl(trimmedQuery);
-    }
+    // URL vs search classification and the search engine are resolved in Rust
+    const { url: fullUrl } = await invoke<{ kind: string; url: string }>("resolve_omnibox_input", { input: trimmedQuery });
 
     onNavigate(fullUrl);
     setSearchQuery("");
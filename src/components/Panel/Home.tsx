@@ -14,10 +14,38 @@ import {
   Bookmark as BookmarkIcon,
   History,
   Download,
-  EyeOff
+  EyeOff,
+  AppWindow,
+  Calculator
 } from "lucide-react";
 import { Bookmark } from "../../hooks/useBookmarks";
-import { Settings as SettingsType, SEARCH_ENGINES } from "../../hooks/useSettings";
+import { Settings as SettingsType } from "../../hooks/useSettings";
+import { SearchEngine } from "../../hooks/useSearchEngines";
+
+// Instant answer computed locally by the backend
+export interface Answer {
+  kind: "calculation" | "unit-conversion" | "time-zone";
+  value: string;
+  display: string;
+}
+
+// Typed omnibox entry from `get_search_suggestions`
+export interface Suggestion {
+  kind: "search" | "history" | "bookmark" | "switch-to-window" | "answer";
+  title: string;
+  url: string | null;
+  windowLabel: string | null;
+  answer: Answer | null;
+  score: number;
+}
+
+const SUGGESTION_ICONS = {
+  search: Search,
+  history: Clock,
+  bookmark: BookmarkIcon,
+  "switch-to-window": AppWindow,
+  answer: Calculator,
+};
 
 interface HomeTabProps {
   onNavigate: (url: string) => void;
@@ -27,7 +55,7 @@ interface HomeTabProps {
   onOpenSettings: () => void;
   onOpenBookmarks: () => void;
   settings: SettingsType;
-  getSearchUrl: (query: string) => string;
+  searchEngines: SearchEngine[];
 }
 
 // Quick Actions toolbar items
@@ -40,6 +68,11 @@ const QUICK_ACTIONS = [
   { id: 'incognito', icon: EyeOff, label: 'Incognito' },
 ];
 
+// Suggestion request IDs must keep increasing for this webview, even across remounts and
+// reloads, because the backend drops anything older than the newest ID it has seen
+let lastSuggestionRequestId = Date.now();
+const nextSuggestionRequestId = () => ++lastSuggestionRequestId;
+
 export default function HomeTab({ 
   onNavigate, 
   onQuickLink, 
@@ -48,20 +81,25 @@ export default function HomeTab({
   onOpenSettings,
   onOpenBookmarks,
   settings,
-  getSearchUrl
+  searchEngines
 }: HomeTabProps) {
   const [url, setUrl] = useState("");
   const [isFocused, setIsFocused] = useState(false);
   const [currentTime, setCurrentTime] = useState(new Date());
   const [showComingSoon, setShowComingSoon] = useState(false);
-  const [suggestions, setSuggestions] = useState<string[]>([]);
+  const [suggestions, setSuggestions] = useState<Suggestion[]>([]);
   const [selectedIndex, setSelectedIndex] = useState(-1);
   const [showSuggestions, setShowSuggestions] = useState(false);
   const inputRef = useRef<HTMLInputElement>(null);
   const suggestionsRef = useRef<HTMLDivElement>(null);
+  const suggestionRequestId = useRef(0);
+  const searchEngineName = searchEngines.find((engine) => engine.id === settings.searchEngine)?.name ?? "the web";
 
   // Debounced search suggestions fetch
   useEffect(() => {
+    const requestId = nextSuggestionRequestId();
+    suggestionRequestId.current = requestId;
+
     if (!url.trim() || url.trim().length < 2) {
       setSuggestions([]);
       setShowSuggestions(false);
@@ -70,12 +108,18 @@ export default function HomeTab({
 
     const timer = setTimeout(async () => {
       try {
-        const results = await invoke<string[]>("get_search_suggestions", { query: url });
-        setSuggestions(results);
-        setShowSuggestions(results.length > 0);
+        const response = await invoke<{ requestId: number; suggestions: Suggestion[] }>(
+          "get_search_suggestions",
+          // Home always opens a new regular window, so there's no private window to protect
+          { query: url, requestId, windowLabel: null }
+        );
+        if (response.requestId !== suggestionRequestId.current) return;
+        setSuggestions(response.suggestions);
+        setShowSuggestions(response.suggestions.length > 0);
         setSelectedIndex(-1);
       } catch {
-        setSuggestions([]);
+        // Superseded requests are cancelled by the backend - nothing to clear
+        if (requestId === suggestionRequestId.current) setSuggestions([]);
       }
     }, 250);
 
@@ -111,30 +155,33 @@ export default function HomeTab({
     navigateToUrl(trimmedUrl);
   };
 
-  const navigateToUrl = (query: string) => {
-    let fullUrl = query;
-
-    const isUrl =
-      (fullUrl.includes(".") && !fullUrl.includes(" ")) ||
-      fullUrl.startsWith("http://") ||
-      fullUrl.startsWith("https://");
-
-    if (isUrl) {
-      if (!fullUrl.startsWith("http://") && !fullUrl.startsWith("https://")) {
-        fullUrl = "https://" + fullUrl;
-      }
-    } else {
-      fullUrl = getSearchUrl(fullUrl);
-    }
+  const navigateToUrl = async (query: string) => {
+    // URL vs search classification and the search engine are resolved in Rust
+    const { url: fullUrl } = await invoke<{ kind: string; url: string }>("resolve_omnibox_input", { input: query });
 
     setShowSuggestions(false);
     setSuggestions([]);
     onNavigate(fullUrl);
   };
 
-  const handleSelectSuggestion = (suggestion: string) => {
-    setUrl(suggestion);
-    navigateToUrl(suggestion);
+  const handleSelectSuggestion = (suggestion: Suggestion) => {
+    if (suggestion.kind === "switch-to-window" && suggestion.windowLabel) {
+      setShowSuggestions(false);
+      invoke("show_browser_window", { windowLabel: suggestion.windowLabel })
+        .catch((error) => console.error("Failed to switch window:", error));
+      return;
+    }
+    if (suggestion.kind === "answer" && suggestion.answer) {
+      // Put the result in the omnibox and on the clipboard instead of searching for it
+      setShowSuggestions(false);
+      setUrl(suggestion.answer.value);
+      navigator.clipboard?.writeText(suggestion.answer.value)
+        .catch((error) => console.error("Failed to copy answer:", error));
+      return;
+    }
+    const target = suggestion.url ?? suggestion.title;
+    setUrl(target);
+    navigateToUrl(target);
   };
 
   const handleKeyDown = (e: React.KeyboardEvent) => {
@@ -366,7 +413,7 @@ export default function HomeTab({
                     }}
                     onKeyDown={handleKeyDown}
                     className="flex-1 bg-transparent text-[clamp(0.7rem,1.5vw,0.875rem)] focus:outline-none placeholder-[var(--color-text-secondary)]/40"
-                    placeholder={`Search ${SEARCH_ENGINES[settings.searchEngine].name} or enter URL...`}
+                    placeholder={`Search ${searchEngineName} or enter URL...`}
                     autoFocus
                   />
                   {url && (
@@ -392,9 +439,11 @@ export default function HomeTab({
                       transition={{ duration: 0.15 }}
                       className="absolute top-full left-0 right-0 mt-2 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded-[clamp(0.5rem,1.5vw,0.75rem)] shadow-xl overflow-hidden z-50"
                     >
-                      {suggestions.map((suggestion, index) => (
+                      {suggestions.map((suggestion, index) => {
+                        const Icon = SUGGESTION_ICONS[suggestion.kind];
+                        return (
                         <button
-                          key={index}
+                          key={`${suggestion.kind}:${suggestion.url ?? suggestion.title}`}
                           type="button"
                           onClick={() => handleSelectSuggestion(suggestion)}
                           className={`w-full flex items-center gap-[clamp(0.375rem,1vw,0.75rem)] px-[clamp(0.5rem,1.5vw,1rem)] py-[clamp(0.375rem,1vw,0.625rem)] text-left text-[clamp(0.65rem,1.25vw,0.875rem)] transition-colors ${
@@ -403,10 +452,20 @@ export default function HomeTab({
                               : 'text-[var(--color-text-primary)] hover:bg-[var(--color-bg-secondary)]'
                           }`}
                         >
-                          <Search className="w-[clamp(0.625rem,1.25vw,0.875rem)] h-[clamp(0.625rem,1.25vw,0.875rem)] text-[var(--color-text-secondary)] shrink-0" />
-                          <span className="truncate">{suggestion}</span>
+                          <Icon className="w-[clamp(0.625rem,1.25vw,0.875rem)] h-[clamp(0.625rem,1.25vw,0.875rem)] text-[var(--color-text-secondary)] shrink-0" />
+                          <span className="truncate">{suggestion.title}</span>
+                          {suggestion.kind === "switch-to-window" && (
+                            <span className="ml-auto shrink-0 text-[clamp(0.55rem,1vw,0.75rem)] text-[var(--color-text-secondary)]">Switch to window</span>
+                          )}
+                          {suggestion.kind === "answer" && (
+                            <span className="ml-auto shrink-0 text-[clamp(0.55rem,1vw,0.75rem)] text-[var(--color-text-secondary)]">Copy result</span>
+                          )}
+                          {(suggestion.kind === "history" || suggestion.kind === "bookmark") && suggestion.url && (
+                            <span className="ml-auto truncate max-w-[40%] text-[clamp(0.55rem,1vw,0.75rem)] text-[var(--color-text-secondary)]">{suggestion.url}</span>
+                          )}
                         </button>
-                      ))}
+                        );
+                      })}
                     </motion.div>
                   )}
                 </AnimatePresence>
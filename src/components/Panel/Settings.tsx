@@ -17,25 +17,29 @@ import {
   Download,
   Upload,
   Check,
+  Plus,
   X
 } from "lucide-react";
-import { Settings as SettingsType, SearchEngine, SEARCH_ENGINES, Theme } from "../../hooks/useSettings";
-import { Bookmark } from "../../hooks/useBookmarks";
-import { save, open } from "@tauri-apps/plugin-dialog";
-import { writeTextFile, readTextFile } from "@tauri-apps/plugin-fs";
+import { Settings as SettingsType, Theme } from "../../hooks/useSettings";
+import { SearchEngine, NewSearchEngine, engineIcon } from "../../hooks/useSearchEngines";
+import { Bookmark, ImportReport } from "../../hooks/useBookmarks";
 
 interface SettingsProps {
   onBack: () => void;
   settings: SettingsType;
   onThemeChange: (theme: Theme) => void;
-  onSearchEngineChange: (engine: SearchEngine) => void;
+  onSearchEngineChange: (engineId: string) => void;
+  searchEngines: SearchEngine[];
+  onAddSearchEngine: (engine: NewSearchEngine) => Promise<SearchEngine>;
+  onRemoveSearchEngine: (id: string) => void;
   onQuickLinksLimitChange: (limit: number) => void;
   onAnimationsChange: (enabled: boolean) => void;
   onResetSettings: () => void;
   // Bookmark operations
   bookmarks: Bookmark[];
   onClearBookmarks: () => void;
-  onImportBookmarks: (bookmarks: Bookmark[]) => void;
+  onImportBookmarks: () => Promise<ImportReport | null>;
+  onExportBookmarks: () => Promise<boolean>;
 }
 
 export default function Settings({ 
@@ -43,14 +47,20 @@ export default function Settings({
   settings,
   onThemeChange,
   onSearchEngineChange,
+  searchEngines,
+  onAddSearchEngine,
+  onRemoveSearchEngine,
   onQuickLinksLimitChange,
   onAnimationsChange,
   onResetSettings,
   bookmarks,
   onClearBookmarks,
-  onImportBookmarks
+  onImportBookmarks,
+  onExportBookmarks
 }: SettingsProps) {
   const [showSearchEngineModal, setShowSearchEngineModal] = useState(false);
+  const [newEngine, setNewEngine] = useState<NewSearchEngine | null>(null);
+  const [newEngineError, setNewEngineError] = useState<string | null>(null);
   const [showQuickLinksModal, setShowQuickLinksModal] = useState(false);
   const [showClearConfirm, setShowClearConfirm] = useState<"bookmarks" | "cache" | "data" | null>(null);
   const [notification, setNotification] = useState<{ type: "success" | "error"; message: string } | null>(null);
@@ -63,13 +73,7 @@ export default function Settings({
 
   const handleExportBookmarks = async () => {
     try {
-      const filePath = await save({
-        defaultPath: "meikai-bookmarks.json",
-        filters: [{ name: "JSON", extensions: ["json"] }],
-      });
-      
-      if (filePath) {
-        await writeTextFile(filePath, JSON.stringify(bookmarks, null, 2));
+      if (await onExportBookmarks()) {
         showNotification("success", "Bookmarks exported successfully!");
       }
     } catch (error) {
@@ -80,21 +84,16 @@ export default function Settings({
 
   const handleImportBookmarks = async () => {
     try {
-      const filePath = await open({
-        filters: [{ name: "JSON", extensions: ["json"] }],
-        multiple: false,
-      });
-      
-      if (filePath && typeof filePath === "string") {
-        const content = await readTextFile(filePath);
-        const imported = JSON.parse(content) as Bookmark[];
-        
-        if (Array.isArray(imported) && imported.every(b => b.id && b.name && b.url)) {
-          onImportBookmarks(imported);
-          showNotification("success", `Imported ${imported.length} bookmarks!`);
-        } else {
-          showNotification("error", "Invalid bookmark file format");
-        }
+      const report = await onImportBookmarks();
+      if (report) {
+        const skipped = [
+          report.duplicates.length && `${report.duplicates.length} duplicates`,
+          report.invalid.length && `${report.invalid.length} invalid`,
+        ].filter(Boolean).join(", ");
+        showNotification(
+          "success",
+          `Imported ${report.imported} bookmarks${skipped ? ` (skipped ${skipped})` : ""}`
+        );
       }
     } catch (error) {
       console.error("Import failed:", error);
@@ -102,6 +101,21 @@ export default function Settings({
     }
   };
 
+  const isCustomEngine = (id: string) => settings.customSearchEngines.some((engine) => engine.id === id);
+
+  const handleAddSearchEngine = async () => {
+    if (!newEngine) return;
+    try {
+      await onAddSearchEngine(newEngine);
+      setNewEngine(null);
+      setNewEngineError(null);
+    } catch (error) {
+      // Validation errors come back as { kind, message }
+      const message = (error as { message?: string }).message;
+      setNewEngineError(message ?? String(error));
+    }
+  };
+
   const handleClearBookmarks = () => {
     onClearBookmarks();
     setShowClearConfirm(null);
@@ -236,7 +250,7 @@ export default function Settings({
                 Search Engine
               </span>
               <span className="font-poppins text-xs text-[var(--color-text-secondary)]">
-                {SEARCH_ENGINES[settings.searchEngine].name}
+                {searchEngines.find((engine) => engine.id === settings.searchEngine)?.name}
               </span>
               <ChevronRight className="w-4 h-4 text-gray-300 group-hover:text-gray-400 transition-colors" />
             </button>
@@ -418,29 +432,100 @@ export default function Settings({
                 <h3 className="font-poppins font-semibold text-[var(--color-text-primary)]">Search Engine</h3>
               </div>
               <div className="p-3">
-                {(Object.keys(SEARCH_ENGINES) as SearchEngine[]).map((engine) => (
+                {searchEngines.map((engine) => (
                   <button
-                    key={engine}
+                    key={engine.id}
                     onClick={() => {
-                      onSearchEngineChange(engine);
+                      onSearchEngineChange(engine.id);
                       setShowSearchEngineModal(false);
                     }}
-                    className={`w-full flex items-center gap-3 px-4 py-3 rounded-xl transition-all ${
-                      settings.searchEngine === engine 
+                    className={`w-full flex items-center gap-3 px-4 py-3 rounded-xl transition-all group ${
+                      settings.searchEngine === engine.id 
                         ? "bg-[var(--color-accent)]/10 border border-[var(--color-accent)]/30" 
                         : "hover:bg-[var(--color-bg-secondary)]"
                     }`}
                   >
-                    <span className="text-lg">{SEARCH_ENGINES[engine].icon}</span>
+                    {engine.iconUrl ? (
+                      <img src={engine.iconUrl} alt="" className="w-5 h-5 object-contain" />
+                    ) : (
+                      <span className="text-lg">{engineIcon(engine)}</span>
+                    )}
                     <span className="flex-1 text-left font-poppins text-sm text-[var(--color-text-primary)]">
-                      {SEARCH_ENGINES[engine].name}
+                      {engine.name}
                     </span>
-                    {settings.searchEngine === engine && (
+                    {engine.keyword && (
+                      <span className="font-poppins text-xs text-[var(--color-text-secondary)]">{engine.keyword}</span>
+                    )}
+                    {settings.searchEngine === engine.id && (
                       <Check className="w-4 h-4 text-[var(--color-accent)]" />
                     )}
+                    {isCustomEngine(engine.id) && (
+                      <span
+                        role="button"
+                        aria-label={`Remove ${engine.name}`}
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          onRemoveSearchEngine(engine.id);
+                        }}
+                        className="text-gray-300 hover:text-red-500 transition-colors"
+                      >
+                        <Trash2 className="w-4 h-4" />
+                      </span>
+                    )}
                   </button>
                 ))}
               </div>
+              <div className="px-6 pb-4">
+                {newEngine ? (
+                  <div className="space-y-2">
+                    {([
+                      ["name", "Name"],
+                      ["keyword", "Keyword (optional), e.g. gh"],
+                      ["searchUrl", "URL with {searchTerms}"],
+                      ["suggestUrl", "Suggestion URL (optional)"],
+                    ] as const).map(([field, placeholder]) => (
+                      <input
+                        key={field}
+                        value={newEngine[field] ?? ""}
+                        placeholder={placeholder}
+                        onChange={(e) => setNewEngine({ ...newEngine, [field]: e.target.value })}
+                        className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] font-poppins text-xs text-[var(--color-text-primary)] focus:outline-none"
+                      />
+                    ))}
+                    {newEngineError && (
+                      <p className="font-poppins text-xs text-red-500">{newEngineError}</p>
+                    )}
+                    <div className="flex gap-2">
+                      <button
+                        onClick={() => {
+                          setNewEngine(null);
+                          setNewEngineError(null);
+                        }}
+                        className="flex-1 px-3 py-2 rounded-lg font-poppins text-xs text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)] transition-all"
+                      >
+                        Cancel
+                      </button>
+                      <button
+                        onClick={handleAddSearchEngine}
+                        className="flex-1 px-3 py-2 rounded-lg font-poppins text-xs bg-[var(--color-accent)] text-white hover:opacity-90 transition-all"
+                      >
+                        Add
+                      </button>
+                    </div>
+                  </div>
+                ) : (
+                  <button
+                    onClick={() => setNewEngine({ name: "", keyword: null, searchUrl: "", suggestUrl: null })}
+                    className="w-full flex items-center gap-2 px-4 py-2 rounded-xl font-poppins text-sm text-[var(--color-text-secondary)] hover:bg-[var(--color-bg-secondary)] transition-all"
+                  >
+                    <Plus className="w-4 h-4" />
+                    Add search engine
+                  </button>
+                )}
+                <p className="mt-2 font-poppins text-[11px] text-[var(--color-text-secondary)]">
+                  Type a keyword before your query, or a bang like !gh, !w or !yt before or after it.
+                </p>
+              </div>
             </motion.div>
           </motion.div>
         )}
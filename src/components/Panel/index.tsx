@@ -9,7 +9,8 @@ import Profile from "./Profile";
 import Settings from "./Settings";
 import Tray from "./Tray";
 import { useBookmarks } from "../../hooks/useBookmarks";
-import { Settings as SettingsType, Theme, SearchEngine } from "../../hooks/useSettings";
+import { useSearchEngines } from "../../hooks/useSearchEngines";
+import { Settings as SettingsType, Theme } from "../../hooks/useSettings";
 
 interface PanelProps {
   onNavigate: (url: string) => void;
@@ -17,11 +18,10 @@ interface PanelProps {
   // Settings passed from App level
   settings: SettingsType;
   onThemeChange: (theme: Theme) => void;
-  onSearchEngineChange: (engine: SearchEngine) => void;
+  onSearchEngineChange: (engineId: string) => void;
   onQuickLinksLimitChange: (limit: number) => void;
   onAnimationsChange: (enabled: boolean) => void;
   onResetSettings: () => void;
-  getSearchUrl: (query: string) => string;
 }
 
 type TabView = "home" | "settings" | "profile";
@@ -34,8 +34,7 @@ export function Panel({
   onSearchEngineChange,
   onQuickLinksLimitChange,
   onAnimationsChange,
-  onResetSettings,
-  getSearchUrl
+  onResetSettings
 }: PanelProps) {
   const [showTray, setShowTray] = useState(false);
   const [activeView, setActiveView] = useState<TabView>("home");
@@ -49,9 +48,13 @@ export function Panel({
     deleteBookmark, 
     toggleStar,
     clearAllBookmarks,
-    importBookmarks
+    importBookmarks,
+    exportBookmarks
   } = useBookmarks();
 
+  // Built-in and custom search engines, owned by the backend settings
+  const { engines, addSearchEngine, removeSearchEngine } = useSearchEngines();
+
   // Handle scroll to show/hide tray (ONLY on home view)
   useEffect(() => {
     // Close tray when leaving home view
@@ -89,7 +92,7 @@ export function Panel({
             onOpenSettings={() => setActiveView("settings")}
             onOpenBookmarks={() => setShowTray(true)}
             settings={settings}
-            getSearchUrl={getSearchUrl}
+            searchEngines={engines}
           />
         );
       case "profile":
@@ -108,12 +111,16 @@ export function Panel({
             settings={settings}
             onThemeChange={onThemeChange}
             onSearchEngineChange={onSearchEngineChange}
+            searchEngines={engines}
+            onAddSearchEngine={addSearchEngine}
+            onRemoveSearchEngine={removeSearchEngine}
             onQuickLinksLimitChange={onQuickLinksLimitChange}
             onAnimationsChange={onAnimationsChange}
             onResetSettings={onResetSettings}
             bookmarks={bookmarks}
             onClearBookmarks={clearAllBookmarks}
             onImportBookmarks={importBookmarks}
+            onExportBookmarks={exportBookmarks}
           />
         );
       default:
@@ -126,7 +133,7 @@ export function Panel({
             onOpenSettings={() => setActiveView("settings")}
             onOpenBookmarks={() => setShowTray(true)}
             settings={settings}
-            getSearchUrl={getSearchUrl}
+            searchEngines={engines}
           />
         );
     }
@@ -0,0 +1,45 @@
+import { motion } from "framer-motion";
+import { History, X } from "lucide-react";
+import { SessionSummary } from "../hooks/useSession";
+
+interface SessionRestorePromptProps {
+  session: SessionSummary;
+  onRestore: () => void;
+  onDismiss: () => void;
+}
+
+const plural = (count: number, word: string) => `${count} ${word}${count === 1 ? "" : "s"}`;
+
+// Offers the windows that were open when Meikai last quit
+export function SessionRestorePrompt({ session, onRestore, onDismiss }: SessionRestorePromptProps) {
+  return (
+    <motion.div
+      initial={{ opacity: 0, y: 20 }}
+      animate={{ opacity: 1, y: 0 }}
+      className="fixed bottom-4 left-1/2 -translate-x-1/2 z-40 flex items-center gap-3 px-4 py-2 rounded-xl font-poppins text-sm"
+      style={{
+        backgroundColor: "var(--color-bg-secondary)",
+        border: "1px solid var(--color-border)",
+        color: "var(--color-text-primary)",
+      }}
+    >
+      <History size={16} style={{ color: "var(--color-accent)" }} />
+      <span className="whitespace-nowrap">
+        Restore {plural(session.windowCount, "window")} ({plural(session.tabCount, "tab")}) from last time?
+      </span>
+      <button
+        onClick={onRestore}
+        className="px-2 py-0.5 rounded-lg bg-[var(--color-accent)] text-white hover:opacity-90 transition-opacity"
+      >
+        Restore
+      </button>
+      <button
+        onClick={onDismiss}
+        className="p-1 rounded-full hover:bg-[var(--color-text-secondary)]/20 text-[var(--color-text-secondary)]"
+        title="Start fresh"
+      >
+        <X size={14} />
+      </button>
+    </motion.div>
+  );
+}
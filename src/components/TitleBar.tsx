@@ -16,7 +16,7 @@ function TitleBar({ windowLabel, initialUrl }: TitleBarProps) {
     
     const updateUrl = async () => {
       try {
-        const currentUrl = await invoke<string>('get_current_url', { windowLabel: contentLabel });
+        const { url: currentUrl } = await invoke<{ url: string }>('get_page_state', { windowLabel: contentLabel });
         if (currentUrl && currentUrl !== url) {
           setUrl(currentUrl);
           // Extract domain for display
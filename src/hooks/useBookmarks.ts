@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
-import { readTextFile, writeTextFile, exists, mkdir, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 export interface Bookmark {
   id: string;
@@ -7,123 +8,135 @@ export interface Bookmark {
   url: string;
   favicon?: string;
   starred?: boolean;
+  parentId?: string | null;
+  position?: number;
+  tags?: string[];
+  createdAt?: number;
+  lastVisitedAt?: number | null;
 }
 
-const BOOKMARKS_FILE = "bookmarks.json";
-
-// Default bookmarks for first-time users (first 4 are starred for quick links)
-const DEFAULT_BOOKMARKS: Bookmark[] = [
-  { id: "1", name: "Google", url: "https://google.com", starred: true },
-  { id: "2", name: "YouTube", url: "https://youtube.com", starred: true },
-  { id: "3", name: "Netflix", url: "https://netflix.com", starred: true },
-  { id: "4", name: "Twitter", url: "https://twitter.com", starred: false },
-  { id: "5", name: "Reddit", url: "https://reddit.com", starred: false },
-  { id: "6", name: "Pinterest", url: "https://pinterest.com", starred: true },
-  { id: "7", name: "Meikai", url: "https://meikai.rhishav.com", starred: true },
-  { id: "8", name: "Rhishav", url: "https://rhishav.com", starred: true },
-];
-
-async function loadBookmarksFromFile(): Promise<Bookmark[]> {
-  try {
-    const fileExists = await exists(BOOKMARKS_FILE, { baseDir: BaseDirectory.AppData });
-    
-    if (!fileExists) {
-      await saveBookmarksToFile(DEFAULT_BOOKMARKS);
-      return DEFAULT_BOOKMARKS;
-    }
-    
-    const content = await readTextFile(BOOKMARKS_FILE, { baseDir: BaseDirectory.AppData });
-    return JSON.parse(content);
-  } catch (error) {
-    console.error("Failed to load bookmarks from file:", error);
-    return DEFAULT_BOOKMARKS;
-  }
+export interface BookmarkFolder {
+  id: string;
+  name: string;
+  parentId: string | null;
+  position: number;
+  createdAt: number;
+}
+
+export interface SkippedBookmark {
+  name: string;
+  url: string;
+  path: string[];
+  reason?: string;
+}
+
+export interface ImportReport {
+  imported: number;
+  foldersCreated: number;
+  duplicates: SkippedBookmark[];
+  invalid: SkippedBookmark[];
 }
 
-async function saveBookmarksToFile(bookmarks: Bookmark[]): Promise<void> {
-  try {
-    // Try to create the AppData directory if it doesn't exist
-    // We do this by simply attempting the write - Tauri creates parent dirs automatically
-    // If that fails, we try to create the directory first
-    try {
-      await writeTextFile(BOOKMARKS_FILE, JSON.stringify(bookmarks, null, 2), { baseDir: BaseDirectory.AppData });
-    } catch {
-      // Directory might not exist, try to create it
-      await mkdir("", { baseDir: BaseDirectory.AppData, recursive: true });
-      await writeTextFile(BOOKMARKS_FILE, JSON.stringify(bookmarks, null, 2), { baseDir: BaseDirectory.AppData });
-    }
-  } catch {
-    // Failed to save bookmarks silently
-  }
+interface BookmarkCollection {
+  folders: BookmarkFolder[];
+  bookmarks: Bookmark[];
 }
 
+// Storage, validation and persistence live in the Rust `bookmarks` module.
+// Every change is broadcast as `bookmarks-changed`, which keeps all windows in sync.
 export function useBookmarks() {
   const [bookmarks, setBookmarks] = useState<Bookmark[]>([]);
+  const [folders, setFolders] = useState<BookmarkFolder[]>([]);
   const [isLoading, setIsLoading] = useState(true);
 
-  // Load bookmarks on mount
+  // Load bookmarks on mount and follow changes made from any window
   useEffect(() => {
-    loadBookmarksFromFile().then((loaded) => {
-      setBookmarks(loaded);
-      setIsLoading(false);
+    const apply = (collection: BookmarkCollection) => {
+      setBookmarks(collection.bookmarks);
+      setFolders(collection.folders);
+    };
+
+    invoke<BookmarkCollection>("get_bookmarks")
+      .then(apply)
+      .catch((error) => console.error("Failed to load bookmarks:", error))
+      .finally(() => setIsLoading(false));
+
+    const unlistenPromise = listen<BookmarkCollection>("bookmarks-changed", (event) => {
+      apply(event.payload);
     });
-  }, []);
 
-  // Save to file whenever bookmarks change (after initial load)
-  useEffect(() => {
-    if (!isLoading) {
-      saveBookmarksToFile(bookmarks);
-    }
-  }, [bookmarks, isLoading]);
-
-  const addBookmark = useCallback((name: string, url: string, starred: boolean = false) => {
-    const newBookmark: Bookmark = {
-      id: Date.now().toString(),
-      name,
-      url: url.startsWith("http") ? url : `https://${url}`,
-      starred,
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
     };
-    setBookmarks((prev) => [...prev, newBookmark]);
+  }, []);
+
+  const addBookmark = useCallback((name: string, url: string, starred: boolean = false, parentId: string | null = null) => {
+    invoke("add_bookmark", { name, url, starred, parentId })
+      .catch((error) => console.error("Failed to add bookmark:", error));
   }, []);
 
   const editBookmark = useCallback((id: string, name: string, url: string) => {
-    setBookmarks((prev) =>
-      prev.map((bookmark) =>
-        bookmark.id === id
-          ? { ...bookmark, name, url: url.startsWith("http") ? url : `https://${url}` }
-          : bookmark
-      )
-    );
+    invoke("update_bookmark", { id, name, url })
+      .catch((error) => console.error("Failed to update bookmark:", error));
   }, []);
 
   const deleteBookmark = useCallback((id: string) => {
-    setBookmarks((prev) => prev.filter((bookmark) => bookmark.id !== id));
+    invoke("delete_bookmark", { id })
+      .catch((error) => console.error("Failed to delete bookmark:", error));
   }, []);
 
   const toggleStar = useCallback((id: string) => {
-    setBookmarks((prev) =>
-      prev.map((bookmark) =>
-        bookmark.id === id
-          ? { ...bookmark, starred: !bookmark.starred }
-          : bookmark
-      )
-    );
+    const bookmark = bookmarks.find((b) => b.id === id);
+    if (!bookmark) return;
+    invoke("set_bookmark_starred", { id, starred: !bookmark.starred })
+      .catch((error) => console.error("Failed to star bookmark:", error));
+  }, [bookmarks]);
+
+  const reorderBookmarks = useCallback((ids: string[], parentId: string | null = null) => {
+    invoke("reorder_bookmarks", { ids, parentId })
+      .catch((error) => console.error("Failed to reorder bookmarks:", error));
+  }, []);
+
+  const setTags = useCallback((id: string, tags: string[]) => {
+    invoke("set_bookmark_tags", { id, tags })
+      .catch((error) => console.error("Failed to tag bookmark:", error));
+  }, []);
+
+  const createFolder = useCallback((name: string, parentId: string | null = null) => {
+    invoke("create_bookmark_folder", { name, parentId })
+      .catch((error) => console.error("Failed to create folder:", error));
+  }, []);
+
+  const renameFolder = useCallback((id: string, name: string) => {
+    invoke("rename_bookmark_folder", { id, name })
+      .catch((error) => console.error("Failed to rename folder:", error));
+  }, []);
+
+  const deleteFolder = useCallback((id: string) => {
+    invoke("delete_bookmark_folder", { id })
+      .catch((error) => console.error("Failed to delete folder:", error));
+  }, []);
+
+  // Works for bookmarks and folders alike
+  const moveItem = useCallback((id: string, parentId: string | null, index?: number) => {
+    invoke("move_bookmark_item", { id, parentId, index })
+      .catch((error) => console.error("Failed to move bookmark:", error));
   }, []);
 
   const clearAllBookmarks = useCallback(() => {
-    setBookmarks([]);
+    invoke("clear_bookmarks")
+      .catch((error) => console.error("Failed to clear bookmarks:", error));
   }, []);
 
-  const importBookmarks = useCallback((imported: Bookmark[]) => {
-    // Merge imported bookmarks with existing, avoiding duplicates by URL
-    setBookmarks((prev) => {
-      const existingUrls = new Set(prev.map(b => b.url));
-      const newBookmarks = imported.filter(b => !existingUrls.has(b.url));
-      return [...prev, ...newBookmarks.map(b => ({
-        ...b,
-        id: Date.now().toString() + Math.random().toString(36).substr(2, 9)
-      }))];
-    });
+  // The backend asks for the file itself: Netscape HTML, Chromium `Bookmarks` or Firefox
+  // `places.sqlite`, and detects which. Resolves to null if the user cancels.
+  const importBookmarks = useCallback(() => {
+    return invoke<ImportReport | null>("import_bookmarks_file");
+  }, []);
+
+  // Resolves to false if the user cancels the save dialog
+  const exportBookmarks = useCallback(() => {
+    return invoke<boolean>("export_bookmarks_html");
   }, []);
 
   // Get only starred bookmarks for quick links
@@ -131,14 +144,22 @@ export function useBookmarks() {
 
   return {
     bookmarks,
+    folders,
     starredBookmarks,
     isLoading,
     addBookmark,
     editBookmark,
     deleteBookmark,
     toggleStar,
+    reorderBookmarks,
+    setTags,
+    createFolder,
+    renameFolder,
+    deleteFolder,
+    moveItem,
     clearAllBookmarks,
     importBookmarks,
+    exportBookmarks,
   };
 }
 
@@ -0,0 +1,36 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// A window the user closed, as listed by the Rust `closed_windows` module
+export interface ClosedWindow {
+  id: string;
+  // Title and URL of the tab that was showing
+  title: string;
+  url: string;
+  tabCount: number;
+  closedAt: number;
+}
+
+// Recently closed windows, newest first
+export function useClosedWindows() {
+  const [closedWindows, setClosedWindows] = useState<ClosedWindow[]>([]);
+
+  useEffect(() => {
+    invoke<ClosedWindow[]>("list_closed_windows")
+      .then(setClosedWindows)
+      .catch((error) => console.error("Failed to load closed windows:", error));
+
+    const unlistenPromise = listen<ClosedWindow[]>("closed-windows-changed", (event) => {
+      setClosedWindows(event.payload);
+    });
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  return closedWindows;
+}
+
+export default useClosedWindows;
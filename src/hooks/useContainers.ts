@@ -0,0 +1,40 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Container, ContainerColor } from "./useSettings";
+
+// Containers live in settings (the Rust `settings` and `containers` modules), so the
+// list is reloaded on every `settings-changed`. Rules are set with the `containerRules` setting.
+export function useContainers() {
+  const [containers, setContainers] = useState<Container[]>([]);
+
+  useEffect(() => {
+    const load = () => {
+      invoke<Container[]>("get_containers")
+        .then(setContainers)
+        .catch((error) => console.error("Failed to load containers:", error));
+    };
+
+    load();
+    const unlistenPromise = listen("settings-changed", load);
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  // Rejects with the backend's validation error so forms can show it
+  const createContainer = useCallback((name: string, color: ContainerColor) =>
+    invoke<Container>("create_container", { name, color }), []);
+
+  const updateContainer = useCallback((container: Container) =>
+    invoke<Container>("update_container", { container }), []);
+
+  // Also drops the container's rules, cookies and storage
+  const deleteContainer = useCallback((id: string) =>
+    invoke("delete_container", { id }), []);
+
+  return { containers, createContainer, updateContainer, deleteContainer };
+}
+
+export default useContainers;
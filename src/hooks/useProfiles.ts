@@ -0,0 +1,51 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// A browser profile, as listed by the Rust `profiles` module
+export interface Profile {
+  id: string;
+  name: string;
+  createdAt: number;
+}
+
+export interface ProfileList {
+  profiles: Profile[];
+  // Profile whose bookmarks, settings and history are loaded; new windows open in it
+  activeId: string;
+}
+
+// Profiles with separate bookmarks, settings, history and webview data
+export function useProfiles() {
+  const [list, setList] = useState<ProfileList>({ profiles: [], activeId: "default" });
+
+  useEffect(() => {
+    invoke<ProfileList>("list_profiles")
+      .then(setList)
+      .catch((error) => console.error("Failed to load profiles:", error));
+
+    const unlistenPromise = listen<ProfileList>("profiles-changed", (event) => {
+      setList(event.payload);
+    });
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  const createProfile = useCallback((name: string) =>
+    invoke<Profile>("create_profile", { name }), []);
+
+  const renameProfile = useCallback((id: string, name: string) =>
+    invoke<Profile>("rename_profile", { id, name }), []);
+
+  const deleteProfile = useCallback((id: string) =>
+    invoke("delete_profile", { id }), []);
+
+  const switchProfile = useCallback((id: string) =>
+    invoke<ProfileList>("switch_profile", { id }), []);
+
+  return { ...list, createProfile, renameProfile, deleteProfile, switchProfile };
+}
+
+export default useProfiles;
@@ -0,0 +1,125 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+export interface SearchEngine {
+  id: string;
+  name: string;
+  // Typed before a query to search this engine once, e.g. "gh rust tauri"; also works as "!gh"
+  keyword: string | null;
+  // Results page with a {searchTerms} placeholder
+  searchUrl: string;
+  // OpenSearch JSON suggestion endpoint with a {searchTerms} placeholder
+  suggestUrl: string | null;
+  // Site icon from an OpenSearch description (http(s) or data: URL)
+  iconUrl: string | null;
+}
+
+export type NewSearchEngine = Omit<SearchEngine, "id" | "iconUrl">;
+
+// Icons for the built-in engines; custom engines share the generic one
+const ENGINE_ICONS: Record<string, string> = {
+  google: "🔍",
+  duckduckgo: "🦆",
+  bing: "🅱️",
+  yahoo: "🟣",
+  brave: "🦁",
+};
+
+export const engineIcon = (engine: SearchEngine) => ENGINE_ICONS[engine.id] ?? "🌐";
+
+// Engines live in settings (the Rust `settings` and `search::engines` modules), so the
+// list is reloaded on every `settings-changed` and the omnibox, suggestions and settings agree.
+export function useSearchEngines() {
+  const [engines, setEngines] = useState<SearchEngine[]>([]);
+
+  useEffect(() => {
+    const load = () => {
+      invoke<SearchEngine[]>("get_search_engines")
+        .then(setEngines)
+        .catch((error) => console.error("Failed to load search engines:", error));
+    };
+
+    load();
+    const unlistenPromise = listen("settings-changed", load);
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  // Rejects with the backend's validation error so forms can show it
+  const addSearchEngine = useCallback((engine: NewSearchEngine) => {
+    return invoke<SearchEngine>("add_search_engine", { ...engine });
+  }, []);
+
+  const removeSearchEngine = useCallback((id: string) => {
+    invoke("remove_search_engine", { id })
+      .catch((error) => console.error("Failed to remove search engine:", error));
+  }, []);
+
+  return {
+    engines,
+    addSearchEngine,
+    removeSearchEngine,
+  };
+}
+
+// A site's OpenSearch engine found while browsing, waiting for the user to add or ignore it
+export interface SearchEngineOffer {
+  descriptionUrl: string;
+  pageUrl: string;
+  windowLabel: string;
+  engine: {
+    name: string;
+    description: string | null;
+    searchUrl: string;
+    suggestUrl: string | null;
+    iconUrl: string | null;
+  };
+}
+
+// Offers come from the Rust `search::opensearch` module when a content page finishes loading
+export function useSearchEngineOffers() {
+  const [offers, setOffers] = useState<SearchEngineOffer[]>([]);
+
+  useEffect(() => {
+    invoke<SearchEngineOffer[]>("get_search_engine_offers")
+      .then(setOffers)
+      .catch((error) => console.error("Failed to load search engine offers:", error));
+
+    const unlistenPromise = listen<SearchEngineOffer>("search-engine-discovered", (event) => {
+      setOffers((current) => [
+        ...current.filter((offer) => offer.descriptionUrl !== event.payload.descriptionUrl),
+        event.payload,
+      ]);
+    });
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  const dismiss = (descriptionUrl: string) =>
+    setOffers((current) => current.filter((offer) => offer.descriptionUrl !== descriptionUrl));
+
+  const acceptOffer = useCallback((descriptionUrl: string) => {
+    dismiss(descriptionUrl);
+    invoke<SearchEngine>("accept_search_engine_offer", { descriptionUrl })
+      .catch((error) => console.error("Failed to add search engine:", error));
+  }, []);
+
+  const ignoreOffer = useCallback((descriptionUrl: string) => {
+    dismiss(descriptionUrl);
+    invoke("ignore_search_engine_offer", { descriptionUrl })
+      .catch((error) => console.error("Failed to ignore search engine:", error));
+  }, []);
+
+  return {
+    offers,
+    acceptOffer,
+    ignoreOffer,
+  };
+}
+
+export default useSearchEngines;
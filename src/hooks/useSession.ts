@@ -0,0 +1,80 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+// A session as listed by the Rust `session` module, without its windows
+export interface SessionSummary {
+  // null for the previous run's session
+  name: string | null;
+  savedAt: number;
+  windowCount: number;
+  tabCount: number;
+}
+
+export interface RestoredWindow {
+  // Content webview label of the window's active tab
+  windowLabel: string;
+  // The OS window, "window-{uuid}"
+  parentLabel: string;
+  url: string;
+}
+
+export interface RestoredSession {
+  windows: RestoredWindow[];
+  // Index into `windows` of the one to show
+  activeIndex: number;
+}
+
+// The previous run's windows, offered once at startup
+export function useSessionRestore() {
+  const [offer, setOffer] = useState<SessionSummary | null>(null);
+
+  useEffect(() => {
+    invoke<SessionSummary | null>("get_session_restore_offer")
+      .then(setOffer)
+      .catch((error) => console.error("Failed to check the last session:", error));
+  }, []);
+
+  const restore = useCallback(async () => {
+    setOffer(null);
+    return invoke<RestoredSession>("restore_last_session");
+  }, []);
+
+  const dismiss = useCallback(() => {
+    setOffer(null);
+    invoke("dismiss_session_restore")
+      .catch((error) => console.error("Failed to dismiss the last session:", error));
+  }, []);
+
+  return { offer, restore, dismiss };
+}
+
+// Sessions saved under a name
+export function useSavedSessions() {
+  const [sessions, setSessions] = useState<SessionSummary[]>([]);
+
+  const reload = useCallback(() => {
+    invoke<SessionSummary[]>("list_sessions")
+      .then(setSessions)
+      .catch((error) => console.error("Failed to load sessions:", error));
+  }, []);
+
+  useEffect(reload, [reload]);
+
+  const saveSession = useCallback(async (name: string) => {
+    const summary = await invoke<SessionSummary>("save_session", { name });
+    reload();
+    return summary;
+  }, [reload]);
+
+  const restoreSession = useCallback((name: string) =>
+    invoke<RestoredSession>("restore_session", { name }), []);
+
+  const deleteSession = useCallback(async (name: string) => {
+    await invoke("delete_session", { name });
+    reload();
+  }, [reload]);
+
+  return { sessions, saveSession, restoreSession, deleteSession };
+}
+
+export default useSessionRestore;
@@ -1,136 +1,130 @@
 import { useState, useEffect, useCallback } from "react";
-import { readTextFile, writeTextFile, exists, mkdir, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { SearchEngine } from "./useSearchEngines";
 
 export type Theme = "light" | "dark";
-export type SearchEngine = "google" | "duckduckgo" | "bing" | "yahoo" | "brave";
+
+export type PopupAction = "native-popup" | "new-tab" | "new-window" | "same-window" | "block";
+
+// Every matcher that's set has to match; the first matching rule wins
+export interface PopupRule {
+  name: string;
+  // "accounts.google.com", or "*.okta.com" for any subdomain
+  host: string | null;
+  // Path glob: * stays within a segment, ** spans segments
+  path: string | null;
+  // Regular expression searched in the whole URL
+  regex: string | null;
+  action: PopupAction;
+}
+
+export type ContainerColor = "blue" | "turquoise" | "green" | "yellow" | "orange" | "red" | "pink" | "purple";
+
+// A separate cookie and storage partition within the profile (the Rust `containers` module)
+export interface Container {
+  id: string;
+  name: string;
+  color: ContainerColor;
+}
+
+// Opens a site, subdomains included, in a container
+export interface ContainerRule {
+  // e.g. "example.com"
+  host: string;
+  containerId: string;
+}
 
 export interface Settings {
   theme: Theme;
-  searchEngine: SearchEngine;
+  // Id of a built-in or custom engine
+  searchEngine: string;
+  customSearchEngines: SearchEngine[];
+  // Description URLs of discovered site engines the user declined
+  ignoredSearchEngineOffers: string[];
   quickLinksLimit: number;
   animationsEnabled: boolean;
   hasSeenDisclaimer: boolean;
+  // Overrides the suggestion provider's host (e.g. a local mock server); null uses the engine's own
+  suggestBaseUrl: string | null;
+  // Checked before the built-in popup rules (the Rust `popup_rules` module)
+  popupRules: PopupRule[];
+  // Sites that may open popups without a click, e.g. "example.com" (subdomains included)
+  popupAllowlist: string[];
+  // Minutes before tabs of a hidden or minimized window are hibernated (0 = never)
+  hibernateHiddenWindowsAfterMinutes: number;
+  // Minutes before background tabs of a shown window are hibernated (0 = never)
+  hibernateBackgroundTabsAfterMinutes: number;
+  // Sites whose tabs are never hibernated, e.g. chat apps (subdomains included)
+  neverHibernate: string[];
+  // Edited through useContainers
+  containers: Container[];
+  // Sites that always open in a container
+  containerRules: ContainerRule[];
 }
 
-const SETTINGS_FILE = "settings.json";
-
+// Used until the backend answers; the Rust `settings` module owns the real values
 const DEFAULT_SETTINGS: Settings = {
   theme: "light",
   searchEngine: "google",
+  customSearchEngines: [],
+  ignoredSearchEngineOffers: [],
   quickLinksLimit: 6,
   animationsEnabled: true,
   hasSeenDisclaimer: false,
+  suggestBaseUrl: null,
+  popupRules: [],
+  popupAllowlist: [],
+  hibernateHiddenWindowsAfterMinutes: 30,
+  hibernateBackgroundTabsAfterMinutes: 60,
+  neverHibernate: [],
+  containers: [],
+  containerRules: [],
 };
 
-// Search engine URL templates
-export const SEARCH_ENGINES: Record<SearchEngine, { name: string; url: string; icon: string }> = {
-  google: {
-    name: "Google",
-    url: "https://www.google.com/search?q=",
-    icon: "🔍",
-  },
-  duckduckgo: {
-    name: "DuckDuckGo",
-    url: "https://duckduckgo.com/?q=",
-    icon: "🦆",
-  },
-  bing: {
-    name: "Bing",
-    url: "https://www.bing.com/search?q=",
-    icon: "🅱️",
-  },
-  yahoo: {
-    name: "Yahoo",
-    url: "https://search.yahoo.com/search?p=",
-    icon: "🟣",
-  },
-  brave: {
-    name: "Brave",
-    url: "https://search.brave.com/search?q=",
-    icon: "🦁",
-  },
+// Applies the theme to this window's document
+const applyTheme = (theme: Theme) => {
+  document.documentElement.setAttribute("data-theme", theme);
 };
 
-async function loadSettingsFromFile(): Promise<Settings> {
-  try {
-    const fileExists = await exists(SETTINGS_FILE, { baseDir: BaseDirectory.AppData });
-    
-    if (!fileExists) {
-      await saveSettingsToFile(DEFAULT_SETTINGS);
-      return DEFAULT_SETTINGS;
-    }
-    
-    const content = await readTextFile(SETTINGS_FILE, { baseDir: BaseDirectory.AppData });
-    const loaded = JSON.parse(content);
-    // Merge with defaults to handle new settings
-    const merged = { ...DEFAULT_SETTINGS, ...loaded };
-    return merged;
-  } catch (error) {
-    return DEFAULT_SETTINGS;
-  }
-}
-
-async function saveSettingsToFile(settings: Settings): Promise<void> {
-  try {
-    // Check if directory exists, create if not
-    try {
-      const dirExists = await exists("", { baseDir: BaseDirectory.AppData });
-      if (!dirExists) {
-        await mkdir("", { baseDir: BaseDirectory.AppData, recursive: true });
-      }
-    } catch {
-      // Directory check/create may fail if already exists
-    }
-    
-    const content = JSON.stringify(settings, null, 2);
-    await writeTextFile(SETTINGS_FILE, content, { baseDir: BaseDirectory.AppData });
-  } catch {
-    // Settings save failed silently
-  }
-}
-
+// Settings are validated, migrated and persisted by the Rust `settings` module.
+// Every change is broadcast as `settings-changed`, so all windows stay in sync.
 export function useSettings() {
   const [settings, setSettings] = useState<Settings>(DEFAULT_SETTINGS);
   const [isLoading, setIsLoading] = useState(true);
 
-  // Load settings on mount
+  // Load settings on mount and follow changes made from any window
   useEffect(() => {
-    loadSettingsFromFile().then((loaded) => {
-      setSettings(loaded);
-      setIsLoading(false);
-      // Apply theme on load
-      applyTheme(loaded.theme);
+    const apply = (next: Settings) => {
+      setSettings(next);
+      applyTheme(next.theme);
+    };
+
+    invoke<Settings>("get_settings")
+      .then(apply)
+      .catch((error) => console.error("Failed to load settings:", error))
+      .finally(() => setIsLoading(false));
+
+    const unlistenPromise = listen<Settings>("settings-changed", (event) => {
+      apply(event.payload);
     });
-  }, []);
 
-  // Save to file whenever settings change (after initial load)
-  useEffect(() => {
-    if (!isLoading) {
-      saveSettingsToFile(settings);
-    }
-  }, [settings, isLoading]);
-
-  // Apply theme to document
-  const applyTheme = useCallback((theme: Theme) => {
-    document.documentElement.setAttribute("data-theme", theme);
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
   }, []);
 
   const updateSetting = useCallback(<K extends keyof Settings>(key: K, value: Settings[K]) => {
-    setSettings((prev) => {
-      const newSettings = { ...prev, [key]: value };
-      if (key === "theme") {
-        applyTheme(value as Theme);
-      }
-      return newSettings;
-    });
-  }, [applyTheme]);
+    invoke<Settings>("set_setting", { key, value })
+      .catch((error) => console.error(`Failed to update ${key}:`, error));
+  }, []);
 
   const setTheme = useCallback((theme: Theme) => {
     updateSetting("theme", theme);
   }, [updateSetting]);
 
-  const setSearchEngine = useCallback((engine: SearchEngine) => {
-    updateSetting("searchEngine", engine);
+  const setSearchEngine = useCallback((engineId: string) => {
+    updateSetting("searchEngine", engineId);
   }, [updateSetting]);
 
   const setQuickLinksLimit = useCallback((limit: number) => {
@@ -146,15 +140,9 @@ export function useSettings() {
   }, [updateSetting]);
 
   const resetSettings = useCallback(() => {
-    setSettings(DEFAULT_SETTINGS);
-    applyTheme(DEFAULT_SETTINGS.theme);
-  }, [applyTheme]);
-
-  // Get search URL for a query
-  const getSearchUrl = useCallback((query: string) => {
-    const engine = SEARCH_ENGINES[settings.searchEngine];
-    return engine.url + encodeURIComponent(query);
-  }, [settings.searchEngine]);
+    invoke("reset_settings")
+      .catch((error) => console.error("Failed to reset settings:", error));
+  }, []);
 
   return {
     settings,
@@ -165,7 +153,6 @@ export function useSettings() {
     setAnimationsEnabled,
     setHasSeenDisclaimer,
     resetSettings,
-    getSearchUrl,
     updateSetting,
   };
 }
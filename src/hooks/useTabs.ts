@@ -0,0 +1,138 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Container } from "./useSettings";
+
+// Page state of one tab, as emitted by the Rust `url_monitor` module
+export interface Tab {
+  // The tab's content webview label
+  windowLabel: string;
+  url: string;
+  title: string;
+  faviconUrl: string | null;
+  loading: "started" | "finished";
+  isSecure: boolean;
+  canGoBack: boolean;
+  canGoForward: boolean;
+  // In a private window: kept out of history and sessions
+  private: boolean;
+}
+
+export type SplitOrientation = "columns" | "rows";
+
+// Tabs of one window shown at once, from the Rust `split_view` module
+export interface SplitLayout {
+  orientation: SplitOrientation;
+  // Tab labels, left to right or top to bottom
+  panes: string[];
+  // Share of the window each pane gets, adding up to 1
+  shares: number[];
+}
+
+// Payload of `tabs-changed`: the tabs of one browser window, in order
+export interface TabStrip {
+  // The OS window, "window-{uuid}"
+  parentLabel: string;
+  tabs: Tab[];
+  activeLabel: string;
+  split: SplitLayout | null;
+  // Tabs whose webview was destroyed to save memory; they reload when shown
+  hibernatedLabels: string[];
+  // Opened with create_private_window
+  private: boolean;
+  // Profile the window belongs to
+  profileId: string;
+  // Container the window's tabs keep cookies and storage in
+  container: Container | null;
+}
+
+// Tabs of the browser window that `tabLabel` belongs to, kept in sync with the Rust `tabs` module
+export function useTabs(tabLabel: string | null) {
+  const [strip, setStrip] = useState<TabStrip | null>(null);
+
+  useEffect(() => {
+    if (!tabLabel) {
+      setStrip(null);
+      return;
+    }
+
+    invoke<TabStrip>("get_tabs", { windowLabel: tabLabel })
+      .then(setStrip)
+      .catch((error) => console.error("Failed to load tabs:", error));
+
+    const unlistenTabs = listen<TabStrip>("tabs-changed", (event) => {
+      setStrip((current) => {
+        const ours = event.payload.tabs.some((tab) => tab.windowLabel === tabLabel)
+          || event.payload.parentLabel === current?.parentLabel;
+        return ours ? event.payload : current;
+      });
+    });
+    // Titles and URLs change without the tab list changing
+    const unlistenPage = listen<Tab>("page-state", (event) => {
+      setStrip((current) => current && {
+        ...current,
+        tabs: current.tabs.map((tab) => tab.windowLabel === event.payload.windowLabel ? event.payload : tab),
+      });
+    });
+
+    return () => {
+      unlistenTabs.then((unlisten) => unlisten());
+      unlistenPage.then((unlisten) => unlisten());
+    };
+  }, [tabLabel]);
+
+  const activateTab = useCallback((label: string) => {
+    invoke("activate_tab", { tabLabel: label })
+      .catch((error) => console.error("Failed to switch tab:", error));
+  }, []);
+
+  const closeTab = useCallback((label: string) => {
+    invoke("close_tab", { tabLabel: label })
+      .catch((error) => console.error("Failed to close tab:", error));
+  }, []);
+
+  const moveTab = useCallback((label: string, index: number) => {
+    invoke("move_tab", { tabLabel: label, index })
+      .catch((error) => console.error("Failed to move tab:", error));
+  }, []);
+
+  const enterSplitView = useCallback((orientation: SplitOrientation, tabLabels?: string[]) => {
+    if (!tabLabel) return;
+    invoke("enter_split_view", { windowLabel: tabLabel, orientation, tabLabels: tabLabels ?? null })
+      .catch((error) => console.error("Failed to split view:", error));
+  }, [tabLabel]);
+
+  const leaveSplitView = useCallback(() => {
+    if (!tabLabel) return;
+    invoke("leave_split_view", { windowLabel: tabLabel })
+      .catch((error) => console.error("Failed to leave split view:", error));
+  }, [tabLabel]);
+
+  const swapSplitPanes = useCallback((first: number, second: number) => {
+    if (!tabLabel) return;
+    invoke("swap_split_panes", { windowLabel: tabLabel, first, second })
+      .catch((error) => console.error("Failed to swap panes:", error));
+  }, [tabLabel]);
+
+  const resizeSplitDivider = useCallback((divider: number, position: number) => {
+    if (!tabLabel) return;
+    invoke("resize_split_divider", { windowLabel: tabLabel, divider, position })
+      .catch((error) => console.error("Failed to resize split view:", error));
+  }, [tabLabel]);
+
+  return {
+    tabs: strip?.tabs ?? [],
+    activeLabel: strip?.activeLabel ?? null,
+    split: strip?.split ?? null,
+    hibernatedLabels: strip?.hibernatedLabels ?? [],
+    activateTab,
+    closeTab,
+    moveTab,
+    enterSplitView,
+    leaveSplitView,
+    swapSplitPanes,
+    resizeSplitDivider,
+  };
+}
+
+export default useTabs;